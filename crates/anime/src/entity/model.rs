@@ -1,7 +1,6 @@
 use chrono::NaiveDate;
 use common::shared::error::Error;
 use serde::{Deserialize, Serialize};
-use unicode_normalization::UnicodeNormalization;
 
 /// 番剧列表查询过滤条件。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -130,58 +129,7 @@ impl AnimeTitle {
 
     /// 将番剧别名处理为仅包含CJK字符的搜索关键字
     pub fn to_keywords(input: &str) -> Vec<String> {
-        let mut tokens = Vec::with_capacity(input.len());
-        let mut current_word = String::new();
-
-        // 1. nfkc() 迭代器会自动处理 Unicode 标准化
-        // 这会将全角的 "ＡＢＣ" 转为 "ABC"，全角数字 "１" 转为 "1"
-        for c in input.nfkc() {
-            if c.is_alphanumeric() {
-                if Self::is_cjk(c) {
-                    // 遇到中日韩字符，先将前面累积的英文/数字 word 刷入 token
-                    if !current_word.is_empty() {
-                        tokens.push(current_word.clone());
-                        current_word.clear();
-                    }
-                    // 中日韩字符独立作为一个 token
-                    tokens.push(c.to_string());
-                } else {
-                    // 非中日韩字符（英文、数字、俄文、法文等），全部转小写并累积成单词
-                    for lower_c in c.to_lowercase() {
-                        current_word.push(lower_c);
-                    }
-                }
-            } else {
-                // is_alphanumeric 为 false 代表遇到了标点符号、空格或特殊符号
-                // 此时作为单词的天然分割点，将累积的 word 刷入，并丢弃该符号
-                if !current_word.is_empty() {
-                    tokens.push(current_word.clone());
-                    current_word.clear();
-                }
-            }
-        }
-
-        // 处理字符串末尾可能遗留的英文/数字单词
-        if !current_word.is_empty() {
-            tokens.push(current_word);
-        }
-        tokens
-    }
-
-    /// 基于 Unicode 码位判断是否为中日韩字符 (汉字、假名、谚文、注音等)
-    fn is_cjk(c: char) -> bool {
-        let u = c as u32;
-        matches!(
-            u,
-            // CJK 统一表意文字及扩展 (涵盖简繁体及绝大部分生僻字)
-            0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x20000..=0x2A6DF |
-            // 日文平假名、片假名、片假名语音扩展
-            0x3040..=0x309F | 0x30A0..=0x30FF | 0x31F0..=0x31FF |
-            // 韩文谚文音节及字母
-            0xAC00..=0xD7A3 | 0x1100..=0x11FF | 0x3130..=0x318F |
-            // 台湾注音符号
-            0x3100..=0x312F
-        )
+        common::shared::str::to_fts_tokens(input)
     }
 }
 
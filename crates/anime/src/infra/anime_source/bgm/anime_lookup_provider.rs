@@ -69,7 +69,7 @@ impl AnimeLookupProvider for BgmClient {
             .and_then(|v| AnimeAirWeekday::try_from(v).ok())
             .context(anyhow!(
                 "bgm lookup parse {} air weekday failed",
-                &subject.name
+                subject.name
             ))?;
 
         let air_date = subject
@@ -82,10 +82,7 @@ impl AnimeLookupProvider for BgmClient {
                     .and_then(|v| v.as_str())
                     .and_then(|v| NaiveDate::parse_from_str(v, "%Y年%m月%d日").ok())
             })
-            .context(anyhow!(
-                "bgm lookup parse {} air date failed",
-                &subject.name
-            ))?;
+            .context(anyhow!("bgm lookup parse {} air date failed", subject.name))?;
         let (year, month) = Self::season_of_date(&air_date)?;
 
         let tmdb_season = self.tmdb.get_anime_season(&tmdb_data, air_date).await?;
@@ -130,7 +130,7 @@ impl BgmClient {
         if let Some(error) = error {
             Err(error)
         } else {
-            Err(anyhow!("not found {} in tmdb", &original_title.name))
+            Err(anyhow!("not found {} in tmdb", original_title.name))
         }
     }
 
@@ -52,6 +52,9 @@ pub struct AppConfig {
     pub external: ExternalConfig,
     /// 工作/数据目录路径
     pub data_dir: String,
+    /// 资源保留策略
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 impl AppConfig {
@@ -111,6 +114,9 @@ impl AppConfig {
         if config.data_dir.is_empty() {
             anyhow::bail!("data_dir is required and cannot be empty");
         }
+        if config.retention.interval_hours == 0 {
+            anyhow::bail!("retention.interval_hours must be greater than 0");
+        }
 
         Ok(config)
     }
@@ -157,6 +163,9 @@ pub struct AuthConfig {
     pub jwt_expire_seconds: u64,
     /// 用于加密敏感信息 (如下载配置密码) 的主密钥
     pub crypto_secret: String,
+    /// 外部推送接口的访问令牌，供爬虫等推送资源使用，为空时只接受管理员的 JWT Token
+    #[serde(default)]
+    pub push_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -165,3 +174,34 @@ pub struct ExternalConfig {
     /// TMDB API 读取权限的 Token
     pub tmdb_token: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// 资源保留天数，按发布时间计算，为空时不按时间清理
+    pub max_age_days: Option<u64>,
+    /// 资源最多保留条数，为空时不限制
+    pub max_rows: Option<u64>,
+    /// 清理后是否执行 VACUUM 回收磁盘空间
+    pub vacuum: bool,
+    /// 清理任务的执行间隔 (单位：小时)
+    pub interval_hours: u64,
+}
+
+impl RetentionConfig {
+    /// 未配置任何清理条件时不注册清理任务
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_rows.is_some()
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_rows: None,
+            vacuum: false,
+            interval_hours: 24,
+        }
+    }
+}
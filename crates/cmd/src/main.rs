@@ -40,6 +40,7 @@ async fn init(
             token: config.auth.jwt_secret.clone(),
             expire: std::time::Duration::from_secs(config.auth.jwt_expire_seconds),
             crypto_secret: config.auth.crypto_secret.clone(),
+            push_token: config.auth.push_token.clone(),
         },
         config.external.tmdb_token.clone(),
         config.data_dir.clone(),
@@ -50,7 +51,7 @@ async fn init(
 
     ctx.init_database().await;
 
-    let scheduler = cmd::task::builder::setup(
+    let mut scheduler = cmd::task::builder::setup(
         ctx.roots.users.clone(),
         ctx.roots.animes.clone(),
         ctx.roots.anime_source.clone(),
@@ -61,6 +62,12 @@ async fn init(
     )
     .await
     .unwrap();
+    cmd::task::builder::setup_retention(
+        &mut scheduler,
+        ctx.roots.resources.clone(),
+        ctx.roots.sub_animes.clone(),
+        config.retention.clone(),
+    );
     scheduler.start();
 
     let app = web::router::route(ctx.clone());
@@ -10,12 +10,16 @@ use subscription::entity::{search_mandates::SearchMandates, sub_animes::SubAnime
 use tracing::info;
 use user::entity::users::Users;
 
-use crate::task::{
-    check_missing_episodes_task::check_missing_episodes_task,
-    download_task::download_task,
-    get_resource_task::get_resource_and_match_task,
-    search_task::{local_match_task, search_task},
-    sync_calendar_task::sync_calendar_task,
+use crate::{
+    config::RetentionConfig,
+    task::{
+        check_missing_episodes_task::check_missing_episodes_task,
+        download_task::download_task,
+        get_resource_task::get_resource_and_match_task,
+        prune_resource_task::prune_resource_task,
+        search_task::{local_match_task, search_task},
+        sync_calendar_task::sync_calendar_task,
+    },
 };
 
 pub async fn setup(
@@ -59,7 +63,8 @@ pub async fn setup(
     scheduer.register(
         TaskConfig {
             name: "get resource task".to_string(),
-            interval: Duration::from_mins(5),
+            // 各 feed 按自身的轮询间隔判断是否到期，这里只决定检查频率
+            interval: Duration::from_mins(1),
             allow_reentry: false,
         },
         move || {
@@ -161,3 +166,32 @@ pub async fn setup(
 
     Ok(scheduer)
 }
+
+/// 注册资源清理任务，未配置任何清理条件时跳过
+pub fn setup_retention(
+    scheduer: &mut TaskScheduler,
+    resources: Resources,
+    sub_animes: SubAnimes,
+    retention: RetentionConfig,
+) {
+    if !retention.is_enabled() {
+        return;
+    }
+    scheduer.register(
+        TaskConfig {
+            name: "prune resource task".to_string(),
+            interval: Duration::from_hours(retention.interval_hours),
+            allow_reentry: false,
+        },
+        move || {
+            let resources = resources.clone();
+            let sub_animes = sub_animes.clone();
+            let retention = retention.clone();
+            async move {
+                if let Err(e) = prune_resource_task(resources, sub_animes, retention).await {
+                    tracing::error!("prune resource task failed, {}", e);
+                }
+            }
+        },
+    );
+}
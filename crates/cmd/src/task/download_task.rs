@@ -23,6 +23,10 @@ pub async fn download_task(sub_animes: SubAnimes, users: Users) -> Result<()> {
         let sub_anime_eps = sub_animes.as_eps(&sub_anime_entity).await;
         if epsiode_entity.download(&downloader).await? {
             sub_anime_eps.save_epsiode(&epsiode_entity).await?;
+            // 修正版下载后按配置删除被替换的旧任务，失败不影响本次下载
+            if let Err(e) = epsiode_entity.remove_superseded(&downloader).await {
+                tracing::warn!("{}", e);
+            }
         }
     };
 
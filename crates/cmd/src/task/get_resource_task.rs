@@ -1,134 +1,92 @@
 use anyhow::Result;
-use chrono::{Duration, Local};
-use feed::entity::{feeds::Feeds, model::FeedFetchResult};
-use futures::StreamExt;
+use chrono::{Duration, Local, Utc};
+use feed::entity::{feed_entity::FeedEntity, feeds::Feeds, model::FeedFetchResult};
+use futures::{StreamExt, stream};
 use resource::entity::{model::ResourceQuery, resources::Resources};
-use subscription::entity::{
-    model::{SubAnimeListQuery, SubAnimeSearchStatus, SubAnimeStatus},
-    sub_animes::SubAnimes,
-};
+use subscription::entity::sub_animes::SubAnimes;
 use tracing::error;
 
+/// 同时请求的 feed 数量上限
+const FEED_FETCH_CONCURRENCY: usize = 4;
+
 pub async fn get_resource_and_match_task(
     feeds: Feeds,
     resources: Resources,
     sub_animes: SubAnimes,
 ) -> Result<()> {
-    let feed_entity_list = feeds.list_site_feeds().await?;
-    for feed_entity in &feed_entity_list {
-        match feed_entity.list().await {
-            Ok(res) => match res {
-                FeedFetchResult::Success(res) => {
-                    if let Err(e) = resources.just_save(res).await {
-                        error!(
-                            "get resource task save {} data failed, {}",
-                            feed_entity.id(),
-                            e
-                        );
-                    }
-                }
-                FeedFetchResult::Retryable(e) => {
-                    tracing::warn!(
-                        "get resource task get {} feed retryable failed, {}",
-                        feed_entity.id(),
-                        e
-                    );
-                }
-                FeedFetchResult::Failure(e) => {
-                    tracing::error!(
-                        "get resource task get {} feed failed, {}",
-                        feed_entity.id(),
-                        e
-                    );
-                }
-                FeedFetchResult::Denied => {
-                    tracing::warn!("get resource task get {} feed denied", feed_entity.id());
-                }
-            },
-            Err(e) => {
-                error!(
-                    "get resource task get {} feed failed, {}",
-                    feed_entity.id(),
-                    e
-                );
-            }
-        }
-    }
+    let now_ts = Utc::now().timestamp();
+    let due_feeds = feeds
+        .list_site_feeds()
+        .await?
+        .into_iter()
+        .filter(|i| i.is_due(now_ts))
+        .collect::<Vec<_>>();
 
-    // 资源获取之后，无论是否获取到新资源，都执行匹配
-    let sub_anime_entity_list = sub_animes
-        .list(&SubAnimeListQuery {
-            anime_id: None,
-            space_id: None,
-            search_status: Some(SubAnimeSearchStatus::NotSearch),
-            sub_status: Some(SubAnimeStatus::Enable),
-            limit: None,
+    // 并发请求到期的 feed，单个站点的慢响应不会阻塞其它 feed
+    stream::iter(due_feeds)
+        .for_each_concurrent(FEED_FETCH_CONCURRENCY, |feed_entity| {
+            let resources = resources.clone();
+            async move { fetch_feed(&feed_entity, &resources).await }
         })
-        .await?;
-    if sub_anime_entity_list.is_empty() {
-        return Ok(());
-    }
-
-    let mut matchers = vec![];
-    for i in &sub_anime_entity_list {
-        let matcher = match sub_animes.as_matcher(i).await {
-            Ok(v) => v,
-            Err(e) => {
-                error!(
-                    "get resource made {} entity to matcher failed, {}",
-                    i.id(),
-                    e
-                );
-                continue;
-            }
-        };
-        matchers.push(matcher);
-    }
-
-    let now = Local::now();
-    let start_at = now - Duration::hours(3);
+        .await;
 
+    // 资源获取之后，无论是否获取到新资源，都执行匹配
+    let start_at = Local::now() - Duration::hours(3);
     let query = ResourceQuery {
         keywords: None,
+        any_titles: None,
         start_at: Some(start_at.timestamp()),
         end_at: None,
         limit: None,
         offset: None,
     };
-    let mut stream = resources.stream(&query);
-    while let Some(res) = stream.next().await {
-        for matcher in &mut matchers {
-            match &res {
-                Ok(res) => {
-                    if let Err(e) = matcher.match_resource(res) {
-                        tracing::error!(
-                            "get resource match task {} match {} failed, {}",
-                            matcher.sub_anime_id(),
-                            res.title(),
-                            e
-                        );
-                    }
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "lget resource match task {} get res failed, {}",
-                        matcher.sub_anime_id(),
+    sub_animes.match_active(resources.stream(&query)).await?;
+
+    Ok(())
+}
+
+async fn fetch_feed(feed_entity: &FeedEntity, resources: &Resources) {
+    match feed_entity.list().await {
+        Ok(res) => match res {
+            FeedFetchResult::Success(data) => {
+                let saved = match resources.just_save(feed_entity.id(), data.items).await {
+                    Ok(_) => resources.record_seen(feed_entity.id(), data.seen).await,
+                    Err(e) => Err(e),
+                };
+                match saved {
+                    Ok(_) => feed_entity.commit(data.cache.as_ref()).await,
+                    Err(e) => error!(
+                        "get resource task save {} data failed, {}",
+                        feed_entity.id(),
                         e
-                    );
+                    ),
                 }
             }
-        }
-    }
-
-    for matcher in matchers {
-        if let Err(e) = sub_animes.save_matcher(&matcher).await {
+            FeedFetchResult::Retryable(e) => {
+                tracing::warn!(
+                    "get resource task get {} feed retryable failed, {}",
+                    feed_entity.id(),
+                    e
+                );
+            }
+            FeedFetchResult::Failure(e) => {
+                tracing::error!(
+                    "get resource task get {} feed failed, {}",
+                    feed_entity.id(),
+                    e
+                );
+            }
+            FeedFetchResult::Denied => {
+                // 退避中或未满足请求间隔，属于正常调度
+                tracing::debug!("get resource task get {} feed denied", feed_entity.id());
+            }
+        },
+        Err(e) => {
             error!(
-                "get resource match task save {} matcher failed, {}",
-                matcher.sub_anime_id(),
+                "get resource task get {} feed failed, {}",
+                feed_entity.id(),
                 e
             );
         }
     }
-
-    Ok(())
 }
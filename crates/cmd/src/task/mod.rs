@@ -2,5 +2,6 @@ pub mod builder;
 mod check_missing_episodes_task;
 mod download_task;
 mod get_resource_task;
+mod prune_resource_task;
 mod search_task;
 mod sync_calendar_task;
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use resource::entity::{model::ResourcePrunePolicy, resources::Resources};
+use subscription::entity::sub_animes::SubAnimes;
+use tracing::info;
+
+use crate::config::RetentionConfig;
+
+/// 最近一次出现时间距所属订阅源最近一次列出条目不超过此天数的条目视为仍被列出
+const LISTED_WINDOW_DAYS: i64 = 7;
+
+pub async fn prune_resource_task(
+    resources: Resources,
+    sub_animes: SubAnimes,
+    config: RetentionConfig,
+) -> Result<()> {
+    let now = Utc::now();
+    let policy = ResourcePrunePolicy {
+        published_before: config
+            .max_age_days
+            .map(|days| (now - Duration::days(days as i64)).timestamp()),
+        max_rows: config.max_rows,
+        listed_window: Some(Duration::days(LISTED_WINDOW_DAYS).num_seconds()),
+    };
+    // 被订阅剧集引用的资源需要保留，否则剧集列表及下载会丢失资源信息
+    let keep = sub_animes.referenced_resource_ids().await?;
+    let report = resources.prune(&policy, &keep).await?;
+    info!(
+        "prune resource task removed {} resources, {} url hashes, {} files",
+        report.resources, report.url_hashes, report.files
+    );
+
+    if config.vacuum && report.resources > 0 {
+        resources.vacuum().await?;
+        info!("prune resource task vacuumed database");
+    }
+    Ok(())
+}
@@ -58,8 +58,12 @@ pub async fn local_match_task(
         let mut matcher = sub_animes.as_matcher(&sub_anime_entity).await?;
         let query = ResourceQuery {
             keywords: None,
+            // 经标题全文索引预筛，避免逐条扫描整个时间范围内的资源
+            any_titles: Some(sub_anime_entity.titles().to_vec()),
             start_at: Some(time_range.start.and_utc().timestamp()),
-            end_at: Some(time_range.end.and_utc().timestamp()),
+            // 合集通常在完结后发布，接受合集时不限制结束时间
+            end_at: (!sub_anime_entity.accept_collection())
+                .then(|| time_range.end.and_utc().timestamp()),
             limit: None,
             offset: None,
         };
@@ -138,7 +142,7 @@ pub async fn search_task(
                 return Ok(());
             }
             feed::entity::model::FeedFetchResult::Denied => {
-                tracing::error!(
+                tracing::debug!(
                     "search task fetch {} mandate denied by policy, will retry",
                     mandate_entity.id()
                 );
@@ -166,7 +170,11 @@ pub async fn search_task(
                 search_mandates.drop(mandate_entity).await?
             }
             Success(data) => {
-                let res = resources.save(data).await?;
+                let res = resources.save(mandate_entity.feed_id(), data.items).await?;
+                resources
+                    .record_seen(mandate_entity.feed_id(), data.seen)
+                    .await?;
+                mandate_entity.commit(data.cache.as_ref()).await;
                 for entity in &sub_anime_entity_list {
                     if let Ok(mut matcher) = sub_animes.as_matcher(entity).await {
                         for res_item in &res {
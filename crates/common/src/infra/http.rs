@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+use reqwest::ClientBuilder;
+
+/// 对外请求使用的 User-Agent
+pub const USER_AGENT: &str = "yanami/1.0";
+/// 对外请求的超时时间
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 带有统一基础设置的 client 构造器，默认 client 与代理 client 均由此构建
+pub fn base_client_builder() -> ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+}
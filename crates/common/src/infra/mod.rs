@@ -1 +1,4 @@
 pub mod app_ctx;
+pub mod http;
+pub mod schema;
+pub mod torrent_store;
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use sqlx::{AssertSqlSafe, Sqlite, Transaction};
+
+/// 为已存在的表补充新增列。
+///
+/// 建表语句使用 `CREATE TABLE IF NOT EXISTS`，旧数据库中的表不会随之更新，
+/// 因此新增列需在建表后再调用本函数，列已存在时不做任何操作。
+/// 返回本次是否新增了该列，调用方可据此回填旧数据。
+pub async fn add_column_if_missing(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &'static str,
+    column: &'static str,
+    definition: &'static str,
+) -> Result<bool> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pragma_table_info(?) WHERE name = ?)")
+            .bind(table)
+            .bind(column)
+            .fetch_one(&mut **tx)
+            .await?;
+    if exists {
+        return Ok(false);
+    }
+
+    sqlx::query(AssertSqlSafe(format!(
+        "ALTER TABLE {} ADD COLUMN {} {}",
+        table, column, definition
+    )))
+    .execute(&mut **tx)
+    .await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn adds_missing_column_once() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t (id) VALUES (1)")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        assert!(
+            add_column_if_missing(&mut tx, "t", "kind", "INTEGER NOT NULL DEFAULT 0")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !add_column_if_missing(&mut tx, "t", "kind", "INTEGER NOT NULL DEFAULT 0")
+                .await
+                .unwrap()
+        );
+
+        let kind: i64 = sqlx::query_scalar("SELECT kind FROM t WHERE id = 1")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(kind, 0);
+    }
+}
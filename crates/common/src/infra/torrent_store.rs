@@ -0,0 +1,90 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+
+use crate::shared::{cap::TorrentStore, error::Error};
+
+/// 种子文件在数据目录下的存放目录
+const TORRENT_DIR: &str = "torrents";
+
+/// 基于本地文件系统的种子存储，按 info hash 内容寻址：
+/// `{data_dir}/torrents/{hash[0..2]}/{hash}.torrent`
+#[derive(Clone)]
+pub struct FsTorrentStore {
+    dir: PathBuf,
+}
+
+impl FsTorrentStore {
+    pub fn new(data_dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: data_dir.as_ref().join(TORRENT_DIR),
+        }
+    }
+
+    fn path_of(&self, hash: [u8; 20]) -> PathBuf {
+        let name = hex::encode(hash);
+        self.dir.join(&name[..2]).join(format!("{}.torrent", name))
+    }
+}
+
+#[async_trait]
+impl TorrentStore for FsTorrentStore {
+    async fn save(&self, hash: [u8; 20], bytes: &[u8]) -> Result<(), Error> {
+        let path = self.path_of(hash);
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+        let dir = path.parent().expect("torrent path must have parent");
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| Error::external("create torrent dir failed", e))?;
+
+        // 先写临时文件再重命名，避免并发读取到不完整的种子
+        let tmp = path.with_extension("torrent.tmp");
+        tokio::fs::write(&tmp, bytes)
+            .await
+            .map_err(|e| Error::external("write torrent file failed", e))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| Error::external("rename torrent file failed", e))?;
+        Ok(())
+    }
+
+    async fn load(&self, hash: [u8; 20]) -> Result<Option<Vec<u8>>, Error> {
+        match tokio::fs::read(self.path_of(hash)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::external("read torrent file failed", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsTorrentStore::new(dir.path());
+        let hash = [0xabu8; 20];
+
+        assert_eq!(store.load(hash).await.unwrap(), None);
+        store.save(hash, b"d4:infod4:name1:aee").await.unwrap();
+        // 重复保存不覆盖已有文件
+        store.save(hash, b"other").await.unwrap();
+        assert_eq!(
+            store.load(hash).await.unwrap().as_deref(),
+            Some(&b"d4:infod4:name1:aee"[..])
+        );
+        assert!(
+            dir.path()
+                .join("torrents/ab")
+                .join(format!("{}.torrent", hex::encode(hash)))
+                .exists()
+        );
+    }
+}
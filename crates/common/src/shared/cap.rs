@@ -1,12 +1,32 @@
 use async_trait::async_trait;
 
-use crate::shared::{error::Error, model::SearchUrls};
+use crate::shared::{
+    error::Error,
+    model::{SearchQuery, SearchUrls},
+};
 
 pub trait FeedSearchUrlProvider: Send + Sync {
-    fn made_search_url(&self, keywords: &[String]) -> Vec<SearchUrls>;
+    fn made_search_url(&self, queries: &[SearchQuery]) -> Vec<SearchUrls>;
 }
 
 #[async_trait]
 pub trait Downloader: Send + Sync {
     async fn download(&self, url: &str, path: &str, hash: [u8; 20]) -> Result<bool, Error>;
+    /// 删除已被修正版替换的旧任务，未开启该选项或任务不存在时返回 false
+    async fn remove_superseded(&self, hash: [u8; 20]) -> Result<bool, Error>;
+}
+
+/// 以 info hash 为键的种子文件存储
+#[async_trait]
+pub trait TorrentStore: Send + Sync {
+    /// 保存种子文件，相同 info hash 的文件已存在时直接跳过
+    async fn save(&self, hash: [u8; 20], bytes: &[u8]) -> Result<(), Error>;
+    /// 读取种子文件，不存在时返回 None
+    async fn load(&self, hash: [u8; 20]) -> Result<Option<Vec<u8>>, Error>;
+}
+
+/// 敏感配置的加解密
+pub trait CryptoProvider: Send + Sync {
+    fn encrypt(&self, plain: &str) -> anyhow::Result<String>;
+    fn decrypt(&self, cipher: &str) -> anyhow::Result<String>;
 }
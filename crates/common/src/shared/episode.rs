@@ -0,0 +1,244 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::shared::str::nfkc_lowercase;
+
+/// 标题中的集数，单集与合集区间互斥
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EpisodeNumber {
+    /// 单集集数，总集篇等可能为小数
+    Single(f64),
+    /// 集数区间（闭区间）
+    Range(u32, u32),
+}
+
+/// 从资源标题解析集数，订阅源入库、资源发布信息及订阅匹配共用
+pub fn parse_episode_number(title: &str) -> Option<EpisodeNumber> {
+    let normalized = normalize_brackets(&nfkc_lowercase(title, true));
+    let (_, rest) = split_group(&normalized, &normalized);
+    episode_number(&prepare_tokens(rest))
+}
+
+/// 判断是否为合集/打包类资源，返回命中的标题片段，用于说明资源被识别为合集的原因；
+/// 除合集关键词外，标题中能识别出集数区间的同样视为合集
+pub fn collection_marker(title: &str) -> Option<String> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // 只有复数 Seasons 1-2 才算，排除单独的 Season 1
+    let re = regex(
+        &RE,
+        r"(?i:\b(?:Batch|Complete|Collection|Seasons?\s*\d+\s*[-~至]\s*\d+)\b|合集|全集|全套|全话|整季|打包)",
+    );
+    if let Some(m) = re.find(title) {
+        return Some(m.as_str().trim().to_string());
+    }
+    match parse_episode_number(title)? {
+        EpisodeNumber::Range(start, end) => Some(format!("{:02}-{:02}", start, end)),
+        EpisodeNumber::Single(_) => None,
+    }
+}
+
+/// 解析中文数字，支持「十二」「二十四」「一百零五」等三位以内的写法
+pub fn cjk_number(s: &str) -> Option<u32> {
+    let digit = |c: char| "零一二三四五六七八九".chars().position(|d| d == c);
+    if let Some((hundreds, rest)) = s.split_once('百') {
+        let [h] = hundreds.chars().collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let rest = match rest.strip_prefix('零') {
+            Some(ones) if ones.chars().count() == 1 => ones,
+            Some(_) => return None,
+            None => rest,
+        };
+        let tail = if rest.is_empty() {
+            0
+        } else {
+            cjk_number(rest)?
+        };
+        return Some(digit(h)? as u32 * 100 + tail);
+    }
+    let chars = s.chars().collect::<Vec<_>>();
+    match chars.as_slice() {
+        [c] if *c == '十' => Some(10),
+        [c] => digit(*c).map(|v| v as u32),
+        ['十', c] => digit(*c).map(|v| 10 + v as u32),
+        [t, '十'] => digit(*t).map(|v| v as u32 * 10),
+        [t, '十', c] => Some(digit(*t)? as u32 * 10 + digit(*c)? as u32),
+        _ => None,
+    }
+}
+
+/// 统一 `【】`、`「」` 为方括号
+pub fn normalize_brackets(s: &str) -> String {
+    s.replace(['【', '「'], "[").replace(['】', '」'], "]")
+}
+
+/// 拆分开头方括号内的发布组，返回发布组（保留原大小写）及归一化后的剩余标题
+pub fn split_group<'a>(cased: &str, normalized: &'a str) -> (Option<String>, &'a str) {
+    let Some(body) = normalized.trim_start().strip_prefix('[') else {
+        return (None, normalized);
+    };
+    let Some(end) = body.find(']') else {
+        return (None, normalized);
+    };
+    let group = cased
+        .trim_start()
+        .strip_prefix('[')
+        .and_then(|b| b.split(']').next())
+        .map(str::trim)
+        .filter(|g| !g.is_empty() && !g.contains("新番"));
+    match group {
+        Some(group) => (Some(group.to_string()), &body[end + 1..]),
+        None => (None, normalized),
+    }
+}
+
+/// 合并常见的带分隔符写法，便于按字母数字切分后识别
+pub fn prepare_tokens(s: &str) -> String {
+    s.replace("h.264", "h264")
+        .replace("h.265", "h265")
+        .replace("e-ac-3", "eac3")
+        .replace("web-dl", "webdl")
+        .replace("web-rip", "webrip")
+        .replace("blu-ray", "bluray")
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("invalid episode regex"))
+}
+
+/// 从已归一化并去掉发布组的标题中识别集数，集数区间优先，避免把合集的起始集数识别为单集
+pub fn episode_number(text: &str) -> Option<EpisodeNumber> {
+    parse_episode_range(text)
+        .map(|(start, end)| EpisodeNumber::Range(start, end))
+        .or_else(|| parse_episode(text).map(EpisodeNumber::Single))
+}
+
+fn parse_episode_range(text: &str) -> Option<(u32, u32)> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    static CJK_RE: OnceLock<Regex> = OnceLock::new();
+    let re = regex(
+        &RE,
+        r"(?x)
+        第\s*(\d{1,3})\s*[-~～至到]\s*(\d{1,3})\s*[话話集回]
+        | s\d{1,2}e(\d{1,3})\s*[-~]\s*(?:s\d{1,2})?e?(\d{1,3})(?:[^a-z0-9.]|$)
+        | (?:^|[^a-z0-9])ep?\s?(\d{1,3})\s*[-~]\s*(?:ep?)?\s?(\d{1,3})(?:[^a-z0-9.]|$)
+        | [\[(](\d{1,3})(?:v\d)?\s*[-~至]\s*(\d{1,3})(?:v\d)?[^\])\d]*[\])]
+        | [\s_](\d{2,3})(?:v\d)?\s?[-~至]\s?(\d{2,3})(?:v\d)?(?:[\s_\[(]|$)
+        ",
+    );
+    let range = re.captures_iter(text).find_map(|caps| {
+        let mut nums = caps
+            .iter()
+            .skip(1)
+            .flatten()
+            .filter_map(|m| m.as_str().parse::<u32>().ok());
+        let (start, end) = (nums.next()?, nums.next()?);
+        (start < end).then_some((start, end))
+    });
+    range.or_else(|| {
+        let caps = regex(
+            &CJK_RE,
+            r"第([零一二三四五六七八九十百]+)\s*[-~～至到]\s*([零一二三四五六七八九十百]+)\s*[话話集回]",
+        )
+        .captures(text)?;
+        let (start, end) = (cjk_number(&caps[1])?, cjk_number(&caps[2])?);
+        (start < end).then_some((start, end))
+    })
+}
+
+fn parse_episode(text: &str) -> Option<f64> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    static CJK_RE: OnceLock<Regex> = OnceLock::new();
+    let re = regex(
+        &RE,
+        r"(?x)
+        s\d{1,2}e(\d{1,4}(?:\.\d)?)(?:[^0-9]|$)
+        | 第\s*(\d{1,4}(?:\.\d)?)\s*[话話集回]
+        | (?:^|[^a-z0-9])(?:ep?|episode)\.?\s?(\d{1,4}(?:\.\d)?)(?:v\d)?(?:[^a-z0-9]|$)
+        | \s-\s*(\d{1,4}(?:\.\d)?)(?:v\d)?(?:\s|\[|\(|\.[a-z0-9]{2,4}$|$)
+        | \[(\d{1,3}(?:\.\d)?)(?:v\d)?(?:\s*end)?\]
+        | (?:^|\s)\#(\d{1,4}(?:\.\d)?)(?:[^0-9.]|$)
+        ",
+    );
+    if let Some(caps) = re.captures(text) {
+        return caps
+            .iter()
+            .skip(1)
+            .flatten()
+            .find_map(|m| m.as_str().parse().ok());
+    }
+    regex(&CJK_RE, r"第([零一二三四五六七八九十百]+)\s*[话話集回]")
+        .captures(text)
+        .and_then(|c| cjk_number(&c[1]))
+        .map(f64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct EpisodeFixture {
+        title: String,
+        #[serde(default)]
+        episode: Option<f64>,
+        #[serde(default)]
+        range: Option<(u32, u32)>,
+    }
+
+    #[test]
+    fn parse_episode_fixture_titles() {
+        let fixtures: Vec<EpisodeFixture> = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/episode_titles.json"
+        )))
+        .unwrap();
+        for fixture in fixtures {
+            let expected = match (fixture.episode, fixture.range) {
+                (Some(ep), _) => Some(EpisodeNumber::Single(ep)),
+                (None, Some((start, end))) => Some(EpisodeNumber::Range(start, end)),
+                (None, None) => None,
+            };
+            assert_eq!(
+                parse_episode_number(&fixture.title),
+                expected,
+                "{}",
+                fixture.title
+            );
+        }
+    }
+
+    #[test]
+    fn parse_cjk_number() {
+        assert_eq!(cjk_number("五"), Some(5));
+        assert_eq!(cjk_number("十"), Some(10));
+        assert_eq!(cjk_number("十二"), Some(12));
+        assert_eq!(cjk_number("二十"), Some(20));
+        assert_eq!(cjk_number("二十四"), Some(24));
+        assert_eq!(cjk_number("一百"), Some(100));
+        assert_eq!(cjk_number("一百零五"), Some(105));
+        assert_eq!(cjk_number("一百二十三"), Some(123));
+        assert_eq!(cjk_number("百"), None);
+        assert_eq!(cjk_number("一百零二十"), None);
+    }
+
+    #[test]
+    fn detect_collection_marker() {
+        let cases = [
+            ("[Group] Title [01-12][1080p]", Some("01-12")),
+            ("[Group] Title 第01~24话 合集", Some("合集")),
+            ("[Group] Title [EP01-EP13 END][WebRip]", Some("01-13")),
+            ("[Group] Title (01v2-13v2)[1080p]", Some("01-13")),
+            ("[Group] Title Batch [1080p]", Some("Batch")),
+            ("[Group] Title Season 1 [1080p]", None),
+            ("[Group] Title - 12 END [1080p]", None),
+            ("[Group] Title [2025-01-08][1080p]", None),
+        ];
+        for (title, expected) in cases {
+            assert_eq!(collection_marker(title).as_deref(), expected, "{title}");
+        }
+    }
+}
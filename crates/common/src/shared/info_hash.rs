@@ -0,0 +1,125 @@
+/// 磁力链接中 BitTorrent v2 哈希的 multihash 前缀：sha2-256 (0x12)，长度 32 (0x20)
+const BTMH_SHA256_PREFIX: &str = "1220";
+
+/// BitTorrent 种子的 info hash，兼容 v1 (SHA-1)、v2 (SHA-256) 及同时包含两者的混合种子
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct InfoHash {
+    pub v1: Option<[u8; 20]>,
+    pub v2: Option<[u8; 32]>,
+}
+
+impl InfoHash {
+    pub fn v1(hash: [u8; 20]) -> Self {
+        Self {
+            v1: Some(hash),
+            v2: None,
+        }
+    }
+
+    pub fn v2(hash: [u8; 32]) -> Self {
+        Self {
+            v1: None,
+            v2: Some(hash),
+        }
+    }
+
+    /// 由资源标识及 v2 哈希还原，标识等于截断的 v2 哈希时视为纯 v2 种子
+    pub fn from_id(id: [u8; 20], v2: Option<[u8; 32]>) -> Self {
+        match v2 {
+            Some(v2) if v2[..20] == id => Self::v2(v2),
+            _ => Self { v1: Some(id), v2 },
+        }
+    }
+
+    /// 解析十六进制哈希，按长度区分 v1 (40 位) 与 v2 (64 位)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let bytes = hex::decode(hex.trim()).ok()?;
+        match bytes.len() {
+            20 => bytes.try_into().ok().map(Self::v1),
+            32 => bytes.try_into().ok().map(Self::v2),
+            _ => None,
+        }
+    }
+
+    /// 解析磁力链接 `urn:btmh:` 的 multihash 值，仅支持 SHA-256
+    pub fn from_btmh(multihash: &str) -> Option<Self> {
+        let hex = multihash.strip_prefix(BTMH_SHA256_PREFIX)?;
+        let bytes = hex::decode(hex).ok()?;
+        bytes.try_into().ok().map(Self::v2)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.v1.is_none() && self.v2.is_none()
+    }
+
+    /// 合并两个来源的哈希，已有的值优先
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            v1: self.v1.or(other.v1),
+            v2: self.v2.or(other.v2),
+        }
+    }
+
+    /// 资源标识：优先使用 v1 哈希，纯 v2 种子取 SHA-256 的前 20 字节，
+    /// 与 BEP 52 在 tracker、DHT 中使用的截断哈希一致
+    pub fn id(&self) -> Option<[u8; 20]> {
+        self.v1.or_else(|| {
+            self.v2
+                .map(|v2| v2[..20].try_into().expect("v2 hash has 32 bytes"))
+        })
+    }
+
+    /// 生成磁力链接，混合种子同时携带 `urn:btih:` 与 `urn:btmh:`
+    pub fn magnet_hex(&self) -> String {
+        let mut xts = vec![];
+        if let Some(v1) = self.v1 {
+            xts.push(format!("xt=urn:btih:{}", hex::encode(v1)));
+        }
+        if let Some(v2) = self.v2 {
+            xts.push(format!(
+                "xt=urn:btmh:{}{}",
+                BTMH_SHA256_PREFIX,
+                hex::encode(v2)
+            ));
+        }
+        format!("magnet:?{}", xts.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_id_and_magnet() {
+        let v1 = [0x11u8; 20];
+        let mut v2 = [0x22u8; 32];
+        v2[0] = 0xab;
+
+        let pure = InfoHash::v2(v2);
+        assert_eq!(pure.id().unwrap()[..], v2[..20]);
+        assert_eq!(InfoHash::from_id(pure.id().unwrap(), Some(v2)), pure);
+        assert_eq!(
+            InfoHash::from_btmh(&format!("1220{}", hex::encode(v2))),
+            Some(pure)
+        );
+        assert_eq!(
+            InfoHash::from_btmh(&format!("1114{}", hex::encode(v1))),
+            None
+        );
+
+        let hybrid = InfoHash::v1(v1).merge(pure);
+        assert_eq!(hybrid.id(), Some(v1));
+        assert_eq!(InfoHash::from_id(v1, Some(v2)), hybrid);
+        assert_eq!(
+            hybrid.magnet_hex(),
+            format!(
+                "magnet:?xt=urn:btih:{}&xt=urn:btmh:1220{}",
+                hex::encode(v1),
+                hex::encode(v2)
+            )
+        );
+        assert_eq!(InfoHash::from_hex(&hex::encode(v1)), Some(InfoHash::v1(v1)));
+        assert!(InfoHash::default().id().is_none());
+    }
+}
@@ -1,6 +1,8 @@
 pub mod biz;
 pub mod boss;
 pub mod cap;
+pub mod episode;
 pub mod error;
+pub mod info_hash;
 pub mod model;
 pub mod str;
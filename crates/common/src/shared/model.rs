@@ -1,5 +1,29 @@
 #[derive(Debug, Clone)]
 pub struct SearchUrls {
     pub feed_id: i64,
+    /// feed 指定的解析器名称
+    pub parser: Option<String>,
+    /// feed 两次请求之间的最小间隔（秒）
+    pub min_spacing: Option<i64>,
+    /// feed 加密后的请求选项
+    pub request_options: Option<String>,
     pub urls: Vec<String>,
 }
+
+/// 生成搜索地址所需的参数，对应搜索地址模板中的命名占位符
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub keyword: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub year: Option<i32>,
+}
+
+impl SearchQuery {
+    pub fn new(keyword: impl Into<String>) -> Self {
+        Self {
+            keyword: keyword.into(),
+            ..Default::default()
+        }
+    }
+}
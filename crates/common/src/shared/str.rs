@@ -2,13 +2,62 @@ use simd_normalizer::UnicodeNormalization;
 
 // nfkc_to_lowercase 将字符串nfkc化并去除空格转小写
 pub fn nfkc_to_lowercase(str: &str) -> String {
+    nfkc_lowercase(str, false)
+}
+
+/// 将字符串 nfkc 化并转小写，`keep_whitespace` 为假时同时去除空白
+pub fn nfkc_lowercase(str: &str, keep_whitespace: bool) -> String {
     str.nfkc()
         .chars()
-        .filter(|c| !c.is_whitespace())
+        .filter(|c| keep_whitespace || !c.is_whitespace())
         .flat_map(|c| c.to_lowercase())
         .collect()
 }
 
+/// 全文索引分词：中日韩字符逐字成词，其余字母数字按单词聚合并转小写，
+/// 标点及空白作为分隔符丢弃
+pub fn to_fts_tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::with_capacity(input.len());
+    let mut current_word = String::new();
+
+    // nfkc 会将全角的 "ＡＢＣ" 转为 "ABC"，全角数字 "１" 转为 "1"
+    for c in input.nfkc().chars() {
+        if c.is_alphanumeric() {
+            if is_cjk(c) {
+                // 遇到中日韩字符，先将前面累积的英文/数字 word 刷入 token
+                if !current_word.is_empty() {
+                    tokens.push(std::mem::take(&mut current_word));
+                }
+                tokens.push(c.to_string());
+            } else {
+                current_word.extend(c.to_lowercase());
+            }
+        } else if !current_word.is_empty() {
+            tokens.push(std::mem::take(&mut current_word));
+        }
+    }
+
+    if !current_word.is_empty() {
+        tokens.push(current_word);
+    }
+    tokens
+}
+
+/// 基于 Unicode 码位判断是否为中日韩字符 (汉字、假名、谚文、注音等)
+pub fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        // CJK 统一表意文字及扩展 (涵盖简繁体及绝大部分生僻字)
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x20000..=0x2A6DF |
+        // 日文平假名、片假名、片假名语音扩展
+        0x3040..=0x309F | 0x30A0..=0x30FF | 0x31F0..=0x31FF |
+        // 韩文谚文音节及字母
+        0xAC00..=0xD7A3 | 0x1100..=0x11FF | 0x3130..=0x318F |
+        // 台湾注音符号
+        0x3100..=0x312F
+    )
+}
+
 pub fn to_search_keywords(str: &str) -> Vec<String> {
     str.chars()
         .map(|c| if c.is_alphanumeric() { c } else { ' ' })
@@ -18,6 +67,84 @@ pub fn to_search_keywords(str: &str) -> Vec<String> {
         .collect()
 }
 
+/// 搜索时忽略的停用词：英文虚词、罗马音助词及季度分段用语
+const SEARCH_STOP_WORDS: [&str; 27] = [
+    "a", "an", "the", "of", "and", "or", "to", "in", "on", "at", "for", "with", "by", "from", "is",
+    "no", "wa", "ga", "wo", "ni", "de", "mo", "na", "ka", "season", "part", "cour",
+];
+
+/// 短语查询至少包含的字母数字个数，中日韩字符按 2 个计
+const MIN_PHRASE_WEIGHT: usize = 4;
+/// 完整标题至少包含的字母数字个数，保留 `86` 这类短标题
+const MIN_TITLE_WEIGHT: usize = 2;
+
+/// 由番剧的多语言标题生成搜索关键字，按优先级排列：
+/// 先是各标题的完整标题（过短的除外），再按标题顺序轮流取各自被标点分隔出的短语（较长的在前）；
+/// 短语去除停用词及单个字母，过短的短语被丢弃，结果按 nfkc 小写去重
+pub fn to_search_queries<S: AsRef<str>>(titles: &[S]) -> Vec<String> {
+    let mut wholes = vec![];
+    let mut phrases = vec![];
+    for title in titles {
+        let title = title.as_ref();
+        // 以 `.`、`_` 代替空格的罗马音标题，如 Majuu.no.Ou
+        let title = if title.contains(char::is_whitespace) {
+            title.to_string()
+        } else {
+            title.replace(['.', '_'], " ")
+        };
+        let whole = to_search_keywords(&title).join(" ");
+        if phrase_weight(&whole) >= MIN_TITLE_WEIGHT {
+            wholes.push(whole);
+        }
+
+        let mut list = title
+            .split(|c: char| !c.is_alphanumeric() && !c.is_whitespace() && c != '\'')
+            .filter_map(significant_phrase)
+            .collect::<Vec<_>>();
+        list.sort_by_key(|(_, weight)| std::cmp::Reverse(*weight));
+        phrases.push(list.into_iter().map(|(phrase, _)| phrase));
+    }
+
+    let mut queries = wholes;
+    loop {
+        let round = phrases
+            .iter_mut()
+            .filter_map(Iterator::next)
+            .collect::<Vec<_>>();
+        if round.is_empty() {
+            break;
+        }
+        queries.extend(round);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    queries.retain(|q| seen.insert(nfkc_lowercase(q, true)));
+    queries
+}
+
+/// 去除短语中的停用词及单个字母，返回短语及其权重，不足最小权重时返回 None
+fn significant_phrase(phrase: &str) -> Option<(String, usize)> {
+    let words = to_search_keywords(phrase)
+        .into_iter()
+        .filter(|w| {
+            let lower = w.to_lowercase();
+            !SEARCH_STOP_WORDS.contains(&lower.as_str())
+                && (w.chars().count() > 1 || w.chars().all(|c| c.is_numeric() || is_cjk(c)))
+        })
+        .collect::<Vec<_>>();
+    let phrase = words.join(" ");
+    let weight = phrase_weight(&phrase);
+    (weight >= MIN_PHRASE_WEIGHT).then_some((phrase, weight))
+}
+
+fn phrase_weight(phrase: &str) -> usize {
+    phrase
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| if is_cjk(c) { 2 } else { 1 })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +203,34 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_to_search_queries() {
+        let titles = [
+            "Clevatess: The King of Devil Beasts, The Baby and the Brave of Undead",
+            "クレバテス-魔獣の王と赤子と屍の勇者-",
+            "Clevatess.Majuu.no.Ou.to.Akago.to.Kabane.no.Yuusha",
+            "クレバテス",
+        ];
+        assert_eq!(
+            to_search_queries(&titles),
+            vec![
+                "Clevatess The King of Devil Beasts The Baby and the Brave of Undead",
+                "クレバテス 魔獣の王と赤子と屍の勇者",
+                "Clevatess Majuu no Ou to Akago to Kabane no Yuusha",
+                "クレバテス",
+                "King Devil Beasts",
+                "魔獣の王と赤子と屍の勇者",
+                "Clevatess Majuu Ou Akago Kabane Yuusha",
+                "Baby Brave Undead",
+                "Clevatess",
+            ]
+        );
+
+        // 停用词及过短的短语不会单独成为关键字
+        assert_eq!(
+            to_search_queries(&["Re:Zero - Season 2", "A"]),
+            vec!["Re Zero Season 2", "Zero"]
+        );
+    }
 }
@@ -2,13 +2,36 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use crate::entity::model::{
-    FeedBaseData, FeedData, FeedFetchError, FeedFetchResult, FeedListQuery, FeedMetadata, FeedProp,
+    FeedBaseData, FeedCacheMark, FeedData, FeedFetchError, FeedFetchResult, FeedListQuery,
+    FeedMetadata, FeedPreview, FeedProp, FeedRequestKind, FeedRequestOptions, PushedEntries,
+    PushedItem,
 };
 
 #[async_trait]
 pub trait FeedFetcher: Send + Sync {
-    async fn fetch_url(&self, url: &str) -> Result<FeedData, FeedFetchError>;
-    async fn get_source_key(&self, url: &str) -> Result<String, FeedFetchError>;
+    async fn fetch_url(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        request: &FeedRequestOptions,
+    ) -> Result<FeedData, FeedFetchError>;
+    // 条目保存成功后写入 `fetch_url` 返回的缓存校验信息
+    async fn save_cache(&self, mark: &FeedCacheMark);
+    // 完整解析一次 feed，返回每个条目的解析结果，用于排查匹配问题；不写入缓存及种子存储
+    async fn preview_url(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        request: &FeedRequestOptions,
+    ) -> Result<FeedPreview, FeedFetchError>;
+    /// 解析外部推送的条目，`download` 为真时下载种子链接以计算 info hash，否则将其放入 pending
+    async fn resolve_pushed(&self, items: Vec<PushedItem>, download: bool) -> PushedEntries;
+    async fn get_source_key(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        request: &FeedRequestOptions,
+    ) -> Result<String, FeedFetchError>;
 }
 
 #[async_trait]
@@ -21,6 +44,7 @@ pub trait FeedRepository: Send + Sync {
 }
 
 // feed访问策略
+#[async_trait]
 pub trait FeedAccessPolicy: Send + Sync {
     // 获取当前这一刻禁止请求的feed id列表
     fn block_feed_ids(&self) -> Vec<i64>;
@@ -28,6 +52,17 @@ pub trait FeedAccessPolicy: Send + Sync {
     fn block_feed_details(&self) -> Vec<(i64, i64)>;
     // 校验是否允许请求
     fn is_access(&self, feed_id: i64) -> bool;
+    // 获取最近一次实际发起请求（含搜索）的时间戳（秒）
+    fn last_fetch_at(&self, feed_id: i64) -> Option<i64>;
+    // 获取最近一次轮询订阅地址的时间戳（秒）
+    fn last_poll_at(&self, feed_id: i64) -> Option<i64>;
+    // 校验距离上一次请求是否已超过最小间隔
+    fn is_spaced(&self, feed_id: i64, min_spacing: Option<i64>, now: i64) -> bool {
+        match (min_spacing, self.last_fetch_at(feed_id)) {
+            (Some(spacing), Some(last)) => now - last >= spacing,
+            _ => true,
+        }
+    }
     // 记录请求结果
-    fn note(&self, feed_id: i64, res: &FeedFetchResult);
+    async fn note(&self, feed_id: i64, kind: FeedRequestKind, res: &FeedFetchResult);
 }
@@ -1,17 +1,27 @@
 use std::sync::Arc;
 
-use common::shared::error::Error;
+use chrono::Utc;
+
+use common::shared::{cap::CryptoProvider, error::Error, model::SearchQuery};
 
 use crate::entity::{
     cap::{FeedAccessPolicy, FeedFetcher},
-    model::{FeedBaseData, FeedFetchResult, FeedMetadata},
+    model::{
+        FeedBaseData, FeedCacheMark, FeedFetchResult, FeedMetadata, FeedPreview, FeedRequestKind,
+        FeedRequestOptions, FeedSchedule,
+    },
+    search_feeds::{expand_search_url, validate_search_url},
 };
 
+/// 判断轮询是否到期时允许的误差（秒）
+const POLL_TOLERANCE: i64 = 30;
+
 #[derive(Clone)]
 pub struct FeedEntity {
     data: FeedBaseData,
     fetch_cap: Arc<dyn FeedFetcher>,
     access_policy: Arc<dyn FeedAccessPolicy>,
+    crypto_provider: Arc<dyn CryptoProvider>,
 }
 
 impl FeedEntity {
@@ -19,11 +29,13 @@ impl FeedEntity {
         data: FeedBaseData,
         fetch_cap: Arc<dyn FeedFetcher>,
         access_policy: Arc<dyn FeedAccessPolicy>,
+        crypto_provider: Arc<dyn CryptoProvider>,
     ) -> Self {
         Self {
             data,
             fetch_cap,
             access_policy,
+            crypto_provider,
         }
     }
 
@@ -43,26 +55,95 @@ impl FeedEntity {
         self.data.metadata.search_url.as_deref()
     }
 
+    pub fn parser(&self) -> Option<&str> {
+        self.data.metadata.parser.as_deref()
+    }
+
+    pub fn schedule(&self) -> &FeedSchedule {
+        &self.data.metadata.schedule
+    }
+
+    /// 是否设置了代理、请求头或 Cookie
+    pub fn has_request_options(&self) -> bool {
+        self.data.metadata.request_options.is_some()
+    }
+
+    /// 解密后的请求选项
+    pub fn request_options(&self) -> Result<FeedRequestOptions, Error> {
+        FeedRequestOptions::decrypt(
+            self.data.metadata.request_options.as_deref(),
+            self.crypto_provider.as_ref(),
+        )
+    }
+
+    /// 轮询间隔（秒），未设置时使用默认值
+    pub fn poll_interval(&self) -> i64 {
+        self.data
+            .metadata
+            .schedule
+            .poll_interval
+            .unwrap_or(FeedSchedule::DEFAULT_POLL_INTERVAL)
+    }
+
+    /// 判断在 `now` 时刻是否已到达下一次轮询时间
+    pub fn is_due(&self, now: i64) -> bool {
+        // 只看轮询时间，搜索请求不会推迟下一次轮询
+        match self.access_policy.last_poll_at(self.data.id) {
+            // 预留调度抖动的余量，避免因任务晚触发几秒而错过一整个周期
+            Some(last) => now - last >= self.poll_interval() - POLL_TOLERANCE,
+            None => true,
+        }
+    }
+
+    fn is_spaced(&self) -> bool {
+        self.access_policy.is_spaced(
+            self.data.id,
+            self.data.metadata.schedule.min_spacing,
+            Utc::now().timestamp(),
+        )
+    }
+
     pub async fn set(
         &mut self,
         title: String,
         site_url: Option<String>,
         search_url: Option<String>,
+        parser: Option<String>,
+        schedule: FeedSchedule,
+        request: Option<FeedRequestOptions>,
     ) -> Result<(), Error> {
-        let metdata =
-            Self::verify_metadata(self.fetch_cap.as_ref(), title, site_url, search_url).await?;
+        // 未传入请求选项时沿用已保存的设置
+        let request = match request {
+            Some(v) => v,
+            None => self.request_options()?,
+        };
+        let mut metdata = Self::verify_metadata(
+            self.fetch_cap.as_ref(),
+            title,
+            site_url,
+            search_url,
+            parser,
+            schedule,
+            &request,
+        )
+        .await?;
+        metdata.request_options = request.encrypt(self.crypto_provider.as_ref())?;
         self.data.metadata = metdata;
         Ok(())
     }
 
     pub async fn list(&self) -> Result<FeedFetchResult, Error> {
-        if !self.access_policy.is_access(self.data.id) {
+        if !self.access_policy.is_access(self.data.id) || !self.is_spaced() {
             return Ok(FeedFetchResult::Denied);
         }
         if let Some(url) = &self.data.metadata.site_url {
-            let data = self.fetch_cap.fetch_url(url).await;
+            let request = self.request_options()?;
+            let data = self
+                .fetch_cap
+                .fetch_url(url, self.data.metadata.parser.as_deref(), &request)
+                .await;
             let res = match data {
-                Ok(data) => FeedFetchResult::Success(data.items),
+                Ok(data) => FeedFetchResult::Success(data),
                 Err(e) => match e {
                     super::model::FeedFetchError::Inaccessible(v) => {
                         FeedFetchResult::Failure(Error::conflict(v))
@@ -75,24 +156,39 @@ impl FeedEntity {
                     }
                 },
             };
-            self.access_policy.note(self.data.id, &res);
+            self.access_policy
+                .note(self.data.id, FeedRequestKind::Poll, &res)
+                .await;
             Ok(res)
         } else {
             Err(Error::invariant("not found feed site url"))
         }
     }
 
+    /// 获取到的条目保存成功后调用，写入本次响应的缓存校验信息
+    pub async fn commit(&self, cache: Option<&FeedCacheMark>) {
+        if let Some(mark) = cache {
+            self.fetch_cap.save_cache(mark).await;
+        }
+    }
+
     pub async fn search(&self, keyword: &str) -> Result<FeedFetchResult, Error> {
+        if !self.is_spaced() {
+            return Ok(FeedFetchResult::Denied);
+        }
         if let Some(url) = &self.data.metadata.search_url {
-            let url = match formatx::formatx!(url, keyword) {
-                Ok(url) => url,
-                Err(e) => {
-                    return Err(Error::external("format search url failed", e));
-                }
-            };
-            let data = self.fetch_cap.fetch_url(&url).await;
+            let url = expand_search_url(
+                url,
+                self.data.metadata.parser.as_deref(),
+                &SearchQuery::new(keyword),
+            )?;
+            let request = self.request_options()?;
+            let data = self
+                .fetch_cap
+                .fetch_url(&url, self.data.metadata.parser.as_deref(), &request)
+                .await;
             let res = match data {
-                Ok(data) => FeedFetchResult::Success(data.items),
+                Ok(data) => FeedFetchResult::Success(data),
                 Err(e) => match e {
                     super::model::FeedFetchError::Inaccessible(v) => {
                         FeedFetchResult::Failure(Error::conflict(v))
@@ -103,7 +199,9 @@ impl FeedEntity {
                     super::model::FeedFetchError::InvalidData(v) => return Err(Error::conflict(v)),
                 },
             };
-            self.access_policy.note(self.data.id, &res);
+            self.access_policy
+                .note(self.data.id, FeedRequestKind::Search, &res)
+                .await;
             Ok(res)
         } else {
             Err(Error::invariant("not found feed site url"))
@@ -111,27 +209,86 @@ impl FeedEntity {
     }
 }
 
+impl FeedEntity {
+    /// 试运行：按当前配置请求订阅地址或搜索地址（传入关键字时）并完整解析，不写入任何数据
+    pub async fn preview(&self, keyword: Option<&str>) -> Result<FeedPreview, Error> {
+        let url = match keyword {
+            Some(keyword) => {
+                let template = self
+                    .data
+                    .metadata
+                    .search_url
+                    .as_ref()
+                    .ok_or_else(|| Error::conflict("feed has no search url"))?;
+                expand_search_url(
+                    template,
+                    self.data.metadata.parser.as_deref(),
+                    &SearchQuery::new(keyword),
+                )?
+            }
+            None => self
+                .data
+                .metadata
+                .site_url
+                .clone()
+                .ok_or_else(|| Error::conflict("feed has no site url"))?,
+        };
+        let request = self.request_options()?;
+        self.fetch_cap
+            .preview_url(&url, self.data.metadata.parser.as_deref(), &request)
+            .await
+            .map_err(|e| Error::external(format!("preview feed {} failed", self.id()), e))
+    }
+}
+
 impl FeedEntity {
     pub(super) async fn verify_metadata(
         fetch_cap: &dyn FeedFetcher,
         title: String,
         site_url: Option<String>,
         search_url: Option<String>,
+        parser: Option<String>,
+        schedule: FeedSchedule,
+        request: &FeedRequestOptions,
     ) -> Result<FeedMetadata, Error> {
         if site_url.is_none() && search_url.is_none() {
             return Err(Error::conflict("feed entity must have url"));
         }
 
+        if schedule.poll_interval.is_some_and(|v| v <= 0)
+            || schedule.min_spacing.is_some_and(|v| v < 0)
+        {
+            return Err(Error::conflict(
+                "feed poll interval must be positive and min spacing must be not negative",
+            ));
+        }
+
         if title.is_empty() {
             return Err(Error::conflict("feed entity title must be not empty"));
         }
 
-        let source_key = FeedEntity::get_source_key(fetch_cap, &site_url, &search_url).await?;
+        if let Some(url) = &search_url {
+            validate_search_url(url)?;
+        }
+
+        let parser = parser.filter(|p| !p.is_empty());
+        let source_key = FeedEntity::get_source_key(
+            fetch_cap,
+            &site_url,
+            &search_url,
+            parser.as_deref(),
+            request,
+        )
+        .await?;
         Ok(FeedMetadata {
             title,
             site_url,
             search_url,
             source_key,
+            parser,
+            schedule,
+            // 由调用方加密后写入
+            request_options: None,
         })
     }
 
@@ -139,21 +296,22 @@ impl FeedEntity {
         fetch_cap: &dyn FeedFetcher,
         url: &Option<String>,
         search_url: &Option<String>,
+        parser: Option<&str>,
+        request: &FeedRequestOptions,
     ) -> Result<String, Error> {
         let mut source_key = None;
         if let Some(url) = url {
             let data = fetch_cap
-                .get_source_key(url)
+                .get_source_key(url, parser, request)
                 .await
                 .map_err(|e| Error::external("feed verify fetch url failed", e))?;
             source_key = Some(data);
         }
 
         if let Some(url) = search_url {
-            let url = formatx::formatx!(url, "败犬")
-                .map_err(|e| Error::external("feed get_source_key format search url failed", e))?;
+            let url = expand_search_url(url, parser, &SearchQuery::new("败犬"))?;
             let data = fetch_cap
-                .get_source_key(&url)
+                .get_source_key(&url, parser, request)
                 .await
                 .map_err(|e| Error::external("feed get_source_key fetch search url failed", e))?;
             if let Some(source_key) = &source_key {
@@ -178,3 +336,162 @@ impl FeedEntity {
         &self.data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        entity::model::{FeedData, FeedFetchError, PushedEntries, PushedItem},
+        infra::feed_access_policy::{
+            BackoffPolicy, FeedBackoffState, FeedFetchOutcome, FeedHealthRepository, FeedLastFetch,
+        },
+    };
+
+    struct NoopFetcher;
+
+    #[async_trait]
+    impl FeedFetcher for NoopFetcher {
+        async fn fetch_url(
+            &self,
+            _url: &str,
+            _parser: Option<&str>,
+            _request: &FeedRequestOptions,
+        ) -> Result<FeedData, FeedFetchError> {
+            Ok(FeedData::default())
+        }
+
+        async fn save_cache(&self, _mark: &FeedCacheMark) {}
+
+        async fn preview_url(
+            &self,
+            _url: &str,
+            _parser: Option<&str>,
+            _request: &FeedRequestOptions,
+        ) -> Result<FeedPreview, FeedFetchError> {
+            Err(FeedFetchError::Inaccessible("noop fetcher".to_string()))
+        }
+
+        async fn resolve_pushed(&self, _items: Vec<PushedItem>, _download: bool) -> PushedEntries {
+            PushedEntries::default()
+        }
+
+        async fn get_source_key(
+            &self,
+            _url: &str,
+            _parser: Option<&str>,
+            _request: &FeedRequestOptions,
+        ) -> Result<String, FeedFetchError> {
+            Err(FeedFetchError::Inaccessible("noop fetcher".to_string()))
+        }
+    }
+
+    struct PlainCrypto;
+
+    impl CryptoProvider for PlainCrypto {
+        fn encrypt(&self, plain: &str) -> anyhow::Result<String> {
+            Ok(plain.to_string())
+        }
+
+        fn decrypt(&self, cipher: &str) -> anyhow::Result<String> {
+            Ok(cipher.to_string())
+        }
+    }
+
+    struct NoopHealth;
+
+    #[async_trait]
+    impl FeedHealthRepository for NoopHealth {
+        async fn list_backoff(&self) -> anyhow::Result<Vec<FeedBackoffState>> {
+            Ok(vec![])
+        }
+
+        async fn list_last_fetch(&self) -> anyhow::Result<Vec<FeedLastFetch>> {
+            Ok(vec![])
+        }
+
+        async fn record(
+            &self,
+            _state: &FeedBackoffState,
+            _kind: FeedRequestKind,
+            _outcome: FeedFetchOutcome<'_>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn feed(policy: Arc<dyn FeedAccessPolicy>, schedule: FeedSchedule) -> FeedEntity {
+        let data = FeedBaseData {
+            id: 1,
+            metadata: FeedMetadata {
+                title: "mikan".to_string(),
+                site_url: Some("https://mikanani.me/RSS/Classic".to_string()),
+                search_url: None,
+                source_key: "mikan".to_string(),
+                parser: None,
+                schedule,
+                request_options: None,
+            },
+        };
+        FeedEntity::new(data, Arc::new(NoopFetcher), policy, Arc::new(PlainCrypto))
+    }
+
+    #[tokio::test]
+    async fn search_requests_do_not_delay_polling() {
+        let policy = Arc::new(BackoffPolicy::new(Arc::new(NoopHealth)));
+        let schedule = FeedSchedule {
+            poll_interval: Some(600),
+            min_spacing: Some(60),
+        };
+        let entity = feed(policy.clone(), schedule);
+        assert!(entity.is_due(Utc::now().timestamp()));
+        assert!(entity.is_spaced());
+
+        // 搜索请求只影响请求间隔，不推迟轮询
+        policy
+            .note(
+                1,
+                FeedRequestKind::Search,
+                &FeedFetchResult::Success(FeedData::default()),
+            )
+            .await;
+        assert!(entity.is_due(Utc::now().timestamp()));
+        assert!(!entity.is_spaced());
+
+        policy
+            .note(
+                1,
+                FeedRequestKind::Poll,
+                &FeedFetchResult::Success(FeedData::default()),
+            )
+            .await;
+        let now = Utc::now().timestamp();
+        assert!(!entity.is_due(now));
+        assert!(!entity.is_due(now + 600 - POLL_TOLERANCE - 1));
+        assert!(entity.is_due(now + 600 - POLL_TOLERANCE));
+    }
+
+    #[tokio::test]
+    async fn spacing_is_unbounded_without_min_spacing() {
+        let policy = Arc::new(BackoffPolicy::new(Arc::new(NoopHealth)));
+        let entity = feed(policy.clone(), FeedSchedule::default());
+
+        policy
+            .note(
+                1,
+                FeedRequestKind::Poll,
+                &FeedFetchResult::Success(FeedData::default()),
+            )
+            .await;
+        assert!(entity.is_spaced());
+        assert!(!entity.is_due(Utc::now().timestamp()));
+
+        // 被拒绝的请求不计入任何时间戳
+        policy
+            .note(2, FeedRequestKind::Poll, &FeedFetchResult::Denied)
+            .await;
+        assert_eq!(policy.last_fetch_at(2), None);
+        assert_eq!(policy.last_poll_at(2), None);
+    }
+}
@@ -1,11 +1,15 @@
 use std::sync::Arc;
 
-use common::shared::error::Error;
+use common::shared::{cap::CryptoProvider, error::Error};
 
 use crate::entity::{
     cap::{FeedAccessPolicy, FeedFetcher, FeedRepository},
     feed_entity::FeedEntity,
-    model::{FeedListQuery, FeedType},
+    model::{
+        FeedBaseData, FeedImportResult, FeedListQuery, FeedOutline, FeedPreview, FeedPreviewEntry,
+        FeedRequestOptions, FeedSchedule, FeedType, PushedEntries, PushedItem,
+    },
+    opml::{read_opml, write_opml},
     search_feeds::SearchFeeds,
 };
 
@@ -14,6 +18,7 @@ pub struct Feeds {
     repo: Arc<dyn FeedRepository>,
     fetch_cap: Arc<dyn FeedFetcher>,
     access_policy: Arc<dyn FeedAccessPolicy>,
+    crypto_provider: Arc<dyn CryptoProvider>,
 }
 
 impl Feeds {
@@ -21,14 +26,25 @@ impl Feeds {
         repo: Arc<dyn FeedRepository>,
         fetch_cap: Arc<dyn FeedFetcher>,
         access_policy: Arc<dyn FeedAccessPolicy>,
+        crypto_provider: Arc<dyn CryptoProvider>,
     ) -> Self {
         Self {
             repo,
             fetch_cap,
             access_policy,
+            crypto_provider,
         }
     }
 
+    fn as_entity(&self, data: FeedBaseData) -> FeedEntity {
+        FeedEntity::new(
+            data,
+            self.fetch_cap.clone(),
+            self.access_policy.clone(),
+            self.crypto_provider.clone(),
+        )
+    }
+
     pub async fn list(&self) -> Result<Vec<FeedEntity>, Error> {
         Ok(self
             .repo
@@ -38,7 +54,7 @@ impl Feeds {
             .await
             .map_err(|e| Error::external("feeds list failed", e))?
             .into_iter()
-            .map(|i| FeedEntity::new(i.data, self.fetch_cap.clone(), self.access_policy.clone()))
+            .map(|i| self.as_entity(i.data))
             .collect())
     }
 
@@ -51,7 +67,7 @@ impl Feeds {
             .await
             .map_err(|e| Error::external("feeds list site feed failed", e))?
             .into_iter()
-            .map(|i| FeedEntity::new(i.data, self.fetch_cap.clone(), self.access_policy.clone()))
+            .map(|i| self.as_entity(i.data))
             .collect())
     }
 
@@ -64,7 +80,7 @@ impl Feeds {
             .await
             .map_err(|e| Error::external("feeds list search feed failed", e))?
             .into_iter()
-            .map(|i| FeedEntity::new(i.data, self.fetch_cap.clone(), self.access_policy.clone()))
+            .map(|i| self.as_entity(i.data))
             .collect())
     }
 
@@ -84,20 +100,115 @@ impl Feeds {
         title: String,
         site_url: Option<String>,
         search_url: Option<String>,
+        parser: Option<String>,
+        schedule: FeedSchedule,
+        request: FeedRequestOptions,
     ) -> Result<FeedEntity, Error> {
-        let metadata =
-            FeedEntity::verify_metadata(self.fetch_cap.as_ref(), title, site_url, search_url)
-                .await?;
+        let mut metadata = FeedEntity::verify_metadata(
+            self.fetch_cap.as_ref(),
+            title,
+            site_url,
+            search_url,
+            parser,
+            schedule,
+            &request,
+        )
+        .await?;
+        metadata.request_options = request.encrypt(self.crypto_provider.as_ref())?;
         let prop = self
             .repo
             .insert(&metadata)
             .await
             .map_err(|e| Error::external("feeds insert failed", e))?;
-        Ok(FeedEntity::new(
-            prop.data,
-            self.fetch_cap.clone(),
-            self.access_policy.clone(),
-        ))
+        Ok(self.as_entity(prop.data))
+    }
+
+    /// 试运行解析任意地址，用于添加 feed 前确认解析结果
+    pub async fn preview(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        request: &FeedRequestOptions,
+    ) -> Result<FeedPreview, Error> {
+        self.fetch_cap
+            .preview_url(url, parser.filter(|p| !p.is_empty()), request)
+            .await
+            .map_err(|e| Error::external(format!("preview feed url {} failed", url), e))
+    }
+
+    /// 解析外部推送的条目，不下载种子，需要下载种子才能确定 info hash 的条目放入 pending
+    pub async fn resolve_pushed(&self, items: Vec<PushedItem>) -> PushedEntries {
+        self.fetch_cap.resolve_pushed(items, false).await
+    }
+
+    /// 下载种子解析推送条目，用于处理 `resolve_pushed` 返回的 pending 条目
+    pub async fn download_pushed(&self, items: Vec<PushedItem>) -> Vec<FeedPreviewEntry> {
+        self.fetch_cap.resolve_pushed(items, true).await.entries
+    }
+
+    /// 导出所有订阅源为 OPML 文档，不包含代理、请求头等请求选项
+    pub async fn export_opml(&self) -> Result<String, Error> {
+        let outlines = self
+            .list()
+            .await?
+            .iter()
+            .map(|i| FeedOutline {
+                title: i.title().to_string(),
+                site_url: i.site_url().map(String::from),
+                search_url: i.search_url().map(String::from),
+                parser: i.parser().map(String::from),
+                schedule: i.schedule().clone(),
+            })
+            .collect::<Vec<_>>();
+        Ok(write_opml("feeds", &outlines))
+    }
+
+    /// 从 OPML 文档导入订阅源，逐条校验并创建，单条失败不影响其他条目；
+    /// 订阅地址或搜索地址与已有订阅源相同的条目被跳过
+    pub async fn import_opml(&self, content: &str) -> Result<Vec<FeedImportResult>, Error> {
+        let outlines = read_opml(content)
+            .map_err(|e| Error::conflict(format!("invalid opml document: {}", e)))?;
+        let mut existing = self
+            .list()
+            .await?
+            .iter()
+            .flat_map(|i| [i.site_url(), i.search_url()])
+            .flatten()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(outlines.len());
+        for outline in outlines {
+            let duplicated = [&outline.site_url, &outline.search_url]
+                .into_iter()
+                .flatten()
+                .any(|url| existing.contains(url));
+            let result = if duplicated {
+                Err("feed already exists".to_string())
+            } else {
+                self.create(
+                    outline.title.clone(),
+                    outline.site_url.clone(),
+                    outline.search_url.clone(),
+                    outline.parser.clone(),
+                    outline.schedule.clone(),
+                    FeedRequestOptions::default(),
+                )
+                .await
+                .map(|entity| entity.id())
+                .map_err(|e| e.to_string())
+            };
+            if result.is_ok() {
+                existing.extend(
+                    [&outline.site_url, &outline.search_url]
+                        .into_iter()
+                        .flatten()
+                        .cloned(),
+                );
+            }
+            results.push(FeedImportResult { outline, result });
+        }
+        Ok(results)
     }
 
     pub async fn save(&self, entity: &FeedEntity) -> Result<(), Error> {
@@ -114,7 +225,7 @@ impl Feeds {
             .get(feed_id)
             .await
             .map_err(|e| Error::external("get feed entity failed", e))?
-            .map(|i| FeedEntity::new(i.data, self.fetch_cap.clone(), self.access_policy.clone())))
+            .map(|i| self.as_entity(i.data)))
     }
 
     pub async fn delete(&self, entity: &FeedEntity) -> Result<(), Error> {
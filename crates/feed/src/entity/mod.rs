@@ -2,4 +2,5 @@ pub mod cap;
 pub mod feed_entity;
 pub mod feeds;
 pub mod model;
+pub mod opml;
 pub mod search_feeds;
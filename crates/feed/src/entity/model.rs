@@ -1,9 +1,48 @@
+use std::collections::BTreeMap;
+
+use common::shared::{cap::CryptoProvider, error::Error as CommonError};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Torznab/Newznab 解析器名称，使用该解析器的 feed 搜索地址可只配置 API 地址
+pub const TORZNAB_PARSER: &str = "torznab";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FeedData {
     pub source_key: String,
     pub items: Vec<FeedItem>,
+    /// 已入库而未放入 `items` 的条目，用于刷新资源在该 feed 中的来源记录
+    pub seen: Vec<FeedSeenItem>,
+    /// 本次响应的缓存校验信息，需在条目保存成功后再写入，
+    /// 否则保存失败时下次轮询会因内容未变化而跳过这些条目
+    pub cache: Option<FeedCacheMark>,
+}
+
+/// 再次出现的已入库条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedSeenItem {
+    /// 入库时记录的资源标识
+    pub info_hash: [u8; 20],
+    pub source_url: String,
+}
+
+/// 待写入的 feed HTTP 缓存校验信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCacheMark {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body_hash: String,
+    pub source_key: String,
+    /// 本次响应列出的条目，内容未变化时据此刷新来源记录
+    pub listed: Vec<FeedListedItem>,
+}
+
+/// feed 响应中列出的条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedListedItem {
+    pub resource_url: String,
+    pub source_url: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,7 +51,123 @@ pub struct FeedItem {
     pub source_url: String,
     pub resource_url: String,
     pub published_at: i64,
+    /// 资源标识，v1 哈希或纯 v2 种子的截断哈希，见 `InfoHash::id`
     pub info_hash: [u8; 20],
+    /// v2 及混合种子的 SHA-256 哈希
+    pub info_hash_v2: Option<[u8; 32]>,
+    pub kind: ResourceKind,
+    /// 合集资源可识别的集数区间（闭区间）
+    pub episode_range: Option<(u32, u32)>,
+    /// 种子内的文件列表，磁力链接资源为空
+    pub files: Vec<TorrentFileInfo>,
+    /// 订阅条目声明的资源大小（字节）
+    pub size: Option<u64>,
+    /// 下载得到的种子内容，入库时按最终的资源标识保存；未下载种子的条目为空
+    pub torrent: Option<Vec<u8>>,
+}
+
+/// 试运行解析结果，不读写 HTTP 缓存，也不过滤已入库的资源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedPreview {
+    pub source_key: String,
+    pub entries: Vec<FeedPreviewEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedPreviewEntry {
+    /// 正常解析的条目
+    Parsed {
+        item: FeedItem,
+        /// 被识别为合集时命中的标题片段
+        collection_marker: Option<String>,
+    },
+    /// 被跳过的条目及原因
+    Skipped {
+        title: Option<String>,
+        source_url: Option<String>,
+        reason: String,
+    },
+}
+
+/// 外部推送的资源条目，与订阅源条目经过相同的解析流程
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushedItem {
+    pub title: String,
+    /// 磁力链接或种子地址
+    pub resource_url: String,
+    /// 详情页地址，为空时使用资源地址
+    pub source_url: Option<String>,
+    /// 发布时间戳（秒），为空时使用当前时间
+    pub published_at: Option<i64>,
+    /// 十六进制 info hash，v1 为 40 位，v2 为 64 位
+    pub info_hash: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// 外部推送条目的解析结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushedEntries {
+    /// 解析完成及被跳过的条目，被跳过的条目排在最后
+    pub entries: Vec<FeedPreviewEntry>,
+    /// 未下载种子而无法确定 info hash 的条目
+    pub pending: Vec<PushedItem>,
+}
+
+/// OPML 中的单个订阅源，请求选项可能包含 Cookie 等凭据，不参与导入导出
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedOutline {
+    pub title: String,
+    pub site_url: Option<String>,
+    pub search_url: Option<String>,
+    pub parser: Option<String>,
+    pub schedule: FeedSchedule,
+}
+
+/// 导入单个订阅源的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedImportResult {
+    pub outline: FeedOutline,
+    /// 导入成功时为新建的 feed id，失败时为原因
+    pub result: Result<i64, String>,
+}
+
+/// 种子内的单个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentFileInfo {
+    /// 相对于种子根目录的路径，以 `/` 分隔
+    pub path: String,
+    pub size: u64,
+}
+
+/// 资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourceKind {
+    /// 单集资源
+    #[default]
+    Episode,
+    /// 合集/打包资源
+    Collection,
+}
+
+impl From<ResourceKind> for i32 {
+    fn from(kind: ResourceKind) -> Self {
+        match kind {
+            ResourceKind::Episode => 0,
+            ResourceKind::Collection => 1,
+        }
+    }
+}
+
+impl TryFrom<i32> for ResourceKind {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ResourceKind::Episode),
+            1 => Ok(ResourceKind::Collection),
+            _ => Err(format!("unknown resource kind: {}", value)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,6 +187,72 @@ pub struct FeedMetadata {
     pub site_url: Option<String>,
     pub search_url: Option<String>,
     pub source_key: String,
+    /// 解析器名称，为空时根据 url 自动识别
+    pub parser: Option<String>,
+    pub schedule: FeedSchedule,
+    /// 经 `CryptoProvider` 加密的 `FeedRequestOptions`，未设置时为空
+    pub request_options: Option<String>,
+}
+
+/// feed 的请求选项，作用于 feed 本身及其种子下载
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedRequestOptions {
+    /// 代理地址，支持 http/https/socks5
+    pub proxy: Option<String>,
+    /// 额外请求头
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Cookie 请求头内容，如私有站点的登录凭证
+    pub cookies: Option<String>,
+}
+
+impl FeedRequestOptions {
+    pub fn is_empty(&self) -> bool {
+        self.proxy.is_none() && self.headers.is_empty() && self.cookies.is_none()
+    }
+
+    pub fn encrypt(
+        &self,
+        crypto_provider: &dyn CryptoProvider,
+    ) -> Result<Option<String>, CommonError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let plain = serde_json::to_string(self)
+            .map_err(|e| CommonError::external("serialize feed request options failed", e))?;
+        crypto_provider
+            .encrypt(&plain)
+            .map(Some)
+            .map_err(|e| CommonError::external("encrypt feed request options failed", e))
+    }
+
+    pub fn decrypt(
+        cipher: Option<&str>,
+        crypto_provider: &dyn CryptoProvider,
+    ) -> Result<Self, CommonError> {
+        let Some(cipher) = cipher else {
+            return Ok(Self::default());
+        };
+        let plain = crypto_provider
+            .decrypt(cipher)
+            .map_err(|e| CommonError::external("decrypt feed request options failed", e))?;
+        serde_json::from_str(&plain)
+            .map_err(|e| CommonError::external("deserialize feed request options failed", e))
+    }
+}
+
+/// feed 请求频率设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedSchedule {
+    /// 轮询间隔（秒），为空时使用默认间隔
+    pub poll_interval: Option<i64>,
+    /// 对该 feed 两次请求之间的最小间隔（秒），同时约束轮询与搜索
+    pub min_spacing: Option<i64>,
+}
+
+impl FeedSchedule {
+    /// 默认轮询间隔
+    pub const DEFAULT_POLL_INTERVAL: i64 = 300;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,9 +282,18 @@ pub enum FeedFetchError {
     InvalidData(String),
 }
 
+/// feed 请求的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedRequestKind {
+    /// 按轮询间隔请求订阅地址
+    Poll,
+    /// 按搜索任务请求搜索地址
+    Search,
+}
+
 #[derive(Debug)]
 pub enum FeedFetchResult {
-    Success(Vec<FeedItem>),
+    Success(FeedData),
     Retryable(common::shared::error::Error),
     Failure(common::shared::error::Error),
     Denied,
@@ -0,0 +1,183 @@
+use std::io::Cursor;
+
+use quick_xml::{
+    Writer, XmlVersion,
+    events::{BytesDecl, BytesText, Event},
+    reader::Reader,
+};
+
+use crate::entity::model::{FeedOutline, FeedSchedule};
+
+/// OPML 标准之外的扩展属性，用于保存搜索地址、解析器及请求频率
+const ATTR_SEARCH_URL: &str = "searchUrl";
+const ATTR_PARSER: &str = "parser";
+const ATTR_POLL_INTERVAL: &str = "pollInterval";
+const ATTR_MIN_SPACING: &str = "minSpacing";
+
+/// 将订阅源列表写为 OPML 2.0 文档
+pub fn write_opml(title: &str, outlines: &[FeedOutline]) -> String {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    write_document(&mut writer, title, outlines).expect("write opml to memory");
+    String::from_utf8(writer.into_inner().into_inner()).expect("opml is utf-8")
+}
+
+fn write_document(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    title: &str,
+    outlines: &[FeedOutline],
+) -> std::io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("opml")
+        .with_attribute(("version", "2.0"))
+        .write_inner_content(|writer| {
+            writer
+                .create_element("head")
+                .write_inner_content(|writer| {
+                    writer
+                        .create_element("title")
+                        .write_text_content(BytesText::new(title))?;
+                    Ok(())
+                })?;
+            writer
+                .create_element("body")
+                .write_inner_content(|writer| {
+                    for outline in outlines {
+                        let poll_interval = outline.schedule.poll_interval.map(|v| v.to_string());
+                        let min_spacing = outline.schedule.min_spacing.map(|v| v.to_string());
+                        let attrs = [
+                            ("type", Some("rss")),
+                            ("text", Some(outline.title.as_str())),
+                            ("title", Some(outline.title.as_str())),
+                            ("xmlUrl", outline.site_url.as_deref()),
+                            (ATTR_SEARCH_URL, outline.search_url.as_deref()),
+                            (ATTR_PARSER, outline.parser.as_deref()),
+                            (ATTR_POLL_INTERVAL, poll_interval.as_deref()),
+                            (ATTR_MIN_SPACING, min_spacing.as_deref()),
+                        ];
+                        writer
+                            .create_element("outline")
+                            .with_attributes(
+                                attrs
+                                    .into_iter()
+                                    .filter_map(|(key, value)| value.map(|v| (key, v))),
+                            )
+                            .write_empty()?;
+                    }
+                    Ok(())
+                })?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+/// 读取 OPML 文档中携带订阅地址或搜索地址的 `outline`，分类等嵌套层级被忽略
+pub fn read_opml(content: &str) -> Result<Vec<FeedOutline>, String> {
+    let mut reader = Reader::from_str(content);
+    let mut outlines = vec![];
+    let mut found = false;
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let element = match &event {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+        match element.local_name().as_ref() {
+            b"opml" => found = true,
+            b"outline" => {
+                let value_of = |key: &str| {
+                    element
+                        .attributes()
+                        .flatten()
+                        .find(|a| a.key.local_name().as_ref() == key.as_bytes())
+                        .and_then(|a| {
+                            a.normalized_value(XmlVersion::Implicit1_0)
+                                .ok()
+                                .map(|v| v.trim().to_string())
+                        })
+                        .filter(|v| !v.is_empty())
+                };
+                let site_url = value_of("xmlUrl");
+                let search_url = value_of(ATTR_SEARCH_URL);
+                if site_url.is_none() && search_url.is_none() {
+                    continue;
+                }
+                let number = |key: &str| value_of(key).and_then(|v| v.parse().ok());
+                outlines.push(FeedOutline {
+                    title: value_of("title")
+                        .or_else(|| value_of("text"))
+                        .unwrap_or_default(),
+                    site_url,
+                    search_url,
+                    parser: value_of(ATTR_PARSER),
+                    schedule: FeedSchedule {
+                        poll_interval: number(ATTR_POLL_INTERVAL),
+                        min_spacing: number(ATTR_MIN_SPACING),
+                    },
+                });
+            }
+            _ => {}
+        }
+    }
+    if !found {
+        return Err("missing opml element".to_string());
+    }
+    Ok(outlines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read_outlines() {
+        let outlines = vec![
+            FeedOutline {
+                title: "Mikan & Friends".to_string(),
+                site_url: Some("https://mikanani.me/RSS/Classic".to_string()),
+                search_url: Some("https://mikanani.me/RSS/Search?searchstr={}".to_string()),
+                parser: Some("mikan".to_string()),
+                schedule: FeedSchedule {
+                    poll_interval: Some(600),
+                    min_spacing: None,
+                },
+            },
+            FeedOutline {
+                title: "Jackett".to_string(),
+                site_url: None,
+                search_url: Some(
+                    "http://127.0.0.1:9117/api/v2.0/indexers/all/results/torznab/api?apikey=k&t=search"
+                        .to_string(),
+                ),
+                parser: None,
+                schedule: FeedSchedule::default(),
+            },
+        ];
+        let content = write_opml("feeds", &outlines);
+        assert!(content.contains(r#"text="Mikan &amp; Friends""#));
+        assert!(content.contains("apikey=k&amp;t=search"));
+        assert_eq!(read_opml(&content).unwrap(), outlines);
+    }
+
+    #[test]
+    fn read_third_party_opml() {
+        let content = r#"<?xml version="1.0"?>
+<opml version="1.0">
+  <body>
+    <outline text="Anime">
+      <outline text="Nyaa" type="rss" xmlUrl="https://nyaa.si/?page=rss" htmlUrl="https://nyaa.si/"/>
+    </outline>
+  </body>
+</opml>"#;
+        assert_eq!(
+            read_opml(content).unwrap(),
+            vec![FeedOutline {
+                title: "Nyaa".to_string(),
+                site_url: Some("https://nyaa.si/?page=rss".to_string()),
+                ..Default::default()
+            }]
+        );
+        assert!(read_opml("<rss></rss>").is_err());
+    }
+}
@@ -1,8 +1,162 @@
-use formatx::formatx;
+use url::{Url, form_urlencoded};
 
-use common::shared::{cap::FeedSearchUrlProvider, model::SearchUrls};
+use common::shared::{
+    cap::FeedSearchUrlProvider,
+    error::Error,
+    model::{SearchQuery, SearchUrls},
+};
 
-use crate::entity::model::FeedProp;
+use crate::entity::model::{FeedProp, TORZNAB_PARSER};
+
+/// 搜索地址模板中的片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder, usize),
+}
+
+/// 搜索地址模板支持的命名占位符，`{}` 等同于 `{keyword_raw}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    /// 关键字，按查询参数编码（空格为 `+`，非 ASCII 字符百分号编码）
+    Keyword,
+    /// 关键字原文，用于路径中或站点自行处理编码的情况
+    KeywordRaw,
+    Season,
+    Episode,
+    Year,
+}
+
+impl Placeholder {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "" | "keyword_raw" => Self::KeywordRaw,
+            "keyword" => Self::Keyword,
+            "season" => Self::Season,
+            "episode" => Self::Episode,
+            "year" => Self::Year,
+            _ => return None,
+        })
+    }
+
+    fn is_number(self) -> bool {
+        matches!(self, Self::Season | Self::Episode | Self::Year)
+    }
+}
+
+/// 解析搜索地址模板：`{{`、`}}` 为花括号转义，数字类占位符可用 `{episode:02}` 指定补零宽度
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = vec![];
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        let (text, tail) = rest.split_at(pos);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        if let Some(tail) = tail.strip_prefix("{{") {
+            segments.push(Segment::Text("{"));
+            rest = tail;
+            continue;
+        }
+        if let Some(tail) = tail.strip_prefix("}}") {
+            segments.push(Segment::Text("}"));
+            rest = tail;
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err("unmatched '}' in search url".to_string());
+        }
+        let end = tail
+            .find('}')
+            .ok_or_else(|| "unclosed '{' in search url".to_string())?;
+        let inner = &tail[1..end];
+        let (name, spec) = inner.split_once(':').unwrap_or((inner, ""));
+        let placeholder = Placeholder::parse(name.trim())
+            .ok_or_else(|| format!("unknown search url placeholder {{{}}}", inner))?;
+        let width = match spec {
+            "" => 0,
+            spec if placeholder.is_number() => spec
+                .strip_prefix('0')
+                .and_then(|w| w.parse().ok())
+                .ok_or_else(|| format!("invalid format spec in {{{}}}", inner))?,
+            _ => return Err(format!("placeholder {{{}}} does not accept format", inner)),
+        };
+        segments.push(Segment::Placeholder(placeholder, width));
+        rest = &tail[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    Ok(segments)
+}
+
+/// 校验搜索地址模板，保存 feed 时调用
+pub(super) fn validate_search_url(template: &str) -> Result<(), Error> {
+    let segments = parse_template(template).map_err(Error::conflict)?;
+    // 占位符替换为示例值后须为合法地址
+    let sample = render(&segments, &SearchQuery::new("sample"));
+    Url::parse(&sample)
+        .map_err(|e| Error::conflict(format!("invalid search url {}: {}", template, e)))?;
+    Ok(())
+}
+
+fn render(segments: &[Segment<'_>], query: &SearchQuery) -> String {
+    let number = |value: Option<i64>, width: usize| {
+        value
+            .map(|v| format!("{:0width$}", v, width = width))
+            .unwrap_or_default()
+    };
+    segments
+        .iter()
+        .map(|segment| match *segment {
+            Segment::Text(text) => text.to_string(),
+            Segment::Placeholder(Placeholder::Keyword, _) => {
+                form_urlencoded::byte_serialize(query.keyword.as_bytes()).collect()
+            }
+            Segment::Placeholder(Placeholder::KeywordRaw, _) => query.keyword.clone(),
+            Segment::Placeholder(Placeholder::Season, width) => {
+                number(query.season.map(i64::from), width)
+            }
+            Segment::Placeholder(Placeholder::Episode, width) => {
+                number(query.episode.map(i64::from), width)
+            }
+            Segment::Placeholder(Placeholder::Year, width) => {
+                number(query.year.map(i64::from), width)
+            }
+        })
+        .collect()
+}
+
+/// 展开 feed 搜索地址：模板包含占位符时按模板替换，缺少的季度、集数等替换为空；
+/// Torznab 订阅源可只配置 API 地址，由此设置 `t=search&q=` 查询参数
+pub(super) fn expand_search_url(
+    template: &str,
+    parser: Option<&str>,
+    query: &SearchQuery,
+) -> Result<String, Error> {
+    let segments = parse_template(template)
+        .map_err(|e| Error::conflict(format!("format search url {} failed, {}", template, e)))?;
+    let has_placeholder = segments
+        .iter()
+        .any(|s| matches!(s, Segment::Placeholder(..)));
+    if parser != Some(TORZNAB_PARSER) || has_placeholder {
+        return Ok(render(&segments, query));
+    }
+
+    let mut url = Url::parse(template)
+        .map_err(|e| Error::external(format!("invalid torznab api url {}", template), e))?;
+    let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| k != "t" && k != "q")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("t", "search")
+        .append_pair("q", &query.keyword)
+        .extend_pairs(pairs);
+    Ok(url.into())
+}
 
 #[derive(Clone)]
 pub struct SearchFeeds {
@@ -16,21 +170,23 @@ impl SearchFeeds {
 }
 
 impl FeedSearchUrlProvider for SearchFeeds {
-    fn made_search_url(&self, keywords: &[String]) -> Vec<SearchUrls> {
+    fn made_search_url(&self, queries: &[SearchQuery]) -> Vec<SearchUrls> {
         let mut res = vec![];
         for feed in &self.data {
             let mut urls = SearchUrls {
                 feed_id: feed.data.id,
+                parser: feed.data.metadata.parser.clone(),
+                min_spacing: feed.data.metadata.schedule.min_spacing,
+                request_options: feed.data.metadata.request_options.clone(),
                 urls: Vec::new(),
             };
-            for key in keywords {
+            for query in queries {
                 if let Some(template) = &feed.data.metadata.search_url {
-                    if let Ok(v) = formatx!(template, key) {
-                        urls.urls.push(v);
-                    } else {
-                        tracing::error!(
-                            "made_search_url formatx search url failed, template={template}, key={key}"
-                        );
+                    match expand_search_url(template, feed.data.metadata.parser.as_deref(), query) {
+                        Ok(v) => urls.urls.push(v),
+                        Err(e) => {
+                            tracing::error!("made_search_url failed, key={}, {e}", query.keyword)
+                        }
                     }
                 }
             }
@@ -39,3 +195,74 @@ impl FeedSearchUrlProvider for SearchFeeds {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_torznab_search_url() {
+        assert_eq!(
+            expand_search_url(
+                "http://127.0.0.1:9696/1/api?apikey=k&t=search&q=old",
+                Some(TORZNAB_PARSER),
+                &SearchQuery::new("败犬 女主")
+            )
+            .unwrap(),
+            "http://127.0.0.1:9696/1/api?t=search&q=%E8%B4%A5%E7%8A%AC+%E5%A5%B3%E4%B8%BB&apikey=k"
+        );
+        assert_eq!(
+            expand_search_url(
+                "https://nyaa.si/?page=rss&q={}",
+                Some("nyaa"),
+                &SearchQuery::new("败犬")
+            )
+            .unwrap(),
+            "https://nyaa.si/?page=rss&q=败犬"
+        );
+    }
+
+    #[test]
+    fn expand_named_placeholders() {
+        let query = SearchQuery {
+            keyword: "败犬 女主".to_string(),
+            season: Some(2),
+            episode: Some(5),
+            year: Some(2024),
+        };
+        assert_eq!(
+            expand_search_url(
+                "https://nyaa.si/?page=rss&c=1_3&q={keyword}+S{season:02}E{episode:02}&y={year}",
+                Some("nyaa"),
+                &query
+            )
+            .unwrap(),
+            "https://nyaa.si/?page=rss&c=1_3&q=%E8%B4%A5%E7%8A%AC+%E5%A5%B3%E4%B8%BB+S02E05&y=2024"
+        );
+        assert_eq!(
+            expand_search_url(
+                "https://example.com/search/{keyword_raw}?e={episode}&s={season}&x={{}}",
+                None,
+                &SearchQuery::new("败犬")
+            )
+            .unwrap(),
+            "https://example.com/search/败犬?e=&s=&x={}"
+        );
+        // 含占位符的 Torznab 地址按模板展开
+        assert_eq!(
+            expand_search_url(
+                "http://127.0.0.1:9696/1/api?apikey=k&t=tvsearch&q={keyword}&season={season}",
+                Some(TORZNAB_PARSER),
+                &query
+            )
+            .unwrap(),
+            "http://127.0.0.1:9696/1/api?apikey=k&t=tvsearch&q=%E8%B4%A5%E7%8A%AC+%E5%A5%B3%E4%B8%BB&season=2"
+        );
+
+        assert!(validate_search_url("https://nyaa.si/?q={keyword}&ep={episode:03}").is_ok());
+        assert!(validate_search_url("https://nyaa.si/?q={title}").is_err());
+        assert!(validate_search_url("https://nyaa.si/?q={keyword:02}").is_err());
+        assert!(validate_search_url("https://nyaa.si/?q={keyword").is_err());
+        assert!(validate_search_url("nyaa.si/?q={keyword}").is_err());
+    }
+}
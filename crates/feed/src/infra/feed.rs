@@ -2,48 +2,44 @@ use anyhow::Result;
 use async_trait::async_trait;
 use base32::Alphabet;
 use chrono::Utc;
-use reqwest::Client;
-use rss::{Channel, Item};
+use common::{
+    infra::http::base_client_builder,
+    shared::{
+        episode::{EpisodeNumber, collection_marker, parse_episode_number},
+        info_hash::InfoHash,
+    },
+};
+use dashmap::DashMap;
+use reqwest::{
+    Client, Proxy, RequestBuilder, StatusCode,
+    header::{
+        COOKIE, ETAG, HeaderMap, HeaderName, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+};
+use rss::Item;
 use serde::{Deserialize, Serialize};
+use serde_bencode::value::Value;
 use sha1::{Digest, Sha1};
-use std::{collections::HashMap, io::Cursor, sync::Arc};
+use sha2::Sha256;
+use std::{collections::HashMap, sync::Arc};
 use tracing::{error, warn};
 use url::Url;
 
-use crate::entity::{
-    cap::FeedFetcher,
-    model::{FeedData, FeedFetchError, FeedItem},
+use crate::{
+    entity::{
+        cap::FeedFetcher,
+        model::{
+            FeedCacheMark, FeedData, FeedFetchError, FeedItem, FeedListedItem, FeedPreview,
+            FeedPreviewEntry, FeedRequestOptions, FeedSeenItem, PushedEntries, PushedItem,
+            ResourceKind, TORZNAB_PARSER, TorrentFileInfo,
+        },
+    },
+    infra::{feed_format::read_channel, torznab},
 };
 
-use regex::Regex;
-use std::sync::OnceLock;
-
-/// 判断是否为合集/打包类资源（准确排除单集 END/完结 标识）
-fn is_collection_resource(title: &str) -> bool {
-    static COLLECTION_RE: OnceLock<Regex> = OnceLock::new();
-
-    let re = COLLECTION_RE.get_or_init(|| {
-        Regex::new(r#"(?x)
-            # 1. 明确的合集关键词（不管是否有数字，直接命中）
-            # 排除 standalone 的 Season 1 等，只有复数 Seasons 1-2 或搭配 Complete/Batch 才算
-            (?i:\b(?:Batch|Complete|Collection|Seasons?\s*\d+\s*[-~至]\s*\d+)\b|合集|全集|全套|全话|整季|季度全集|打包) |
-            
-            # 2. 括号内的集数区间：[01-12], [01~24], (01-13), [01v2-12v2], [Ep01-Ep12]
-            # 关键保护：严格限制数字必须有 '-' 或 '~' 连接，如 [12 END] 没有连字符就不会命中
-            # 并且限制首个数字为 1~3 位，防止匹配到日期 [2025-01-08]
-            (?:\[|\() \s* (?:[Eｅ][Pｐ]?)?\d{1,3}(?:[vV]\d)? \s* [-~至] \s* (?:[Eｅ][Pｐ]?)?\d{1,3}(?:[vV]\d)? \s* (?:END|完结|完)? \s* (?:\]|\)) |
-            
-            # 3. 中文集数区间格式：第01-12话, 第01~24集(完结)
-            第 \s* \d{1,3} \s* [-~至] \s* \d{1,3} \s* [话集] |
-            
-            # 4. 无括号但带有连字符且以 END/完结 结尾的区间： " 01-12 END ", "- 01~24 完结 -"
-            # 必须满足 [数字]-[数字]+[END/完结]，从而安全放过 " - 12 END " (无连字符区间)
-            (?:\s|_|-)(?:[Eｅ][Pｐ]?)?\d{2,3}(?:[vV]\d)?\s*[-~至]\s*(?:[Eｅ][Pｐ]?)?\d{2,3}(?:[vV]\d)?\s*(?:END|完结|全集)(?:\s|_|\[|\(|$)
-        "#).expect("Invalid regex for collection filtering")
-    });
-
-    re.is_match(title)
-}
+/// 外部推送的条目没有所属的 feed 地址，日志中以此标识来源
+const PUSH_SOURCE: &str = "push";
 
 #[async_trait]
 pub trait FeedItemRepository: Send + Sync {
@@ -51,9 +47,45 @@ pub trait FeedItemRepository: Send + Sync {
     async fn get_url_info_hash(&self, urls: Vec<&str>) -> Result<HashMap<String, [u8; 20]>>;
 }
 
+/// feed url 对应的 HTTP 缓存校验信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedHttpCache {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// 上次完整处理的响应体摘要
+    pub body_hash: String,
+    pub source_key: String,
+    /// 上次完整处理时列出的条目
+    pub listed: Vec<FeedListedItem>,
+}
+
+#[async_trait]
+pub trait FeedHttpCacheRepository: Send + Sync {
+    async fn get_http_cache(&self, url: &str) -> Result<Option<FeedHttpCache>>;
+    async fn save_http_cache(&self, url: &str, cache: &FeedHttpCache) -> Result<()>;
+}
+
 struct ParsedFeed {
     pub source_key: String,
     pub items: Vec<ParsedItem>,
+    /// 因数据缺失或无效被跳过的条目
+    pub skipped: Vec<SkippedItem>,
+}
+
+struct SkippedItem {
+    pub title: Option<String>,
+    pub source_url: Option<String>,
+    pub reason: String,
+}
+
+impl From<SkippedItem> for FeedPreviewEntry {
+    fn from(value: SkippedItem) -> Self {
+        FeedPreviewEntry::Skipped {
+            title: value.title,
+            source_url: value.source_url,
+            reason: value.reason,
+        }
+    }
 }
 
 struct ParsedItem {
@@ -61,18 +93,110 @@ struct ParsedItem {
     pub source_url: String,
     pub resource_url: String,
     pub published_at: i64,
-    pub info_hash: [u8; 20],
+    /// 为空时需下载种子文件计算
+    pub info_hash: InfoHash,
+    pub kind: ResourceKind,
+    pub episode_range: Option<(u32, u32)>,
+    /// 被识别为合集时命中的标题片段
+    pub collection_marker: Option<String>,
+    pub size: Option<u64>,
 }
 
 #[derive(Clone)]
 pub struct HttpFeedFetcher {
     client: Client,
     repo: Arc<dyn FeedItemRepository>,
+    cache_repo: Arc<dyn FeedHttpCacheRepository>,
+    parsers: Arc<FeedParserRegistry>,
+    /// 代理地址 -> 使用该代理的 client
+    proxy_clients: Arc<DashMap<String, Client>>,
 }
 
 impl HttpFeedFetcher {
-    pub fn new(client: Client, repo: Arc<dyn FeedItemRepository>) -> Self {
-        Self { client, repo }
+    pub fn new(
+        client: Client,
+        repo: Arc<dyn FeedItemRepository>,
+        cache_repo: Arc<dyn FeedHttpCacheRepository>,
+    ) -> Self {
+        Self {
+            client,
+            repo,
+            cache_repo,
+            parsers: Arc::new(FeedParserRegistry::default()),
+            proxy_clients: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// 根据 feed 的请求选项构造请求，代理在 client 级别生效，因此按代理地址缓存 client
+    fn request(
+        &self,
+        url: &str,
+        options: &FeedRequestOptions,
+    ) -> Result<RequestBuilder, FeedFetchError> {
+        let client = match &options.proxy {
+            Some(proxy) => self.proxy_client(proxy)?,
+            None => self.client.clone(),
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &options.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                FeedFetchError::InvalidData(format!("invalid header name {}: {}", name, e))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                FeedFetchError::InvalidData(format!("invalid header value of {}: {}", name, e))
+            })?;
+            headers.insert(name, value);
+        }
+        if let Some(cookies) = &options.cookies {
+            let value = HeaderValue::from_str(cookies)
+                .map_err(|e| FeedFetchError::InvalidData(format!("invalid cookies: {}", e)))?;
+            headers.insert(COOKIE, value);
+        }
+
+        Ok(client.get(url).headers(headers))
+    }
+
+    fn proxy_client(&self, proxy: &str) -> Result<Client, FeedFetchError> {
+        if let Some(client) = self.proxy_clients.get(proxy) {
+            return Ok(client.clone());
+        }
+        // 代理 client 与默认 client 使用相同的基础设置
+        let client = base_client_builder()
+            .proxy(
+                Proxy::all(proxy)
+                    .map_err(|e| FeedFetchError::InvalidData(format!("invalid proxy: {}", e)))?,
+            )
+            .build()
+            .map_err(|e| {
+                FeedFetchError::InvalidData(format!("build proxy client failed: {}", e))
+            })?;
+        self.proxy_clients.insert(proxy.to_string(), client.clone());
+        Ok(client)
+    }
+
+    /// 内容未变化时，上次列出的条目仍在 feed 中，按入库时的资源标识返回以刷新来源记录
+    async fn listed_seen(&self, url: &str, listed: &[FeedListedItem]) -> Vec<FeedSeenItem> {
+        if listed.is_empty() {
+            return vec![];
+        }
+        let urls = listed.iter().map(|i| i.resource_url.as_str()).collect();
+        let hashes = match self.repo.get_url_info_hash(urls).await {
+            Ok(map) => map,
+            Err(e) => {
+                error!(feed_url = %url, error = %e, "failed to query info_hash cache from repo, skip refreshing seen items");
+                return vec![];
+            }
+        };
+        listed
+            .iter()
+            .filter_map(|i| {
+                hashes.get(&i.resource_url).map(|info_hash| FeedSeenItem {
+                    info_hash: *info_hash,
+                    source_url: i.source_url.clone(),
+                })
+            })
+            .collect()
     }
 
     fn handle_status_error(url: &str, status: reqwest::StatusCode) -> FeedFetchError {
@@ -86,25 +210,90 @@ impl HttpFeedFetcher {
 
 #[async_trait]
 impl FeedFetcher for HttpFeedFetcher {
-    async fn fetch_url(&self, url: &str) -> Result<FeedData, FeedFetchError> {
-        let response = self
-            .client
-            .get(url)
+    async fn fetch_url(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        options: &FeedRequestOptions,
+    ) -> Result<FeedData, FeedFetchError> {
+        let parser = self.parsers.resolve(parser, url)?;
+
+        // 读取上次的缓存校验信息，如果 Repo 报错则降级为无条件请求
+        let http_cache = match self.cache_repo.get_http_cache(url).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(feed_url = %url, error = %e, "failed to query http cache from repo, falling back to full request");
+                None
+            }
+        };
+
+        let mut request = self.request(url, options)?;
+        if let Some(cache) = &http_cache {
+            if let Some(etag) = &cache.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cache.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| FeedFetchError::Retryable(e.to_string()))?;
 
         let status = response.status();
+        if status == StatusCode::NOT_MODIFIED
+            && let Some(cache) = http_cache
+        {
+            tracing::debug!(feed_url = %url, "feed not modified");
+            return Ok(FeedData {
+                seen: self.listed_seen(url, &cache.listed).await,
+                source_key: cache.source_key,
+                items: vec![],
+                cache: None,
+            });
+        }
         if !status.is_success() {
             return Err(Self::handle_status_error(url, status));
         }
 
+        let header_value = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(String::from)
+        };
+        let etag = header_value(ETAG);
+        let last_modified = header_value(LAST_MODIFIED);
+
         let body = response
             .bytes()
             .await
             .map_err(|e| FeedFetchError::Retryable(format!("failed to read body: {}", e)))?;
 
-        let parsed = parse_feed(&body, url)?;
+        // 服务端不支持条件请求时，通过响应体摘要判断内容是否变化
+        // 摘要包含解析器名称，切换解析器后需要重新解析
+        let body_hash = hex::encode(
+            Sha1::new()
+                .chain_update(parser.name())
+                .chain_update(&body)
+                .finalize(),
+        );
+        if let Some(cache) = http_cache
+            && cache.body_hash == body_hash
+        {
+            tracing::debug!(feed_url = %url, "feed body unchanged, skip parsing");
+            return Ok(FeedData {
+                seen: self.listed_seen(url, &cache.listed).await,
+                source_key: cache.source_key,
+                items: vec![],
+                cache: None,
+            });
+        }
+
+        let parsed = parse_feed(parser, &body, url)?;
 
         // 搜集所有 URL
         let all_urls: Vec<&str> = parsed
@@ -127,54 +316,152 @@ impl FeedFetcher for HttpFeedFetcher {
         };
 
         let mut items = Vec::with_capacity(parsed.items.len());
+        let mut seen = vec![];
+        let mut listed = Vec::with_capacity(parsed.items.len());
+        // 存在获取失败的条目时不写入缓存，保证下次轮询仍会重试
+        let mut complete = true;
         for mut item in parsed.items {
-            // 命中缓存的旧数据不返回给上游
-            if cached_hashes.contains_key(&item.resource_url) {
+            // 命中缓存的旧数据不再解析入库，只用于刷新来源记录
+            if let Some(info_hash) = cached_hashes.get(&item.resource_url) {
+                listed.push(FeedListedItem {
+                    resource_url: item.resource_url,
+                    source_url: item.source_url.clone(),
+                });
+                seen.push(FeedSeenItem {
+                    info_hash: *info_hash,
+                    source_url: item.source_url,
+                });
                 continue;
             }
-            if item.info_hash == [0u8; 20] {
-                if let Some(hash) = self.download_torrent_hash(url, &item).await {
-                    item.info_hash = hash;
+            let mut files = vec![];
+            let mut bytes = None;
+            if item.info_hash.is_empty() {
+                if let Some(torrent) = self.download_torrent(url, &item, options).await {
+                    item.info_hash = torrent.info_hash;
+                    files = torrent.files;
+                    bytes = Some(torrent.bytes);
                 } else {
+                    complete = false;
                     continue; // 彻底获取失败，跳过该脏数据
                 }
             }
+            let Some(info_hash) = item.info_hash.id() else {
+                continue;
+            };
 
+            listed.push(FeedListedItem {
+                resource_url: item.resource_url.clone(),
+                source_url: item.source_url.clone(),
+            });
             items.push(FeedItem {
                 title: item.title,
                 source_url: item.source_url,
                 resource_url: item.resource_url,
                 published_at: item.published_at,
-                info_hash: item.info_hash,
+                info_hash,
+                info_hash_v2: item.info_hash.v2,
+                kind: item.kind,
+                episode_range: item.episode_range,
+                files,
+                size: item.size,
+                torrent: bytes,
             });
         }
 
+        let cache = complete.then(|| FeedCacheMark {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            body_hash,
+            source_key: parsed.source_key.clone(),
+            listed,
+        });
+
         Ok(FeedData {
             source_key: parsed.source_key,
             items,
+            seen,
+            cache,
         })
     }
 
-    async fn get_source_key(&self, url: &str) -> Result<String, FeedFetchError> {
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| FeedFetchError::Retryable(e.to_string()))?;
+    async fn save_cache(&self, mark: &FeedCacheMark) {
+        let cache = FeedHttpCache {
+            etag: mark.etag.clone(),
+            last_modified: mark.last_modified.clone(),
+            body_hash: mark.body_hash.clone(),
+            source_key: mark.source_key.clone(),
+            listed: mark.listed.clone(),
+        };
+        if let Err(e) = self.cache_repo.save_http_cache(&mark.url, &cache).await {
+            error!(feed_url = %mark.url, error = %e, "failed to save http cache");
+        }
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            return Err(Self::handle_status_error(url, status));
+    async fn preview_url(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        options: &FeedRequestOptions,
+    ) -> Result<FeedPreview, FeedFetchError> {
+        let parser = self.parsers.resolve(parser, url)?;
+        let body = self.get_body(url, options).await?;
+        let parsed = parse_feed(parser, &body, url)?;
+
+        let (mut entries, _) = self
+            .resolve_entries(url, parsed.items, options, parsed.skipped.len(), true)
+            .await;
+        entries.extend(parsed.skipped.into_iter().map(Into::into));
+
+        Ok(FeedPreview {
+            source_key: parsed.source_key,
+            entries,
+        })
+    }
+
+    async fn resolve_pushed(&self, items: Vec<PushedItem>, download: bool) -> PushedEntries {
+        let mut parsed = Vec::with_capacity(items.len());
+        let mut skipped = vec![];
+        for item in items {
+            match parse_pushed_item(item) {
+                Ok(v) => parsed.push(v),
+                Err(v) => skipped.push(v),
+            }
         }
 
-        let body = response
-            .bytes()
-            .await
-            .map_err(|e| FeedFetchError::Retryable(format!("failed to read body: {}", e)))?;
+        let (mut entries, pending) = self
+            .resolve_entries(
+                PUSH_SOURCE,
+                parsed,
+                &FeedRequestOptions::default(),
+                skipped.len(),
+                download,
+            )
+            .await;
+        entries.extend(skipped.into_iter().map(Into::into));
+        PushedEntries {
+            entries,
+            pending: pending.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    async fn get_source_key(
+        &self,
+        url: &str,
+        parser: Option<&str>,
+        options: &FeedRequestOptions,
+    ) -> Result<String, FeedFetchError> {
+        let parser = self.parsers.resolve(parser, url)?;
+        if let Some(caps_url) = parser.caps_url(url) {
+            self.check_caps(&caps_url, options).await?;
+        }
+        let body = self.get_body(url, options).await?;
+        // 校验订阅源时保持严格解码，不接受非 UTF-8 的响应体
+        std::str::from_utf8(&body)
+            .map_err(|e| FeedFetchError::InvalidData(format!("invalid utf-8 body: {}", e)))?;
 
-        let channel = Channel::read_from(Cursor::new(body.as_ref()))
-            .map_err(|e| FeedFetchError::InvalidData(e.to_string()))?;
+        let channel =
+            read_channel(&parser.preprocess_xml(&body)).map_err(FeedFetchError::InvalidData)?;
 
         let title = channel.title().trim();
         if title.is_empty() {
@@ -196,8 +483,138 @@ impl FeedFetcher for HttpFeedFetcher {
 }
 
 impl HttpFeedFetcher {
-    async fn download_torrent_hash(&self, feed_url: &str, item: &ParsedItem) -> Option<[u8; 20]> {
-        let bytes = match self.client.get(&item.resource_url).send().await {
+    async fn get_body(
+        &self,
+        url: &str,
+        options: &FeedRequestOptions,
+    ) -> Result<Vec<u8>, FeedFetchError> {
+        let response = self
+            .request(url, options)?
+            .send()
+            .await
+            .map_err(|e| FeedFetchError::Retryable(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Self::handle_status_error(url, status));
+        }
+
+        response
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| FeedFetchError::Retryable(format!("failed to read body: {}", e)))
+    }
+
+    /// 查询 Torznab 服务能力，确认 API Key 有效且支持关键字搜索
+    async fn check_caps(
+        &self,
+        caps_url: &str,
+        options: &FeedRequestOptions,
+    ) -> Result<(), FeedFetchError> {
+        let body = self.get_body(caps_url, options).await?;
+        let caps = torznab::parse_caps(&String::from_utf8_lossy(&body))
+            .map_err(FeedFetchError::Inaccessible)?;
+        if !caps.search {
+            return Err(FeedFetchError::InvalidData(format!(
+                "torznab server {} does not support search",
+                caps.server.as_deref().unwrap_or(caps_url)
+            )));
+        }
+        Ok(())
+    }
+
+    /// 补全条目的 info hash：优先使用已入库的记录，否则下载种子计算；不过滤已入库的资源
+    ///
+    /// `download` 为假时不下载种子，需要下载的条目原样返回
+    async fn resolve_entries(
+        &self,
+        feed_url: &str,
+        items: Vec<ParsedItem>,
+        options: &FeedRequestOptions,
+        extra_capacity: usize,
+        download: bool,
+    ) -> (Vec<FeedPreviewEntry>, Vec<ParsedItem>) {
+        let all_urls: Vec<&str> = items
+            .iter()
+            .map(|item| item.resource_url.as_str())
+            .collect();
+        let cached_hashes = if !all_urls.is_empty() {
+            self.repo
+                .get_url_info_hash(all_urls)
+                .await
+                .unwrap_or_else(|e| {
+                    error!(feed_url = %feed_url, error = %e, "failed to query info_hash cache from repo, falling back to network");
+                    HashMap::new()
+                })
+        } else {
+            HashMap::new()
+        };
+
+        let mut entries = Vec::with_capacity(items.len() + extra_capacity);
+        let mut pending = vec![];
+        for mut item in items {
+            let mut files = vec![];
+            let mut bytes = None;
+            if item.info_hash.is_empty() {
+                if let Some(hash) = cached_hashes.get(&item.resource_url) {
+                    item.info_hash = InfoHash::from_id(*hash, None);
+                } else if !download {
+                    pending.push(item);
+                    continue;
+                } else if let Some(torrent) = self.download_torrent(feed_url, &item, options).await
+                {
+                    item.info_hash = torrent.info_hash;
+                    files = torrent.files;
+                    bytes = Some(torrent.bytes);
+                } else {
+                    entries.push(FeedPreviewEntry::Skipped {
+                        title: Some(item.title),
+                        source_url: Some(item.source_url),
+                        reason: "failed to download or parse torrent".to_string(),
+                    });
+                    continue;
+                }
+            }
+            let Some(info_hash) = item.info_hash.id() else {
+                continue;
+            };
+
+            entries.push(FeedPreviewEntry::Parsed {
+                item: FeedItem {
+                    title: item.title,
+                    source_url: item.source_url,
+                    resource_url: item.resource_url,
+                    published_at: item.published_at,
+                    info_hash,
+                    info_hash_v2: item.info_hash.v2,
+                    kind: item.kind,
+                    episode_range: item.episode_range,
+                    files,
+                    size: item.size,
+                    torrent: bytes,
+                },
+                collection_marker: item.collection_marker,
+            });
+        }
+        (entries, pending)
+    }
+
+    /// 下载种子并解析 info hash 及文件列表，种子内容随条目返回，由入库方按最终的资源标识保存
+    async fn download_torrent(
+        &self,
+        feed_url: &str,
+        item: &ParsedItem,
+        options: &FeedRequestOptions,
+    ) -> Option<ParsedTorrent> {
+        let request = match self.request(&item.resource_url, options) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(feed_url = %feed_url, resource_url = %item.resource_url, error = %e, "failed to build torrent request");
+                return None;
+            }
+        };
+        let bytes = match request.send().await {
             Ok(resp) => match resp.bytes().await {
                 Ok(b) => b,
                 Err(e) => {
@@ -211,22 +628,26 @@ impl HttpFeedFetcher {
             }
         };
 
-        match torrent_info_hash(&bytes) {
-            Ok(h) => Some(h),
+        let torrent = match parse_torrent(&bytes) {
+            Ok(v) => v,
             Err(e) => {
                 error!(feed_url = %feed_url, title = %item.title, resource_url = %item.resource_url, error = %e, "failed to compute torrent hash");
-                None
+                return None;
             }
-        }
+        };
+
+        Some(torrent)
     }
 }
 
-fn parse_feed(content: &[u8], feed_url: &str) -> Result<ParsedFeed, FeedFetchError> {
-    let parser = get_feed_parser(feed_url);
+fn parse_feed(
+    parser: &dyn FeedParser,
+    content: &[u8],
+    feed_url: &str,
+) -> Result<ParsedFeed, FeedFetchError> {
     let xml_str = parser.preprocess_xml(content);
 
-    let channel = Channel::read_from(Cursor::new(xml_str.as_bytes()))
-        .map_err(|e| FeedFetchError::InvalidData(e.to_string()))?;
+    let channel = read_channel(&xml_str).map_err(FeedFetchError::InvalidData)?;
 
     let channel_title = channel.title().trim();
     if channel_title.is_empty() {
@@ -245,33 +666,60 @@ fn parse_feed(content: &[u8], feed_url: &str) -> Result<ParsedFeed, FeedFetchErr
 
     let source_key = build_source_key(channel_link);
     let mut items = Vec::new();
+    let mut skipped = Vec::new();
 
     for item in channel.items() {
         let Some(source_url) = parser.extract_source_url(item) else {
             warn!(feed_url = %feed_url, title = ?item.title(), "skipping rss item missing source link");
+            skipped.push(SkippedItem {
+                title: item.title().map(String::from),
+                source_url: None,
+                reason: "missing source link".to_string(),
+            });
             continue;
         };
 
         let Some(title) = item.title().map(str::trim).filter(|s| !s.is_empty()) else {
             warn!(feed_url = %feed_url, link = %source_url, "skipping rss item without title");
+            skipped.push(SkippedItem {
+                title: None,
+                source_url: Some(source_url),
+                reason: "missing title".to_string(),
+            });
             continue;
         };
 
-        // 过滤掉集合类资源
-        if is_collection_resource(title) {
-            tracing::debug!(feed_url = %feed_url, title = %title, "skipping collection/batch resource");
-            continue;
-        }
+        let (kind, episode_range, collection_marker) = classify_title(title);
 
         let Some(resource_url) = parser.extract_resource_url(item) else {
             warn!(feed_url = %feed_url, title = %title, "skipping rss item without resource url");
+            skipped.push(SkippedItem {
+                title: Some(title.to_string()),
+                source_url: Some(source_url),
+                reason: "missing resource url".to_string(),
+            });
             continue;
         };
 
+        // 搜索源返回的无人做种资源无法下载
+        if parser.extract_seeders(item) == Some(0) {
+            skipped.push(SkippedItem {
+                title: Some(title.to_string()),
+                source_url: Some(source_url),
+                reason: "no seeders".to_string(),
+            });
+            continue;
+        }
+
         let info_hash = match parser.extract_info_hash(item, &resource_url) {
             Ok(hash) => hash,
             Err(e) => {
                 warn!(feed_url = %feed_url, title = %title, resource_url = %resource_url, error = %e, "failed to get info_hash");
+                skipped.push(SkippedItem {
+                    title: Some(title.to_string()),
+                    source_url: Some(source_url),
+                    reason: e,
+                });
                 continue;
             }
         };
@@ -287,10 +735,106 @@ fn parse_feed(content: &[u8], feed_url: &str) -> Result<ParsedFeed, FeedFetchErr
             resource_url,
             published_at,
             info_hash,
+            kind,
+            episode_range,
+            collection_marker,
+            size: parser.extract_size(item),
         });
     }
 
-    Ok(ParsedFeed { source_key, items })
+    Ok(ParsedFeed {
+        source_key,
+        items,
+        skipped,
+    })
+}
+
+/// 合集类资源单独标记，由订阅设置决定是否接受；返回类型、集数区间及命中的标题片段
+fn classify_title(title: &str) -> (ResourceKind, Option<(u32, u32)>, Option<String>) {
+    match collection_marker(title) {
+        Some(marker) => {
+            let range = match parse_episode_number(title) {
+                Some(EpisodeNumber::Range(start, end)) => Some((start, end)),
+                _ => None,
+            };
+            (ResourceKind::Collection, range, Some(marker))
+        }
+        None => (ResourceKind::Episode, None, None),
+    }
+}
+
+/// 从资源地址获取 info hash：磁力链接直接解析，种子链接的哈希在下载种子文件后计算
+fn resource_info_hash(resource_url: &str) -> Result<InfoHash, String> {
+    if resource_url.starts_with("magnet:?") {
+        match magnet_info_hash(resource_url) {
+            Ok(Some(hash)) => Ok(hash),
+            Ok(None) => Err("skipping magnet missing valid btih".to_string()),
+            Err(e) => Err(format!("failed to extract info_hash from magnet: {}", e)),
+        }
+    } else if Url::parse(resource_url).is_ok() {
+        Ok(InfoHash::default())
+    } else {
+        Err("skipping unparseable resource url".to_string())
+    }
+}
+
+/// 按订阅源条目的规则校验外部推送的条目，info hash 与资源地址中的哈希合并
+fn parse_pushed_item(item: PushedItem) -> Result<ParsedItem, SkippedItem> {
+    let resource_url = item.resource_url.trim().to_string();
+    let source_url = item
+        .source_url
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| resource_url.clone());
+    let skip = |title: Option<String>, reason: &str| SkippedItem {
+        title,
+        source_url: Some(source_url.clone()),
+        reason: reason.to_string(),
+    };
+
+    let title = item.title.trim();
+    if title.is_empty() {
+        return Err(skip(None, "missing title"));
+    }
+    if resource_url.is_empty() {
+        return Err(skip(Some(title.to_string()), "missing resource url"));
+    }
+    let given = match item.info_hash.as_deref().map(str::trim) {
+        Some(hex) if !hex.is_empty() => match InfoHash::from_hex(hex) {
+            Some(hash) => hash,
+            None => return Err(skip(Some(title.to_string()), "invalid info hash")),
+        },
+        _ => InfoHash::default(),
+    };
+    let info_hash = resource_info_hash(&resource_url)
+        .map_err(|e| skip(Some(title.to_string()), &e))?
+        .merge(given);
+
+    let (kind, episode_range, collection_marker) = classify_title(title);
+    Ok(ParsedItem {
+        title: title.to_string(),
+        source_url,
+        resource_url,
+        published_at: item.published_at.unwrap_or_else(|| Utc::now().timestamp()),
+        info_hash,
+        kind,
+        episode_range,
+        collection_marker,
+        size: item.size.filter(|v| *v > 0),
+    })
+}
+
+impl From<ParsedItem> for PushedItem {
+    fn from(value: ParsedItem) -> Self {
+        Self {
+            title: value.title,
+            resource_url: value.resource_url,
+            source_url: Some(value.source_url),
+            published_at: Some(value.published_at),
+            info_hash: None,
+            size: value.size,
+        }
+    }
 }
 
 fn build_source_key(link: &str) -> String {
@@ -299,47 +843,173 @@ fn build_source_key(link: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-fn magnet_info_hash(resource_url: &str) -> Result<Option<[u8; 20]>, anyhow::Error> {
+/// 解析磁力链接中的 `urn:btih:` (v1) 与 `urn:btmh:` (v2)，混合种子通常同时携带两者
+fn magnet_info_hash(resource_url: &str) -> Result<Option<InfoHash>, anyhow::Error> {
     let Ok(url) = Url::parse(resource_url) else {
         return Ok(None);
     };
     if url.scheme() != "magnet" {
         return Ok(None);
     }
-    let Some((_, value)) = url.query_pairs().find(|(key, _)| key == "xt") else {
-        return Ok(None);
-    };
-    let Some(hash) = value.strip_prefix("urn:btih:") else {
-        return Ok(None);
-    };
 
-    let bytes = if hash.len() <= 32 {
-        base32::decode(Alphabet::Rfc4648 { padding: true }, &hash.to_uppercase())
-            .ok_or_else(|| anyhow::anyhow!("invalid base32 btih"))?
-    } else {
-        hex::decode(hash)?
-    };
+    let mut info_hash = InfoHash::default();
+    for (_, value) in url.query_pairs().filter(|(key, _)| key == "xt") {
+        if let Some(hash) = value.strip_prefix("urn:btih:") {
+            let bytes = if hash.len() <= 32 {
+                base32::decode(Alphabet::Rfc4648 { padding: true }, &hash.to_uppercase())
+                    .ok_or_else(|| anyhow::anyhow!("invalid base32 btih"))?
+            } else {
+                hex::decode(hash)?
+            };
+            let arr: [u8; 20] = bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("btih must be 20 bytes"))?;
+            info_hash.v1 = Some(arr);
+        } else if let Some(multihash) = value.strip_prefix("urn:btmh:") {
+            let hash = InfoHash::from_btmh(&multihash.to_lowercase())
+                .ok_or_else(|| anyhow::anyhow!("btmh must be a sha2-256 multihash"))?;
+            info_hash = info_hash.merge(hash);
+        }
+    }
+    Ok((!info_hash.is_empty()).then_some(info_hash))
+}
 
-    let arr: [u8; 20] = bytes
-        .try_into()
-        .map_err(|_| anyhow::anyhow!("btih must be 20 bytes"))?;
-    Ok(Some(arr))
+struct ParsedTorrent {
+    info_hash: InfoHash,
+    files: Vec<TorrentFileInfo>,
+    bytes: Vec<u8>,
 }
 
-fn torrent_info_hash(bytes: &[u8]) -> Result<[u8; 20], anyhow::Error> {
+/// 计算种子的 info hash：含 `pieces` 的为 v1，`meta version` 为 2 的为 v2，混合种子两者皆有
+fn parse_torrent(bytes: &[u8]) -> Result<ParsedTorrent, anyhow::Error> {
     let torrent: TorrentFile = serde_bencode::from_bytes(bytes)?;
     let info = serde_bencode::to_bytes(&torrent.info)?;
-    let mut hasher = Sha1::new();
-    hasher.update(info);
-    let hash: [u8; 20] = hasher
-        .finalize()
-        .as_slice()
-        .try_into()
-        .map_err(|_| anyhow::anyhow!("sha1 must produce 20 bytes"))?;
-    Ok(hash)
+    let Value::Dict(dict) = &torrent.info.0 else {
+        return Err(anyhow::anyhow!("torrent info must be a dict"));
+    };
+
+    let mut info_hash = InfoHash::default();
+    if dict.contains_key(b"pieces".as_slice()) {
+        info_hash.v1 = Some(Sha1::digest(&info).into());
+    }
+    if matches!(dict.get(b"meta version".as_slice()), Some(Value::Int(2))) {
+        info_hash.v2 = Some(Sha256::digest(&info).into());
+    }
+    if info_hash.is_empty() {
+        return Err(anyhow::anyhow!(
+            "torrent info has neither pieces nor meta version 2"
+        ));
+    }
+    Ok(ParsedTorrent {
+        info_hash,
+        files: torrent_files(&torrent.info.0),
+        bytes: bytes.to_vec(),
+    })
+}
+
+/// 从 info 字典中读取文件列表，兼容单文件与多文件种子，优先使用 `.utf-8` 字段
+fn torrent_files(info: &Value) -> Vec<TorrentFileInfo> {
+    let Value::Dict(info) = info else {
+        return vec![];
+    };
+    let field = |dict: &HashMap<Vec<u8>, Value>, key: &str| {
+        dict.get(format!("{}.utf-8", key).as_bytes())
+            .or_else(|| dict.get(key.as_bytes()))
+            .cloned()
+    };
+    let text = |v: &Value| match v {
+        Value::Bytes(b) => Some(String::from_utf8_lossy(b).into_owned()),
+        _ => None,
+    };
+
+    let name = field(info, "name")
+        .and_then(|v| text(&v))
+        .unwrap_or_default();
+    match info.get(b"files".as_slice()) {
+        Some(Value::List(files)) => files
+            .iter()
+            .filter_map(|file| {
+                let Value::Dict(file) = file else {
+                    return None;
+                };
+                let Some(Value::Int(size)) = file.get(b"length".as_slice()) else {
+                    return None;
+                };
+                let Some(Value::List(parts)) = field(file, "path") else {
+                    return None;
+                };
+                let path = std::iter::once(name.clone())
+                    .chain(parts.iter().filter_map(text))
+                    .filter(|p| !p.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                Some(TorrentFileInfo {
+                    path,
+                    size: (*size).max(0) as u64,
+                })
+            })
+            .collect(),
+        _ => match info.get(b"length".as_slice()) {
+            Some(Value::Int(size)) => vec![TorrentFileInfo {
+                path: name,
+                size: (*size).max(0) as u64,
+            }],
+            _ => match info.get(b"file tree".as_slice()) {
+                Some(Value::Dict(tree)) => {
+                    let mut files = vec![];
+                    file_tree_files(tree, &mut vec![], &mut files);
+                    // 单文件 v2 种子的文件树只有一个与 name 同名的文件
+                    let single = files.len() == 1 && files[0].path == name;
+                    if !single {
+                        for file in &mut files {
+                            file.path = format!("{}/{}", name, file.path);
+                        }
+                    }
+                    files
+                }
+                _ => vec![],
+            },
+        },
+    }
 }
 
-trait FeedParser {
+/// 遍历 v2 种子的 `file tree`，叶子节点以空字符串为键保存文件长度；
+/// bencode 字典按键排序，与种子内的文件顺序一致
+fn file_tree_files(
+    tree: &HashMap<Vec<u8>, Value>,
+    parents: &mut Vec<String>,
+    files: &mut Vec<TorrentFileInfo>,
+) {
+    let mut entries = tree.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in entries {
+        let Value::Dict(node) = value else {
+            continue;
+        };
+        if key.is_empty() {
+            if let Some(Value::Int(size)) = node.get(b"length".as_slice()) {
+                files.push(TorrentFileInfo {
+                    path: parents.join("/"),
+                    size: (*size).max(0) as u64,
+                });
+            }
+            continue;
+        }
+        parents.push(String::from_utf8_lossy(key).into_owned());
+        file_tree_files(node, parents, files);
+        parents.pop();
+    }
+}
+
+trait FeedParser: Send + Sync {
+    /// 注册名称，保存在 feed 配置中用于指定解析器
+    fn name(&self) -> &'static str;
+
+    /// 未指定解析器时，根据 feed url 判断是否适用
+    fn detect(&self, _feed_url: &str) -> bool {
+        false
+    }
+
     fn preprocess_xml(&self, content: &[u8]) -> String {
         String::from_utf8_lossy(content).into_owned()
     }
@@ -354,18 +1024,25 @@ trait FeedParser {
         item.enclosure().map(|e| e.url().to_string())
     }
 
-    fn extract_info_hash(&self, _item: &Item, resource_url: &str) -> Result<[u8; 20], String> {
-        if resource_url.starts_with("magnet:?") {
-            match magnet_info_hash(resource_url) {
-                Ok(Some(hash)) => Ok(hash),
-                Ok(None) => Err("skipping magnet missing valid btih".to_string()),
-                Err(e) => Err(format!("failed to extract info_hash from magnet: {}", e)),
-            }
-        } else if Url::parse(resource_url).is_ok() {
-            Ok([0u8; 20])
-        } else {
-            Err("skipping unparseable resource url".to_string())
-        }
+    fn extract_info_hash(&self, _item: &Item, resource_url: &str) -> Result<InfoHash, String> {
+        resource_info_hash(resource_url)
+    }
+
+    /// 资源大小，默认取附件声明的长度
+    fn extract_size(&self, item: &Item) -> Option<u64> {
+        item.enclosure()
+            .and_then(|e| e.length().trim().parse().ok())
+            .filter(|v| *v > 0)
+    }
+
+    /// 做种人数，仅搜索类站点提供
+    fn extract_seeders(&self, _item: &Item) -> Option<u32> {
+        None
+    }
+
+    /// 校验 feed 时需要额外查询的服务能力地址
+    fn caps_url(&self, _feed_url: &str) -> Option<String> {
+        None
     }
 
     fn extract_pub_date(&self, item: &Item) -> Option<i64> {
@@ -380,10 +1057,22 @@ trait FeedParser {
 }
 
 struct DefaultParser;
-impl FeedParser for DefaultParser {}
+impl FeedParser for DefaultParser {
+    fn name(&self) -> &'static str {
+        "default"
+    }
+}
 
 struct MikanParser;
 impl FeedParser for MikanParser {
+    fn name(&self) -> &'static str {
+        "mikan"
+    }
+
+    fn detect(&self, feed_url: &str) -> bool {
+        feed_url.contains("mikanani.me")
+    }
+
     fn preprocess_xml(&self, content: &[u8]) -> String {
         let xml_str = String::from_utf8_lossy(content).into_owned();
         // 修复 Mikan 的 RSS 数据：Mikan 移除了 'mikan' 前缀，转而对 `<torrent>` 使用默认命名空间。
@@ -429,6 +1118,14 @@ impl FeedParser for MikanParser {
 
 struct NyaaParser;
 impl FeedParser for NyaaParser {
+    fn name(&self) -> &'static str {
+        "nyaa"
+    }
+
+    fn detect(&self, feed_url: &str) -> bool {
+        feed_url.contains("nyaa.si")
+    }
+
     fn extract_source_url(&self, item: &Item) -> Option<String> {
         item.guid().map(|g| g.value.trim().to_string())
     }
@@ -439,31 +1136,123 @@ impl FeedParser for NyaaParser {
             .filter(|s| !s.is_empty())
     }
 
-    fn extract_info_hash(&self, item: &Item, resource_url: &str) -> Result<[u8; 20], String> {
+    fn extract_info_hash(&self, item: &Item, resource_url: &str) -> Result<InfoHash, String> {
         if let Some(hash_str) = item
             .extensions()
             .get("nyaa")
             .and_then(|m| m.get("infoHash"))
             .and_then(|v| v.first())
             .and_then(|e| e.value())
-            && let Ok(bytes) = hex::decode(hash_str)
-            && bytes.len() == 20
+            && let Some(hash) = InfoHash::from_hex(hash_str)
         {
-            let mut arr = [0u8; 20];
-            arr.copy_from_slice(&bytes);
-            return Ok(arr);
+            return Ok(hash);
         }
         DefaultParser.extract_info_hash(item, resource_url)
     }
 }
 
-fn get_feed_parser(feed_url: &str) -> Box<dyn FeedParser> {
-    if feed_url.contains("mikanani.me") {
-        Box::new(MikanParser)
-    } else if feed_url.contains("nyaa.si") {
-        Box::new(NyaaParser)
-    } else {
-        Box::new(DefaultParser)
+/// Jackett、Prowlarr 等索引器的 Torznab/Newznab API，
+/// 资源信息通过 `<torznab:attr>` 扩展属性提供
+struct TorznabParser;
+impl FeedParser for TorznabParser {
+    fn name(&self) -> &'static str {
+        TORZNAB_PARSER
+    }
+
+    fn detect(&self, feed_url: &str) -> bool {
+        torznab::is_api_url(feed_url)
+    }
+
+    /// `comments` 为详情页，`link` 通常是索引器代理的种子下载地址
+    fn extract_source_url(&self, item: &Item) -> Option<String> {
+        item.comments()
+            .or_else(|| item.guid().map(|g| g.value()))
+            .or_else(|| item.link())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    fn extract_resource_url(&self, item: &Item) -> Option<String> {
+        item.enclosure()
+            .map(|e| e.url())
+            .or_else(|| torznab::attr(item, "magneturl"))
+            .or_else(|| item.link())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    fn extract_info_hash(&self, item: &Item, resource_url: &str) -> Result<InfoHash, String> {
+        if let Some(hash_str) = torznab::attr(item, "infohash")
+            && let Some(hash) = InfoHash::from_hex(hash_str)
+        {
+            return Ok(hash);
+        }
+        if let Some(magnet) = torznab::attr(item, "magneturl")
+            && let Ok(Some(hash)) = magnet_info_hash(magnet)
+        {
+            return Ok(hash);
+        }
+        DefaultParser.extract_info_hash(item, resource_url)
+    }
+
+    fn extract_size(&self, item: &Item) -> Option<u64> {
+        torznab::attr(item, "size")
+            .and_then(|v| v.parse().ok())
+            .or_else(|| DefaultParser.extract_size(item))
+    }
+
+    fn extract_seeders(&self, item: &Item) -> Option<u32> {
+        torznab::attr(item, "seeders").and_then(|v| v.parse().ok())
+    }
+
+    fn caps_url(&self, feed_url: &str) -> Option<String> {
+        torznab::caps_url(feed_url)
+    }
+}
+
+/// 解析器注册表，新增站点解析器只需在 `Default` 中注册
+struct FeedParserRegistry {
+    parsers: Vec<Box<dyn FeedParser>>,
+}
+
+impl Default for FeedParserRegistry {
+    fn default() -> Self {
+        let mut registry = Self { parsers: vec![] };
+        registry.register(Box::new(DefaultParser));
+        registry.register(Box::new(MikanParser));
+        registry.register(Box::new(NyaaParser));
+        registry.register(Box::new(TorznabParser));
+        registry
+    }
+}
+
+impl FeedParserRegistry {
+    fn register(&mut self, parser: Box<dyn FeedParser>) {
+        self.parsers.push(parser);
+    }
+
+    /// 优先使用指定的解析器，未指定时按 url 自动识别，均未命中则回退到默认解析器
+    fn resolve(
+        &self,
+        name: Option<&str>,
+        feed_url: &str,
+    ) -> Result<&dyn FeedParser, FeedFetchError> {
+        if let Some(name) = name {
+            return self
+                .parsers
+                .iter()
+                .find(|p| p.name() == name)
+                .map(|p| p.as_ref())
+                .ok_or_else(|| {
+                    FeedFetchError::InvalidData(format!("unknown feed parser: {}", name))
+                });
+        }
+        Ok(self
+            .parsers
+            .iter()
+            .find(|p| p.detect(feed_url))
+            .map(|p| p.as_ref())
+            .unwrap_or(&DefaultParser))
     }
 }
 
@@ -473,4 +1262,402 @@ struct TorrentFile {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct TorrentInfo(serde_bencode::value::Value);
+struct TorrentInfo(Value);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_parser() {
+        let registry = FeedParserRegistry::default();
+        let name =
+            |parser: Option<&str>, url: &str| registry.resolve(parser, url).map(|p| p.name());
+
+        assert_eq!(
+            name(None, "https://mikanani.me/RSS/Classic").unwrap(),
+            "mikan"
+        );
+        assert_eq!(name(None, "https://nyaa.si/?page=rss").unwrap(), "nyaa");
+        assert_eq!(name(None, "https://example.com/rss").unwrap(), "default");
+        // 镜像站点通过显式指定解析器
+        assert_eq!(
+            name(Some("mikan"), "https://mikan.mirror.example/RSS/Classic").unwrap(),
+            "mikan"
+        );
+        assert!(name(Some("unknown"), "https://example.com/rss").is_err());
+    }
+
+    #[test]
+    fn parse_collection_range() {
+        let cases = [
+            ("[Group] Title [01-12][1080p]", Some((1, 12))),
+            ("[Group] Title 第01~24话 合集", Some((1, 24))),
+            ("[Group] Title [EP01-EP13 END][WebRip]", Some((1, 13))),
+            ("[Group] Title Batch [1080p]", None),
+            ("[Group] Title 合集 [2025-01-08]", None),
+        ];
+        for (title, expected) in cases {
+            let (kind, range, marker) = classify_title(title);
+            assert_eq!(kind, ResourceKind::Collection, "{title}");
+            assert!(marker.is_some(), "{title}");
+            assert_eq!(range, expected, "{title}");
+        }
+        assert_eq!(
+            classify_title("[Group] Title - 12 END [1080p]").0,
+            ResourceKind::Episode
+        );
+    }
+
+    #[test]
+    fn parse_torrent_files() {
+        let multi = b"d4:infod5:filesld6:lengthi100e4:pathl3:sub5:a.mkveed6:lengthi20e4:pathl5:b.asseee4:name5:Title12:piece lengthi16384e6:pieces0:ee";
+        let torrent = parse_torrent(multi).unwrap();
+        assert_eq!(
+            torrent.files,
+            vec![
+                TorrentFileInfo {
+                    path: "Title/sub/a.mkv".to_string(),
+                    size: 100,
+                },
+                TorrentFileInfo {
+                    path: "Title/b.ass".to_string(),
+                    size: 20,
+                },
+            ]
+        );
+
+        let single = b"d4:infod6:lengthi42e4:name5:a.mkv12:piece lengthi16384e6:pieces0:ee";
+        let torrent = parse_torrent(single).unwrap();
+        assert_eq!(
+            torrent.files,
+            vec![TorrentFileInfo {
+                path: "a.mkv".to_string(),
+                size: 42,
+            }]
+        );
+        assert!(torrent.info_hash.v1.is_some() && torrent.info_hash.v2.is_none());
+    }
+
+    #[test]
+    fn parse_v2_and_hybrid_torrent() {
+        let torrent_of = |info: &[u8]| [b"d4:info".as_slice(), info, b"e"].concat();
+
+        let v2 = b"d9:file treed5:a.mkvd0:d6:lengthi42eee5:b.assd0:d6:lengthi2eeee12:meta versioni2e4:name4:Show12:piece lengthi16384ee";
+        let torrent = parse_torrent(&torrent_of(v2)).unwrap();
+        assert_eq!(torrent.info_hash, InfoHash::v2(Sha256::digest(v2).into()));
+        assert_eq!(
+            torrent.files,
+            vec![
+                TorrentFileInfo {
+                    path: "Show/a.mkv".to_string(),
+                    size: 42,
+                },
+                TorrentFileInfo {
+                    path: "Show/b.ass".to_string(),
+                    size: 2,
+                },
+            ]
+        );
+
+        let hybrid = b"d9:file treed5:a.mkvd0:d6:lengthi42eeee6:lengthi42e12:meta versioni2e4:name5:a.mkv12:piece lengthi16384e6:pieces0:e";
+        let torrent = parse_torrent(&torrent_of(hybrid)).unwrap();
+        assert_eq!(torrent.info_hash.v1, Some(Sha1::digest(hybrid).into()));
+        assert_eq!(torrent.info_hash.v2, Some(Sha256::digest(hybrid).into()));
+        assert_eq!(torrent.info_hash.id(), torrent.info_hash.v1);
+    }
+
+    #[test]
+    fn parse_magnet_info_hash() {
+        let v1 = "0123456789abcdef0123456789abcdef01234567";
+        let v2 = "a".repeat(64);
+        let hybrid = format!("magnet:?xt=urn:btih:{}&xt=urn:btmh:1220{}&dn=Title", v1, v2);
+        let hash = magnet_info_hash(&hybrid).unwrap().unwrap();
+        assert_eq!(hash.v1.map(hex::encode).as_deref(), Some(v1));
+        assert_eq!(hash.v2.map(hex::encode), Some(v2.clone()));
+
+        let pure = magnet_info_hash(&format!("magnet:?xt=urn:btmh:1220{}", v2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(pure.v1, None);
+        assert_eq!(pure.id().map(hex::encode), Some("a".repeat(40)));
+
+        assert!(magnet_info_hash("magnet:?xt=urn:btmh:1114abcd").is_err());
+        assert!(magnet_info_hash("magnet:?dn=Title").unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_pushed_items() {
+        let v1 = "0123456789abcdef0123456789abcdef01234567";
+        let v2 = "b".repeat(64);
+        let item = parse_pushed_item(PushedItem {
+            title: " [Group] Title [01-12][1080p] ".to_string(),
+            resource_url: format!("magnet:?xt=urn:btih:{}", v1),
+            info_hash: Some(v2.clone()),
+            published_at: Some(1_700_000_000),
+            ..Default::default()
+        })
+        .ok()
+        .unwrap();
+        assert_eq!(item.title, "[Group] Title [01-12][1080p]");
+        assert_eq!(item.source_url, item.resource_url);
+        assert_eq!(item.info_hash.v1.map(hex::encode).as_deref(), Some(v1));
+        assert_eq!(item.info_hash.v2.map(hex::encode), Some(v2));
+        assert_eq!(item.kind, ResourceKind::Collection);
+        assert_eq!(item.episode_range, Some((1, 12)));
+
+        let torrent = parse_pushed_item(PushedItem {
+            title: "Title - 05".to_string(),
+            resource_url: "https://example.com/a.torrent".to_string(),
+            ..Default::default()
+        })
+        .ok()
+        .unwrap();
+        assert!(torrent.info_hash.is_empty());
+        assert_eq!(torrent.kind, ResourceKind::Episode);
+
+        let reason = |item: PushedItem| parse_pushed_item(item).err().unwrap().reason;
+        assert_eq!(
+            reason(PushedItem {
+                resource_url: "https://example.com/a.torrent".to_string(),
+                ..Default::default()
+            }),
+            "missing title"
+        );
+        assert_eq!(
+            reason(PushedItem {
+                title: "Title".to_string(),
+                resource_url: "https://example.com/a.torrent".to_string(),
+                info_hash: Some("abcd".to_string()),
+                ..Default::default()
+            }),
+            "invalid info hash"
+        );
+    }
+
+    struct StubRepo;
+
+    #[async_trait]
+    impl FeedItemRepository for StubRepo {
+        async fn get_url_info_hash(&self, _urls: Vec<&str>) -> Result<HashMap<String, [u8; 20]>> {
+            Ok(HashMap::new())
+        }
+    }
+
+    #[async_trait]
+    impl FeedHttpCacheRepository for StubRepo {
+        async fn get_http_cache(&self, _url: &str) -> Result<Option<FeedHttpCache>> {
+            Ok(None)
+        }
+
+        async fn save_http_cache(&self, _url: &str, _cache: &FeedHttpCache) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    const TORZNAB_CAPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<caps>
+  <server title="Jackett" />
+  <searching><search available="yes" supportedParams="q" /></searching>
+</caps>"#;
+
+    const TORZNAB_SEARCH: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="1.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+  <channel>
+    <title>Nyaa</title>
+    <link>https://nyaa.si/</link>
+    <item>
+      <title>[Group] Title - 01 [1080p]</title>
+      <guid>https://nyaa.si/view/1</guid>
+      <comments>https://nyaa.si/view/1</comments>
+      <pubDate>Wed, 08 Jan 2025 12:00:00 +0000</pubDate>
+      <link>http://127.0.0.1:9117/dl/nyaa/?file=1</link>
+      <enclosure url="http://127.0.0.1:9117/dl/nyaa/?file=1" length="1073741824" type="application/x-bittorrent" />
+      <torznab:attr name="size" value="1073741824" />
+      <torznab:attr name="seeders" value="12" />
+      <torznab:attr name="infohash" value="0123456789abcdef0123456789abcdef01234567" />
+    </item>
+    <item>
+      <title>[Group] Title - 02 [1080p]</title>
+      <guid>https://nyaa.si/view/2</guid>
+      <comments>https://nyaa.si/view/2</comments>
+      <pubDate>Wed, 15 Jan 2025 12:00:00 +0000</pubDate>
+      <link>http://127.0.0.1:9117/dl/nyaa/?file=2</link>
+      <torznab:attr name="seeders" value="0" />
+      <torznab:attr name="infohash" value="89abcdef0123456789abcdef0123456789abcdef" />
+    </item>
+  </channel>
+</rss>"#;
+
+    /// 启动本地 Torznab 桩服务，按 `t` 参数返回能力文档或搜索结果，携带缓存校验信息时返回 304
+    async fn serve_torznab() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                let body = if request.contains("t=caps") {
+                    TORZNAB_CAPS
+                } else {
+                    TORZNAB_SEARCH
+                };
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/rss+xml\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!(
+            "http://{}/api/v2.0/indexers/all/results/torznab/api?apikey=k&t=search&q=Title",
+            addr
+        )
+    }
+
+    #[tokio::test]
+    async fn fetch_torznab_search() {
+        let url = serve_torznab().await;
+        let repo = Arc::new(StubRepo);
+        let fetcher = HttpFeedFetcher::new(Client::new(), repo.clone(), repo);
+        let options = FeedRequestOptions::default();
+
+        // 未指定解析器时根据地址识别，校验时先查询能力文档
+        let source_key = fetcher.get_source_key(&url, None, &options).await.unwrap();
+        assert_eq!(source_key, build_source_key("https://nyaa.si/"));
+
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert_eq!(data.items.len(), 1);
+        let item = &data.items[0];
+        assert_eq!(item.source_url, "https://nyaa.si/view/1");
+        assert_eq!(item.resource_url, "http://127.0.0.1:9117/dl/nyaa/?file=1");
+        assert_eq!(
+            hex::encode(item.info_hash),
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+        assert_eq!(item.size, Some(1073741824));
+
+        let preview = fetcher.preview_url(&url, None, &options).await.unwrap();
+        assert!(matches!(
+            &preview.entries[1],
+            FeedPreviewEntry::Skipped { reason, .. } if reason == "no seeders"
+        ));
+    }
+
+    struct KnownRepo(HashMap<String, [u8; 20]>);
+
+    #[async_trait]
+    impl FeedItemRepository for KnownRepo {
+        async fn get_url_info_hash(&self, urls: Vec<&str>) -> Result<HashMap<String, [u8; 20]>> {
+            Ok(urls
+                .into_iter()
+                .filter_map(|url| self.0.get(url).map(|id| (url.to_string(), *id)))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn known_items_returned_as_seen() {
+        let url = serve_torznab().await;
+        let known = HashMap::from([("http://127.0.0.1:9117/dl/nyaa/?file=1".to_string(), [1; 20])]);
+        let fetcher = HttpFeedFetcher::new(
+            Client::new(),
+            Arc::new(KnownRepo(known)),
+            Arc::new(StubRepo),
+        );
+
+        // 已入库的条目不再返回，但保留其来源用于刷新出现时间
+        let data = fetcher
+            .fetch_url(&url, None, &FeedRequestOptions::default())
+            .await
+            .unwrap();
+        assert!(data.items.is_empty());
+        assert_eq!(
+            data.seen,
+            vec![FeedSeenItem {
+                info_hash: [1; 20],
+                source_url: "https://nyaa.si/view/1".to_string(),
+            }]
+        );
+    }
+
+    #[derive(Default)]
+    struct MemoryCache(std::sync::Mutex<HashMap<String, FeedHttpCache>>);
+
+    #[async_trait]
+    impl FeedHttpCacheRepository for MemoryCache {
+        async fn get_http_cache(&self, url: &str) -> Result<Option<FeedHttpCache>> {
+            Ok(self.0.lock().unwrap().get(url).cloned())
+        }
+
+        async fn save_http_cache(&self, url: &str, cache: &FeedHttpCache) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(url.to_string(), cache.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn http_cache_saved_after_commit() {
+        let url = serve_torznab().await;
+        let repo = Arc::new(StubRepo);
+        let cache = Arc::new(MemoryCache::default());
+        let fetcher = HttpFeedFetcher::new(Client::new(), repo, cache);
+        let options = FeedRequestOptions::default();
+
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert_eq!(data.items.len(), 1);
+        assert!(data.cache.is_some());
+
+        // 条目未保存成功时不写入缓存，下次请求仍返回这些条目
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert_eq!(data.items.len(), 1);
+
+        fetcher.save_cache(data.cache.as_ref().unwrap()).await;
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert!(data.items.is_empty());
+        assert!(data.cache.is_none());
+    }
+
+    #[tokio::test]
+    async fn unchanged_feed_returns_listed_as_seen() {
+        let url = serve_torznab().await;
+        let known = HashMap::from([("http://127.0.0.1:9117/dl/nyaa/?file=1".to_string(), [1; 20])]);
+        let cache = Arc::new(MemoryCache::default());
+        let fetcher =
+            HttpFeedFetcher::new(Client::new(), Arc::new(KnownRepo(known)), cache.clone());
+        let options = FeedRequestOptions::default();
+
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        fetcher.save_cache(data.cache.as_ref().unwrap()).await;
+        assert!(cache.0.lock().unwrap()[&url].etag.is_some());
+
+        // 返回 304 时不再解析，但上次列出的条目仍作为 seen 返回，以刷新来源记录的出现时间
+        let expected = vec![FeedSeenItem {
+            info_hash: [1; 20],
+            source_url: "https://nyaa.si/view/1".to_string(),
+        }];
+        assert_eq!(data.seen, expected);
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert!(data.items.is_empty());
+        assert!(data.cache.is_none());
+        assert_eq!(data.seen, expected);
+
+        // 服务端不支持条件请求时，响应体未变化同样返回上次列出的条目
+        cache.0.lock().unwrap().get_mut(&url).unwrap().etag = None;
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert!(data.cache.is_none());
+        assert_eq!(data.seen, expected);
+    }
+}
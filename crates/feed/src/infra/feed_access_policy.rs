@@ -1,11 +1,17 @@
 use std::{
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use anyhow::Result;
+use async_trait::async_trait;
 use dashmap::DashMap;
+use tracing::error;
 
-use crate::entity::cap::FeedAccessPolicy;
+use crate::entity::{
+    cap::FeedAccessPolicy,
+    model::{FeedFetchResult, FeedRequestKind},
+};
 
 struct Value {
     /// 在此时间之前，对应 feed_id 不可访问
@@ -14,22 +20,87 @@ struct Value {
     retries: u32,
 }
 
+/// 持久化的 feed 退避状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedBackoffState {
+    pub feed_id: i64,
+    /// 连续失败次数，成功后归零
+    pub retries: u32,
+    /// 允许再次请求的时间戳（秒）
+    pub next_allowed_at: i64,
+}
+
+/// feed 最近一次请求及最近一次轮询的时间戳（秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedLastFetch {
+    pub feed_id: i64,
+    pub fetched_at: i64,
+    pub polled_at: Option<i64>,
+}
+
+/// 单次请求结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFetchOutcome<'a> {
+    Success,
+    Failure(&'a str),
+}
+
+#[async_trait]
+pub trait FeedHealthRepository: Send + Sync {
+    // 获取所有未恢复的退避状态
+    async fn list_backoff(&self) -> Result<Vec<FeedBackoffState>>;
+    // 获取每个 feed 最近一次请求及最近一次轮询的时间戳
+    async fn list_last_fetch(&self) -> Result<Vec<FeedLastFetch>>;
+    // 记录请求结果及最新的退避状态
+    async fn record(
+        &self,
+        state: &FeedBackoffState,
+        kind: FeedRequestKind,
+        outcome: FeedFetchOutcome<'_>,
+    ) -> Result<()>;
+}
+
 #[derive(Clone)]
 pub struct BackoffPolicy {
     cache: Arc<DashMap<i64, Value>>,
-}
-
-impl Default for BackoffPolicy {
-    fn default() -> Self {
-        Self::new()
-    }
+    /// feed_id -> 最近一次请求（含搜索）的时间戳（秒），用于控制请求间隔
+    fetched: Arc<DashMap<i64, i64>>,
+    /// feed_id -> 最近一次轮询订阅地址的时间戳（秒），用于判断轮询是否到期
+    polled: Arc<DashMap<i64, i64>>,
+    repo: Arc<dyn FeedHealthRepository>,
 }
 
 impl BackoffPolicy {
-    pub fn new() -> Self {
+    pub fn new(repo: Arc<dyn FeedHealthRepository>) -> Self {
         Self {
             cache: Arc::new(DashMap::new()),
+            fetched: Arc::new(DashMap::new()),
+            polled: Arc::new(DashMap::new()),
+            repo,
+        }
+    }
+
+    /// 从数据库恢复退避状态及最近请求时间，避免重启后立即请求失败中的 feed
+    pub async fn restore(&self) -> Result<()> {
+        let now = Instant::now();
+        let now_ts = unix_now();
+        for state in self.repo.list_backoff().await? {
+            let remaining = (state.next_allowed_at - now_ts).max(0) as u64;
+            self.cache.insert(
+                state.feed_id,
+                Value {
+                    deadline: now + Duration::from_secs(remaining),
+                    retries: state.retries,
+                },
+            );
+        }
+        for last in self.repo.list_last_fetch().await? {
+            self.fetched.insert(last.feed_id, last.fetched_at);
+            if let Some(polled_at) = last.polled_at {
+                self.polled.insert(last.feed_id, polled_at);
+            }
         }
+        Ok(())
     }
 
     fn backoff_delay(retries: u32) -> Duration {
@@ -38,8 +109,35 @@ impl BackoffPolicy {
             .min(3600);
         Duration::from_secs(secs)
     }
+
+    /// 更新内存中的退避记录并返回最新状态
+    fn note_failure(&self, feed_id: i64) -> FeedBackoffState {
+        let now = Instant::now();
+        let mut entry = self.cache.entry(feed_id).or_insert_with(|| Value {
+            deadline: now,
+            retries: 0,
+        });
+        // 如果已经有有效的退避记录（未到期），则不延长时间
+        if entry.deadline <= now {
+            entry.retries += 1;
+            entry.deadline = now + BackoffPolicy::backoff_delay(entry.retries);
+        }
+        FeedBackoffState {
+            feed_id,
+            retries: entry.retries,
+            next_allowed_at: unix_now() + entry.deadline.duration_since(now).as_secs() as i64,
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
 }
 
+#[async_trait]
 impl FeedAccessPolicy for BackoffPolicy {
     fn block_feed_ids(&self) -> Vec<i64> {
         let now = Instant::now();
@@ -57,17 +155,14 @@ impl FeedAccessPolicy for BackoffPolicy {
 
     fn block_feed_details(&self) -> Vec<(i64, i64)> {
         let now = Instant::now();
-        let sys_now = std::time::SystemTime::now();
+        let sys_now = SystemTime::now();
         self.cache
             .iter()
             .filter_map(|entry| {
                 if entry.value().deadline > now {
                     let dur = entry.value().deadline.duration_since(now);
                     let sys_deadline = sys_now + dur;
-                    let ts = sys_deadline
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64;
+                    let ts = sys_deadline.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
                     Some((*entry.key(), ts))
                 } else {
                     None
@@ -83,33 +178,46 @@ impl FeedAccessPolicy for BackoffPolicy {
         }
     }
 
-    fn note(&self, feed_id: i64, res: &crate::entity::model::FeedFetchResult) {
-        match res {
-            crate::entity::model::FeedFetchResult::Success(_) => {
+    fn last_fetch_at(&self, feed_id: i64) -> Option<i64> {
+        self.fetched.get(&feed_id).map(|v| *v)
+    }
+
+    fn last_poll_at(&self, feed_id: i64) -> Option<i64> {
+        self.polled.get(&feed_id).map(|v| *v)
+    }
+
+    async fn note(&self, feed_id: i64, kind: FeedRequestKind, res: &FeedFetchResult) {
+        if !matches!(res, FeedFetchResult::Denied) {
+            let now = unix_now();
+            self.fetched.insert(feed_id, now);
+            if kind == FeedRequestKind::Poll {
+                self.polled.insert(feed_id, now);
+            }
+        }
+
+        let message;
+        let (state, outcome) = match res {
+            FeedFetchResult::Success(_) => {
                 self.cache.remove(&feed_id);
+                let state = FeedBackoffState {
+                    feed_id,
+                    retries: 0,
+                    next_allowed_at: 0,
+                };
+                (state, FeedFetchOutcome::Success)
             }
-            crate::entity::model::FeedFetchResult::Retryable(_)
-            | crate::entity::model::FeedFetchResult::Failure(_) => {
-                let now = Instant::now();
-                // 如果已经有有效的退避记录（未到期），则忽略本次调用，不延长时间
-                if let Some(entry) = self.cache.get(&feed_id)
-                    && entry.deadline > now
-                {
-                    return;
-                }
-                // 无记录或已过期，执行退避更新
-                self.cache
-                    .entry(feed_id)
-                    .and_modify(|entry| {
-                        entry.retries += 1;
-                        entry.deadline = now + BackoffPolicy::backoff_delay(entry.retries);
-                    })
-                    .or_insert_with(|| Value {
-                        deadline: now + BackoffPolicy::backoff_delay(1),
-                        retries: 1,
-                    });
+            FeedFetchResult::Retryable(e) | FeedFetchResult::Failure(e) => {
+                message = e.to_string();
+                (
+                    self.note_failure(feed_id),
+                    FeedFetchOutcome::Failure(&message),
+                )
             }
-            crate::entity::model::FeedFetchResult::Denied => {}
+            FeedFetchResult::Denied => return,
+        };
+
+        if let Err(e) = self.repo.record(&state, kind, outcome).await {
+            error!(feed_id = feed_id, error = %e, "failed to persist feed health");
         }
     }
 }
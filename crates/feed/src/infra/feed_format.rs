@@ -0,0 +1,305 @@
+use std::{collections::BTreeMap, io::Cursor};
+
+use rss::{
+    Channel, Enclosure, Guid, Item,
+    extension::{Extension, ExtensionMap},
+};
+use serde::Deserialize;
+
+/// 订阅文档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    /// 根据文档内容嗅探格式：JSON 以 `{` 开头，XML 则根据根元素名区分 Atom 与 RSS
+    pub(crate) fn detect(content: &str) -> Self {
+        let content = content.trim_start_matches('\u{feff}').trim_start();
+        if content.starts_with('{') {
+            return FeedFormat::Json;
+        }
+
+        let mut rest = content;
+        while let Some(pos) = rest.find('<') {
+            rest = &rest[pos + 1..];
+            // 跳过 <?xml ?>、注释及 DOCTYPE
+            if rest.starts_with('?') || rest.starts_with('!') {
+                continue;
+            }
+            let name = rest
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .next()
+                .unwrap_or_default();
+            let local = name.rsplit(':').next().unwrap_or(name);
+            return if local == "feed" {
+                FeedFormat::Atom
+            } else {
+                FeedFormat::Rss
+            };
+        }
+        FeedFormat::Rss
+    }
+}
+
+/// 读取任意支持格式的订阅文档，并统一映射为 RSS `Channel`，
+/// 使得下游各站点的 `FeedParser` 钩子无需关心原始格式
+pub(crate) fn read_channel(content: &str) -> Result<Channel, String> {
+    match FeedFormat::detect(content) {
+        FeedFormat::Rss => {
+            Channel::read_from(Cursor::new(content.as_bytes())).map_err(|e| e.to_string())
+        }
+        FeedFormat::Atom => atom_syndication::Feed::read_from(Cursor::new(content.as_bytes()))
+            .map(atom_to_channel)
+            .map_err(|e| e.to_string()),
+        FeedFormat::Json => serde_json::from_str::<JsonFeed>(content)
+            .map(json_to_channel)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+fn atom_to_channel(feed: atom_syndication::Feed) -> Channel {
+    let link = feed
+        .links()
+        .iter()
+        .find(|l| l.rel() == "alternate")
+        .or_else(|| feed.links().iter().find(|l| l.rel() != "self"))
+        .or_else(|| feed.links().first())
+        .map(|l| l.href().to_string())
+        .unwrap_or_else(|| feed.id().to_string());
+
+    let items = feed
+        .entries()
+        .iter()
+        .map(|entry| {
+            let link = entry
+                .links()
+                .iter()
+                .find(|l| l.rel() == "alternate")
+                .map(|l| l.href().to_string());
+            let enclosure = entry
+                .links()
+                .iter()
+                .find(|l| l.rel() == "enclosure")
+                .map(|l| Enclosure {
+                    url: l.href().to_string(),
+                    length: l.length().unwrap_or_default().to_string(),
+                    mime_type: l.mime_type().unwrap_or_default().to_string(),
+                });
+            let pub_date = entry.published().unwrap_or(entry.updated()).to_rfc3339();
+
+            Item {
+                title: Some(entry.title().as_str().to_string()),
+                link,
+                enclosure,
+                guid: Some(Guid {
+                    value: entry.id().to_string(),
+                    permalink: false,
+                }),
+                pub_date: Some(pub_date),
+                extensions: convert_extensions(entry.extensions()),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Channel {
+        title: feed.title().as_str().to_string(),
+        link,
+        items,
+        ..Default::default()
+    }
+}
+
+fn convert_extensions(map: &atom_syndication::extension::ExtensionMap) -> ExtensionMap {
+    map.iter()
+        .map(|(ns, elements)| {
+            let elements = elements
+                .iter()
+                .map(|(name, list)| (name.clone(), list.iter().map(convert_extension).collect()))
+                .collect();
+            (ns.clone(), elements)
+        })
+        .collect()
+}
+
+fn convert_extension(ext: &atom_syndication::extension::Extension) -> Extension {
+    Extension {
+        name: ext.name.clone(),
+        value: ext.value.clone(),
+        attrs: ext.attrs.clone(),
+        children: ext
+            .children
+            .iter()
+            .map(|(name, list)| (name.clone(), list.iter().map(convert_extension).collect()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+/// JSON Feed 1.x 文档，仅保留解析资源所需字段
+#[derive(Debug, Deserialize)]
+struct JsonFeed {
+    title: String,
+    home_page_url: Option<String>,
+    feed_url: Option<String>,
+    #[serde(default)]
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonFeedItem {
+    id: String,
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    #[serde(default)]
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonFeedAttachment {
+    url: String,
+    mime_type: Option<String>,
+    size_in_bytes: Option<u64>,
+}
+
+fn json_to_channel(feed: JsonFeed) -> Channel {
+    let items = feed
+        .items
+        .into_iter()
+        .map(|item| {
+            // 优先选择种子或磁力附件，否则取第一个附件
+            let attachment = item
+                .attachments
+                .iter()
+                .find(|a| {
+                    a.url.starts_with("magnet:?")
+                        || a.mime_type.as_deref() == Some("application/x-bittorrent")
+                })
+                .or_else(|| item.attachments.first());
+            let enclosure = attachment.map(|a| Enclosure {
+                url: a.url.clone(),
+                length: a.size_in_bytes.map(|v| v.to_string()).unwrap_or_default(),
+                mime_type: a.mime_type.clone().unwrap_or_default(),
+            });
+
+            Item {
+                title: item.title,
+                link: item.url.or(item.external_url),
+                enclosure,
+                guid: Some(Guid {
+                    value: item.id,
+                    permalink: false,
+                }),
+                pub_date: item.date_published.or(item.date_modified),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Channel {
+        title: feed.title,
+        link: feed.home_page_url.or(feed.feed_url).unwrap_or_default(),
+        items,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_format() {
+        assert_eq!(
+            FeedFormat::detect("<?xml version=\"1.0\"?>\n<rss version=\"2.0\"></rss>"),
+            FeedFormat::Rss
+        );
+        assert_eq!(
+            FeedFormat::detect(
+                "\u{feff}<?xml version=\"1.0\"?><!-- c --><feed xmlns=\"http://www.w3.org/2005/Atom\"></feed>"
+            ),
+            FeedFormat::Atom
+        );
+        assert_eq!(
+            FeedFormat::detect("  {\"version\": \"https://jsonfeed.org/version/1.1\"}"),
+            FeedFormat::Json
+        );
+    }
+
+    #[test]
+    fn read_atom_entry() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:nyaa="https://nyaa.si/xmlns/nyaa">
+  <title>Private Tracker</title>
+  <id>urn:uuid:60a76c80-d399-11d9-b93C-0003939e0af6</id>
+  <updated>2025-01-08T12:00:00Z</updated>
+  <link rel="self" href="https://tracker.example/atom"/>
+  <link rel="alternate" href="https://tracker.example/"/>
+  <entry>
+    <title>[Group] Title - 01 [1080p]</title>
+    <id>https://tracker.example/view/1</id>
+    <updated>2025-01-08T12:00:00Z</updated>
+    <link rel="alternate" href="https://tracker.example/view/1"/>
+    <link rel="enclosure" type="application/x-bittorrent" length="1024" href="https://tracker.example/download/1.torrent"/>
+    <nyaa:infoHash>0123456789abcdef0123456789abcdef01234567</nyaa:infoHash>
+  </entry>
+</feed>"#;
+        let channel = read_channel(xml).unwrap();
+        assert_eq!(channel.title(), "Private Tracker");
+        assert_eq!(channel.link(), "https://tracker.example/");
+
+        let item = &channel.items()[0];
+        assert_eq!(item.title(), Some("[Group] Title - 01 [1080p]"));
+        assert_eq!(item.link(), Some("https://tracker.example/view/1"));
+        assert_eq!(
+            item.enclosure().map(|e| e.url()),
+            Some("https://tracker.example/download/1.torrent")
+        );
+        assert_eq!(
+            item.guid().map(|g| g.value()),
+            Some("https://tracker.example/view/1")
+        );
+        assert_eq!(item.pub_date(), Some("2025-01-08T12:00:00+00:00"));
+        assert!(
+            item.extensions()
+                .get("nyaa")
+                .and_then(|m| m.get("infoHash"))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn read_json_feed_item() {
+        let json = r#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Json Tracker",
+            "home_page_url": "https://json.example/",
+            "items": [{
+                "id": "42",
+                "url": "https://json.example/view/42",
+                "title": "[Group] Title - 02 [1080p]",
+                "date_published": "2025-01-08T12:00:00+08:00",
+                "attachments": [
+                    {"url": "https://json.example/cover.jpg", "mime_type": "image/jpeg"},
+                    {"url": "https://json.example/42.torrent", "mime_type": "application/x-bittorrent", "size_in_bytes": 2048}
+                ]
+            }]
+        }"#;
+        let channel = read_channel(json).unwrap();
+        assert_eq!(channel.title(), "Json Tracker");
+        assert_eq!(channel.link(), "https://json.example/");
+
+        let item = &channel.items()[0];
+        assert_eq!(item.link(), Some("https://json.example/view/42"));
+        assert_eq!(
+            item.enclosure().map(|e| e.url()),
+            Some("https://json.example/42.torrent")
+        );
+        assert_eq!(item.pub_date(), Some("2025-01-08T12:00:00+08:00"));
+    }
+}
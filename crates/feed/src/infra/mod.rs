@@ -1,3 +1,5 @@
 pub mod feed;
 pub mod feed_access_policy;
+mod feed_format;
 pub mod repository;
+mod torznab;
@@ -1,5 +1,8 @@
 use anyhow::Result;
-use sqlx::{Pool, Sqlite, Transaction};
+use common::infra::schema::add_column_if_missing;
+use sqlx::{Pool, Row, Sqlite, Transaction, sqlite::SqliteRow};
+
+use crate::entity::model::{FeedBaseData, FeedMetadata, FeedSchedule};
 
 #[derive(Clone)]
 pub struct FeedSqliteClient {
@@ -29,6 +32,10 @@ impl FeedSqliteClient {
                 site_url   TEXT,                      -- 可为空，对应 Option<String>
                 search_url TEXT,                      -- 可为空，对应 Option<String>
                 source_key TEXT    NOT NULL UNIQUE,   -- 订阅源的唯一标识键
+                parser     TEXT,                      -- 解析器名称，为空时自动识别
+                poll_interval INTEGER,                -- 轮询间隔（秒），为空时使用默认值
+                min_spacing   INTEGER,                -- 两次请求的最小间隔（秒）
+                request_options TEXT,                 -- 加密后的代理、请求头及 Cookie 设置
                 created_at INTEGER NOT NULL DEFAULT (unixepoch()),
                 updated_at INTEGER NOT NULL DEFAULT (unixepoch())
             );",
@@ -36,9 +43,90 @@ impl FeedSqliteClient {
         .execute(&mut **tx)
         .await?;
 
+        add_column_if_missing(tx, "feed", "parser", "TEXT").await?;
+        add_column_if_missing(tx, "feed", "poll_interval", "INTEGER").await?;
+        add_column_if_missing(tx, "feed", "min_spacing", "INTEGER").await?;
+        add_column_if_missing(tx, "feed", "request_options", "TEXT").await?;
+
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_feed_source_key ON feed(source_key);")
             .execute(&mut **tx)
             .await?;
+
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS feed_health (
+                feed_id         INTEGER PRIMARY KEY,
+                retries         INTEGER NOT NULL DEFAULT 0,   -- 连续失败次数
+                next_allowed_at INTEGER NOT NULL DEFAULT 0,   -- 退避截止时间戳
+                last_error      TEXT,
+                last_error_at   INTEGER,
+                last_success_at INTEGER,
+                updated_at      INTEGER NOT NULL DEFAULT (unixepoch())
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS feed_fetch_log (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                feed_id     INTEGER NOT NULL,
+                kind        INTEGER NOT NULL DEFAULT 0,   -- 0=轮询, 1=搜索
+                success     INTEGER NOT NULL,
+                error       TEXT,
+                created_at  INTEGER NOT NULL DEFAULT (unixepoch())
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        add_column_if_missing(tx, "feed_fetch_log", "kind", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_feed_fetch_log_feed_id ON feed_fetch_log(feed_id);",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS feed_http_cache (
+                url           TEXT    PRIMARY KEY,       -- 实际请求的 feed url
+                etag          TEXT,
+                last_modified TEXT,
+                body_hash     TEXT    NOT NULL,          -- 响应体 sha1 摘要
+                source_key    TEXT    NOT NULL,
+                listed        TEXT,                      -- 列出条目的 JSON 数组
+                updated_at    INTEGER NOT NULL DEFAULT (unixepoch())
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        add_column_if_missing(tx, "feed_http_cache", "listed", "TEXT").await?;
         Ok(())
     }
 }
+
+impl FeedSqliteClient {
+    pub(super) const FEED_SELECT: &str = "SELECT id, title, site_url, search_url, source_key, parser, poll_interval, min_spacing, request_options FROM feed";
+
+    pub(super) fn parse_feed_row(row: &SqliteRow) -> Result<FeedBaseData> {
+        Ok(FeedBaseData {
+            id: row.try_get("id")?,
+            metadata: FeedMetadata {
+                title: row.try_get("title")?,
+                site_url: row.try_get("site_url")?,
+                search_url: row.try_get("search_url")?,
+                source_key: row.try_get("source_key")?,
+                parser: row.try_get("parser")?,
+                schedule: FeedSchedule {
+                    poll_interval: row.try_get("poll_interval")?,
+                    min_spacing: row.try_get("min_spacing")?,
+                },
+                request_options: row.try_get("request_options")?,
+            },
+        })
+    }
+}
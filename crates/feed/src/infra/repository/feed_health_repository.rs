@@ -0,0 +1,131 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{
+    entity::model::FeedRequestKind,
+    infra::{
+        feed_access_policy::{
+            FeedBackoffState, FeedFetchOutcome, FeedHealthRepository, FeedLastFetch,
+        },
+        repository::client::FeedSqliteClient,
+    },
+};
+
+/// 每个 feed 保留的请求历史条数
+const FETCH_LOG_KEEP: i64 = 100;
+
+#[async_trait]
+impl FeedHealthRepository for FeedSqliteClient {
+    async fn list_backoff(&self) -> Result<Vec<FeedBackoffState>> {
+        let rows = sqlx::query_as::<_, (i64, i64, i64)>(
+            "SELECT feed_id, retries, next_allowed_at FROM feed_health WHERE retries > 0",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(feed_id, retries, next_allowed_at)| FeedBackoffState {
+                feed_id,
+                retries: retries as u32,
+                next_allowed_at,
+            })
+            .collect())
+    }
+
+    async fn list_last_fetch(&self) -> Result<Vec<FeedLastFetch>> {
+        let rows = sqlx::query_as::<_, (i64, i64, Option<i64>)>(
+            "SELECT feed_id, MAX(created_at), MAX(CASE WHEN kind = 0 THEN created_at END)
+             FROM feed_fetch_log GROUP BY feed_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(feed_id, fetched_at, polled_at)| FeedLastFetch {
+                feed_id,
+                fetched_at,
+                polled_at,
+            })
+            .collect())
+    }
+
+    async fn record(
+        &self,
+        state: &FeedBackoffState,
+        kind: FeedRequestKind,
+        outcome: FeedFetchOutcome<'_>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let error = match outcome {
+            FeedFetchOutcome::Success => {
+                sqlx::query(
+                    "INSERT INTO feed_health (feed_id, retries, next_allowed_at, last_success_at)
+                     VALUES (?, ?, ?, unixepoch())
+                     ON CONFLICT(feed_id) DO UPDATE SET
+                        retries = excluded.retries,
+                        next_allowed_at = excluded.next_allowed_at,
+                        last_success_at = excluded.last_success_at,
+                        updated_at = unixepoch()",
+                )
+                .bind(state.feed_id)
+                .bind(state.retries as i64)
+                .bind(state.next_allowed_at)
+                .execute(&mut *tx)
+                .await?;
+                None
+            }
+            FeedFetchOutcome::Failure(error) => {
+                sqlx::query(
+                    "INSERT INTO feed_health (feed_id, retries, next_allowed_at, last_error, last_error_at)
+                     VALUES (?, ?, ?, ?, unixepoch())
+                     ON CONFLICT(feed_id) DO UPDATE SET
+                        retries = excluded.retries,
+                        next_allowed_at = excluded.next_allowed_at,
+                        last_error = excluded.last_error,
+                        last_error_at = excluded.last_error_at,
+                        updated_at = unixepoch()",
+                )
+                .bind(state.feed_id)
+                .bind(state.retries as i64)
+                .bind(state.next_allowed_at)
+                .bind(error)
+                .execute(&mut *tx)
+                .await?;
+                Some(error)
+            }
+        };
+
+        let kind = match kind {
+            FeedRequestKind::Poll => 0,
+            FeedRequestKind::Search => 1,
+        };
+        sqlx::query(
+            "INSERT INTO feed_fetch_log (feed_id, kind, success, error) VALUES (?, ?, ?, ?)",
+        )
+        .bind(state.feed_id)
+        .bind(kind)
+        .bind(error.is_none())
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+
+        // 只保留最近的请求历史
+        sqlx::query(
+            "DELETE FROM feed_fetch_log
+             WHERE feed_id = ?
+               AND id NOT IN (
+                   SELECT id FROM feed_fetch_log WHERE feed_id = ? ORDER BY id DESC LIMIT ?
+               )",
+        )
+        .bind(state.feed_id)
+        .bind(state.feed_id)
+        .bind(FETCH_LOG_KEEP)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::infra::{
+    feed::{FeedHttpCache, FeedHttpCacheRepository},
+    repository::client::FeedSqliteClient,
+};
+
+#[async_trait]
+impl FeedHttpCacheRepository for FeedSqliteClient {
+    async fn get_http_cache(&self, url: &str) -> Result<Option<FeedHttpCache>> {
+        // 未记录列出条目的旧缓存视为不存在，完整请求一次以补充该记录
+        let row = sqlx::query_as::<_, (Option<String>, Option<String>, String, String, String)>(
+            "SELECT etag, last_modified, body_hash, source_key, listed FROM feed_http_cache WHERE url = ? AND listed IS NOT NULL",
+        )
+        .bind(url)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(
+            |(etag, last_modified, body_hash, source_key, listed)| -> Result<FeedHttpCache> {
+                Ok(FeedHttpCache {
+                    etag,
+                    last_modified,
+                    body_hash,
+                    source_key,
+                    listed: serde_json::from_str(&listed)?,
+                })
+            },
+        )
+        .transpose()
+    }
+
+    async fn save_http_cache(&self, url: &str, cache: &FeedHttpCache) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO feed_http_cache (url, etag, last_modified, body_hash, source_key, listed)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(url) DO UPDATE SET
+                etag = excluded.etag,
+                last_modified = excluded.last_modified,
+                body_hash = excluded.body_hash,
+                source_key = excluded.source_key,
+                listed = excluded.listed,
+                updated_at = unixepoch()",
+        )
+        .bind(url)
+        .bind(&cache.etag)
+        .bind(&cache.last_modified)
+        .bind(&cache.body_hash)
+        .bind(&cache.source_key)
+        .bind(serde_json::to_string(&cache.listed)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -13,8 +13,7 @@ use crate::{
 #[async_trait]
 impl FeedRepository for FeedSqliteClient {
     async fn list(&self, query: &FeedListQuery) -> Result<Vec<FeedProp>> {
-        let mut builder =
-            QueryBuilder::new("SELECT id, title, site_url, search_url, source_key FROM feed");
+        let mut builder = QueryBuilder::new(Self::FEED_SELECT);
 
         match query.feed_type {
             FeedType::Site => {
@@ -26,39 +25,32 @@ impl FeedRepository for FeedSqliteClient {
             FeedType::Both => {}
         };
 
-        let rows = builder
-            .build_query_as::<(i64, String, Option<String>, Option<String>, String)>()
-            .fetch_all(&self.pool)
-            .await?;
+        let rows = builder.build().fetch_all(&self.pool).await?;
 
-        let result = rows
-            .into_iter()
-            .map(|(id, title, site_url, search_url, source_key)| FeedProp {
-                data: FeedBaseData {
-                    id,
-                    metadata: FeedMetadata {
-                        title,
-                        site_url,
-                        search_url,
-                        source_key,
-                    },
-                },
-            })
-            .collect();
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            result.push(FeedProp {
+                data: Self::parse_feed_row(&row)?,
+            });
+        }
 
         Ok(result)
     }
 
     async fn insert(&self, entity: &FeedMetadata) -> Result<FeedProp> {
         let row = sqlx::query_as::<_, (i64,)>(
-            "INSERT INTO feed (title, site_url, search_url, source_key)
-             VALUES (?, ?, ?, ?)
+            "INSERT INTO feed (title, site_url, search_url, source_key, parser, poll_interval, min_spacing, request_options)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)
              RETURNING id",
         )
         .bind(&entity.title)
         .bind(&entity.site_url)
         .bind(&entity.search_url)
         .bind(&entity.source_key)
+        .bind(&entity.parser)
+        .bind(entity.schedule.poll_interval)
+        .bind(entity.schedule.min_spacing)
+        .bind(&entity.request_options)
         .fetch_one(&self.pool)
         .await?;
 
@@ -77,6 +69,10 @@ impl FeedRepository for FeedSqliteClient {
                  site_url = ?,
                  search_url = ?,
                  source_key = ?,
+                 parser = ?,
+                 poll_interval = ?,
+                 min_spacing = ?,
+                 request_options = ?,
                  updated_at = unixepoch()
              WHERE id = ?",
         )
@@ -84,6 +80,10 @@ impl FeedRepository for FeedSqliteClient {
         .bind(&entity.metadata.site_url)
         .bind(&entity.metadata.search_url)
         .bind(&entity.metadata.source_key)
+        .bind(&entity.metadata.parser)
+        .bind(entity.metadata.schedule.poll_interval)
+        .bind(entity.metadata.schedule.min_spacing)
+        .bind(&entity.metadata.request_options)
         .bind(entity.id)
         .execute(&self.pool)
         .await?;
@@ -92,33 +92,34 @@ impl FeedRepository for FeedSqliteClient {
     }
 
     async fn delete(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query("DELETE FROM feed WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM feed_health WHERE feed_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM feed_fetch_log WHERE feed_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
             .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
     async fn get(&self, id: i64) -> Result<Option<FeedProp>> {
-        let (id, title, site_url, search_url, source_key) =
-            sqlx::query_as::<_, (i64, String, Option<String>, Option<String>, String)>(
-                "SELECT id, title, site_url, search_url, source_key FROM feed WHERE id = ?",
-            )
-            .bind(id)
-            .fetch_one(&self.pool)
-            .await?;
+        let mut builder = QueryBuilder::new(Self::FEED_SELECT);
+        builder.push(" WHERE id = ");
+        builder.push_bind(id);
 
-        Ok(Some(FeedProp {
-            data: FeedBaseData {
-                id,
-                metadata: FeedMetadata {
-                    title,
-                    site_url,
-                    search_url,
-                    source_key,
-                },
-            },
-        }))
+        let row = builder.build().fetch_optional(&self.pool).await?;
+        row.map(|r| Self::parse_feed_row(&r).map(|data| FeedProp { data }))
+            .transpose()
     }
 }
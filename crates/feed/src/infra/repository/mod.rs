@@ -1,2 +1,4 @@
 pub mod client;
+mod feed_health_repository;
+mod feed_http_cache_repository;
 mod feed_repository;
@@ -0,0 +1,140 @@
+use quick_xml::{XmlVersion, events::Event, reader::Reader};
+use rss::Item;
+use url::Url;
+
+/// Torznab/Newznab 扩展属性的命名空间前缀
+const ATTR_NAMESPACES: [&str; 2] = ["torznab", "newznab"];
+
+/// `t=caps` 查询结果中与搜索相关的能力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TorznabCaps {
+    /// 服务名称，如 Jackett、Prowlarr
+    pub server: Option<String>,
+    /// 是否支持 `t=search`
+    pub search: bool,
+}
+
+/// 判断是否为 Torznab/Newznab API 地址：
+/// Jackett 路径包含 `torznab`，Prowlarr 等则为携带 `apikey` 的 `/api` 路径
+pub(crate) fn is_api_url(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let path = url.path().trim_end_matches('/');
+    path.contains("/torznab")
+        || (path.ends_with("/api") && url.query_pairs().any(|(k, _)| k == "apikey"))
+}
+
+/// 由 API 地址构造 `t=caps` 查询地址，保留 `apikey` 等鉴权参数
+pub(crate) fn caps_url(url: &str) -> Option<String> {
+    let mut url = Url::parse(url).ok()?;
+    let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| k != "t" && k != "q")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("t", "caps")
+        .extend_pairs(pairs);
+    Some(url.into())
+}
+
+/// 读取条目中的 `<torznab:attr name=".." value=".."/>` 扩展属性
+pub(crate) fn attr<'a>(item: &'a Item, name: &str) -> Option<&'a str> {
+    ATTR_NAMESPACES
+        .iter()
+        .filter_map(|ns| item.extensions().get(*ns))
+        .filter_map(|m| m.get("attr"))
+        .flatten()
+        .find(|e| e.attrs().get("name").is_some_and(|n| n == name))
+        .and_then(|e| e.attrs().get("value"))
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+/// 解析 `t=caps` 返回的能力文档，API 返回 `<error>` 时给出错误描述
+pub(crate) fn parse_caps(content: &str) -> Result<TorznabCaps, String> {
+    let mut reader = Reader::from_str(content);
+    let mut caps = TorznabCaps {
+        server: None,
+        search: false,
+    };
+    let mut found = false;
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        let element = match &event {
+            Event::Start(e) | Event::Empty(e) => e,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let value_of = |key: &[u8]| {
+            element
+                .attributes()
+                .flatten()
+                .find(|a| a.key.local_name().as_ref() == key)
+                .and_then(|a| {
+                    a.normalized_value(XmlVersion::Implicit1_0)
+                        .ok()
+                        .map(|v| v.into_owned())
+                })
+        };
+        match element.local_name().as_ref() {
+            b"caps" => found = true,
+            b"error" => {
+                return Err(format!(
+                    "torznab error {}: {}",
+                    value_of(b"code").unwrap_or_default(),
+                    value_of(b"description").unwrap_or_default()
+                ));
+            }
+            b"server" => caps.server = value_of(b"title"),
+            b"search" => caps.search = value_of(b"available").is_some_and(|v| v == "yes"),
+            _ => {}
+        }
+    }
+    if !found {
+        return Err("missing caps element".to_string());
+    }
+    Ok(caps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_api_urls() {
+        let base = "http://127.0.0.1:9117/api/v2.0/indexers/all/results/torznab/api?apikey=k&t=search&q=old";
+        assert!(is_api_url(base));
+        assert!(is_api_url("http://127.0.0.1:9696/1/api?apikey=k"));
+        assert!(!is_api_url("https://nyaa.si/?page=rss"));
+
+        assert_eq!(
+            caps_url(base).unwrap(),
+            "http://127.0.0.1:9117/api/v2.0/indexers/all/results/torznab/api?t=caps&apikey=k"
+        );
+    }
+
+    #[test]
+    fn parse_caps_document() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<caps>
+  <server title="Jackett" />
+  <searching>
+    <search available="yes" supportedParams="q" />
+    <tv-search available="no" supportedParams="q,season,ep" />
+  </searching>
+</caps>"#;
+        assert_eq!(
+            parse_caps(xml).unwrap(),
+            TorznabCaps {
+                server: Some("Jackett".to_string()),
+                search: true,
+            }
+        );
+
+        let err = parse_caps(r#"<error code="100" description="Invalid API Key" />"#);
+        assert_eq!(err.unwrap_err(), "torznab error 100: Invalid API Key");
+    }
+}
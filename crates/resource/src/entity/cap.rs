@@ -4,7 +4,10 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 
-use crate::entity::model::{ResourceBaseData, ResourceProp, ResourceQuery};
+use crate::entity::model::{
+    ResourceBaseData, ResourceFeedSource, ResourceProp, ResourcePrunePolicy, ResourcePruneReport,
+    ResourceQuery,
+};
 
 #[async_trait]
 pub trait ResourceRepository: Send + Sync {
@@ -13,9 +16,29 @@ pub trait ResourceRepository: Send + Sync {
         query: &'a ResourceQuery,
     ) -> Pin<Box<dyn Stream<Item = Result<ResourceProp>> + Send + 'a>>;
 
+    /// 按资源标识（v1 哈希或截断的 v2 哈希）查找资源
+    async fn find(&self, id: &[u8; 20]) -> Result<Option<ResourceProp>>;
+
+    /// 将 v2 哈希已知的条目改用入库时实际使用的资源标识（已入库资源或同批次混合种子的 v1 哈希）
+    async fn resolve_ids(&self, items: &mut [ResourceBaseData]) -> Result<()>;
+
     async fn insert_or_skip(&self, items: Vec<ResourceBaseData>) -> Result<()>;
     async fn insert_or_skip_return_new(
         &self,
         items: Vec<ResourceBaseData>,
     ) -> Result<Vec<ResourceProp>>;
+
+    /// 刷新已入库资源在 feed 中的来源记录，不存在时新增
+    async fn touch_feed_sources(&self, sources: Vec<([u8; 20], ResourceFeedSource)>) -> Result<()>;
+    /// 删除 feed 的全部来源记录
+    async fn delete_feed_sources(&self, feed_id: i64) -> Result<()>;
+
+    /// 按策略删除资源及其关联的 url、文件记录，`keep` 中的资源不会被删除
+    async fn prune(
+        &self,
+        policy: &ResourcePrunePolicy,
+        keep: &[[u8; 20]],
+    ) -> Result<ResourcePruneReport>;
+    /// 回收已删除数据占用的磁盘空间
+    async fn vacuum(&self) -> Result<()>;
 }
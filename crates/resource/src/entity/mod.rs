@@ -1,4 +1,5 @@
 pub mod cap;
 pub mod model;
+pub mod release_parser;
 pub mod resource_entity;
 pub mod resources;
@@ -1,7 +1,12 @@
+use feed::entity::model::{ResourceKind, TorrentFileInfo};
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone)]
 pub struct ResourceQuery {
-    /// title模糊关键字
+    /// title模糊关键字，需全部命中；与订阅匹配一致，nfkc 小写化并去除空白后按子串匹配 match_title
     pub keywords: Option<Vec<String>>,
+    /// 命中任一标题即可，用于按订阅别名预筛资源，匹配方式同 `keywords`
+    pub any_titles: Option<Vec<String>>,
     /// 开始时间戳
     pub start_at: Option<i64>,
     /// 结束时间戳
@@ -10,16 +15,163 @@ pub struct ResourceQuery {
     pub offset: Option<i32>,
 }
 
+/// 资源清理策略，调用方传入的被引用资源始终保留
+#[derive(Debug, Clone, Default)]
+pub struct ResourcePrunePolicy {
+    /// 清理发布时间早于该时间戳的资源
+    pub published_before: Option<i64>,
+    /// 按发布时间保留最新的条数，超出部分被清理
+    pub max_rows: Option<u64>,
+    /// 最近一次出现时间与所属订阅源最近一次列出条目的时间相差不超过该秒数的条目保留 url 记录，
+    /// 避免下次轮询时被当作新资源再次入库；按订阅源自身的时间判断，暂时无法请求的订阅源不会因此丢失记录
+    pub listed_window: Option<i64>,
+}
+
+/// 单次清理删除的行数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourcePruneReport {
+    pub resources: u64,
+    pub url_hashes: u64,
+    pub files: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct ResourceBaseData {
     pub title: String,
     pub match_title: String,
     pub url: String,
+    /// 资源标识，v1 哈希或纯 v2 种子的截断哈希
     pub info_hash: [u8; 20],
+    /// v2 及混合种子的 SHA-256 哈希，用于与 v1 来源去重
+    pub info_hash_v2: Option<[u8; 32]>,
     pub published_at: i64,
+    pub kind: ResourceKind,
+    /// 从标题解析出的发布信息，包含合集资源的集数区间
+    pub release: ReleaseInfo,
+    /// 种子内的文件列表，未获取到种子文件时为空
+    pub files: Vec<TorrentFileInfo>,
+    /// 订阅条目声明的资源大小，种子文件列表缺失时使用
+    pub size: Option<u64>,
+    /// 本次入库的来源 feed，写入来源记录使用；从库中读取时为空
+    pub source: Option<ResourceFeedSource>,
+}
+
+/// 资源在某个 feed 中出现的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceFeedSource {
+    pub feed_id: i64,
+    /// 条目详情页地址
+    pub source_url: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct ResourceProp {
     pub data: ResourceBaseData,
 }
+
+/// 从资源标题解析出的发布信息，无法识别的字段为空
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReleaseInfo {
+    /// 发布组，取标题开头方括号内的内容
+    pub group: Option<String>,
+    /// 单集集数，总集篇等可能为小数
+    pub episode: Option<f64>,
+    /// 集数区间（闭区间），与单集集数互斥
+    pub episode_range: Option<(u32, u32)>,
+    pub season: Option<u32>,
+    /// 修正版本号，如 `05v2` 中的 2
+    pub version: Option<u32>,
+    /// 纵向分辨率，如 1080
+    pub resolution: Option<u32>,
+    pub video_codec: Option<VideoCodec>,
+    pub audio_codec: Option<AudioCodec>,
+    pub source: Option<ReleaseSource>,
+    /// 字幕语言，去重后按枚举顺序排列
+    pub subtitle_langs: Vec<SubtitleLang>,
+    pub subtitle_kind: Option<SubtitleKind>,
+}
+
+/// 定义以短代码持久化的枚举，代码同时用于 serde 序列化
+macro_rules! code_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$vmeta:meta])* $variant:ident = $code:literal,)+ }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        pub enum $name {
+            $($(#[$vmeta])* #[serde(rename = $code)] $variant,)+
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)+
+                }
+            }
+
+            pub fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+code_enum!(
+    /// 视频编码
+    VideoCodec {
+        Avc = "avc",
+        Hevc = "hevc",
+        Av1 = "av1",
+    }
+);
+
+code_enum!(
+    /// 音频编码
+    AudioCodec {
+        Aac = "aac",
+        Flac = "flac",
+        Opus = "opus",
+        Ac3 = "ac3",
+        Eac3 = "eac3",
+        Dts = "dts",
+    }
+);
+
+code_enum!(
+    /// 片源
+    ReleaseSource {
+        /// 网络流媒体
+        Web = "web",
+        /// 蓝光
+        Bd = "bd",
+        /// 电视录制
+        Tv = "tv",
+        Dvd = "dvd",
+    }
+);
+
+code_enum!(
+    /// 字幕语言
+    SubtitleLang {
+        /// 简体中文
+        Chs = "chs",
+        /// 繁体中文
+        Cht = "cht",
+        Jpn = "jpn",
+        Eng = "eng",
+    }
+);
+
+code_enum!(
+    /// 字幕形式
+    SubtitleKind {
+        /// 内嵌（硬字幕）
+        Hardsub = "hardsub",
+        /// 内封（软字幕封装在容器内）
+        Softsub = "softsub",
+        /// 外挂字幕文件
+        External = "external",
+    }
+);
@@ -0,0 +1,232 @@
+use std::sync::OnceLock;
+
+use common::shared::{
+    episode::{
+        EpisodeNumber, cjk_number, episode_number, normalize_brackets, prepare_tokens, split_group,
+    },
+    str::nfkc_lowercase,
+};
+use regex::Regex;
+
+use crate::entity::model::{
+    AudioCodec, ReleaseInfo, ReleaseSource, SubtitleKind, SubtitleLang, VideoCodec,
+};
+
+/// 从资源标题解析发布信息
+///
+/// 标题先做 NFKC 归一化并统一 `【】` 为 `[]`，发布组取开头方括号内容，
+/// 集数等数字信息在去掉发布组后的剩余部分中识别，避免组名中的数字干扰
+pub fn parse_release(title: &str) -> ReleaseInfo {
+    let normalized = normalize_brackets(&nfkc_lowercase(title, true));
+    let cased = normalize_brackets(title);
+    let (group, rest) = split_group(&cased, &normalized);
+    let text = prepare_tokens(rest);
+
+    let mut info = ReleaseInfo {
+        group,
+        season: parse_season(&text),
+        version: parse_version(&text),
+        ..Default::default()
+    };
+    match episode_number(&text) {
+        Some(EpisodeNumber::Range(start, end)) => info.episode_range = Some((start, end)),
+        Some(EpisodeNumber::Single(ep)) => info.episode = Some(ep),
+        None => {}
+    }
+
+    for token in ascii_tokens(&text) {
+        info.resolution = info.resolution.or_else(|| parse_resolution(token));
+        info.video_codec = info.video_codec.or_else(|| parse_video_codec(token));
+        info.audio_codec = info.audio_codec.or_else(|| parse_audio_codec(token));
+        info.source = info.source.or_else(|| parse_source(token));
+        for lang in ascii_subtitle_langs(token) {
+            push_unique(&mut info.subtitle_langs, *lang);
+        }
+        info.subtitle_kind = info.subtitle_kind.or(match token {
+            "hardsub" => Some(SubtitleKind::Hardsub),
+            "softsub" => Some(SubtitleKind::Softsub),
+            _ => None,
+        });
+    }
+
+    for segment in cjk_segments(&text).filter(|s| is_subtitle_segment(s)) {
+        for c in segment.chars() {
+            let lang = match c {
+                '简' | '簡' => SubtitleLang::Chs,
+                '繁' => SubtitleLang::Cht,
+                '日' => SubtitleLang::Jpn,
+                '英' => SubtitleLang::Eng,
+                _ => continue,
+            };
+            push_unique(&mut info.subtitle_langs, lang);
+        }
+        info.subtitle_kind = info.subtitle_kind.or_else(|| cjk_subtitle_kind(segment));
+    }
+    info.subtitle_langs.sort();
+
+    info
+}
+
+fn ascii_tokens(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+}
+
+fn cjk_segments(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| !('\u{4e00}'..='\u{9fff}').contains(&c))
+        .filter(|t| !t.is_empty())
+}
+
+fn push_unique<T: PartialEq>(list: &mut Vec<T>, value: T) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("invalid release regex"))
+}
+
+fn parse_resolution(token: &str) -> Option<u32> {
+    const HEIGHTS: [u32; 8] = [360, 480, 540, 576, 720, 1080, 1440, 2160];
+    if token == "4k" || token == "uhd" {
+        return Some(2160);
+    }
+    let height = match token.split_once('x') {
+        Some((w, h)) if w.len() >= 3 && w.bytes().all(|b| b.is_ascii_digit()) => h.parse().ok(),
+        _ => token.strip_suffix(['p', 'i']).and_then(|v| v.parse().ok()),
+    }?;
+    HEIGHTS.contains(&height).then_some(height)
+}
+
+fn parse_video_codec(token: &str) -> Option<VideoCodec> {
+    match token {
+        "avc" | "x264" | "h264" => Some(VideoCodec::Avc),
+        "hevc" | "x265" | "h265" => Some(VideoCodec::Hevc),
+        "av1" => Some(VideoCodec::Av1),
+        _ => None,
+    }
+}
+
+fn parse_audio_codec(token: &str) -> Option<AudioCodec> {
+    match token {
+        "ac3" => return Some(AudioCodec::Ac3),
+        "eac3" => return Some(AudioCodec::Eac3),
+        _ => {}
+    }
+    // 多音轨写作 flacx2、aacx2，声道数写作 ddp2、aac2
+    let token = match token.rsplit_once('x') {
+        Some((codec, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => codec,
+        _ => token.trim_end_matches(|c: char| c.is_ascii_digit()),
+    };
+    match token {
+        "aac" => Some(AudioCodec::Aac),
+        "flac" => Some(AudioCodec::Flac),
+        "opus" => Some(AudioCodec::Opus),
+        "ddp" => Some(AudioCodec::Eac3),
+        "dts" => Some(AudioCodec::Dts),
+        _ => None,
+    }
+}
+
+fn parse_source(token: &str) -> Option<ReleaseSource> {
+    match token {
+        "web" | "webdl" | "webrip" | "baha" | "bilibili" | "cr" | "abema" | "nf" | "netflix"
+        | "amzn" => Some(ReleaseSource::Web),
+        "bd" | "bdrip" | "bluray" | "bdmv" | "bdremux" => Some(ReleaseSource::Bd),
+        "tvrip" | "hdtv" => Some(ReleaseSource::Tv),
+        "dvd" | "dvdrip" => Some(ReleaseSource::Dvd),
+        _ => None,
+    }
+}
+
+fn ascii_subtitle_langs(token: &str) -> &'static [SubtitleLang] {
+    match token {
+        "chs" | "sc" | "gb" => &[SubtitleLang::Chs],
+        "cht" | "tc" | "big5" => &[SubtitleLang::Cht],
+        "jp" | "jpn" | "jap" => &[SubtitleLang::Jpn],
+        "jpsc" => &[SubtitleLang::Jpn, SubtitleLang::Chs],
+        "jptc" => &[SubtitleLang::Jpn, SubtitleLang::Cht],
+        "eng" => &[SubtitleLang::Eng],
+        _ => &[],
+    }
+}
+
+/// 仅在描述字幕的中文片段中识别语言，避免标题中的「日」「英」等字被误判
+fn is_subtitle_segment(segment: &str) -> bool {
+    const MARKERS: [&str; 12] = [
+        "简", "簡", "繁", "字幕", "双语", "雙語", "内嵌", "內嵌", "内封", "內封", "外挂", "外掛",
+    ];
+    segment.chars().count() <= 12 && MARKERS.iter().any(|m| segment.contains(m))
+}
+
+fn cjk_subtitle_kind(segment: &str) -> Option<SubtitleKind> {
+    if segment.contains("内嵌") || segment.contains("內嵌") {
+        Some(SubtitleKind::Hardsub)
+    } else if segment.contains("内封") || segment.contains("內封") {
+        Some(SubtitleKind::Softsub)
+    } else if segment.contains("外挂") || segment.contains("外掛") {
+        Some(SubtitleKind::External)
+    } else {
+        None
+    }
+}
+
+fn parse_version(text: &str) -> Option<u32> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    regex(&RE, r"(?:^|[^a-z0-9])\d*v(\d)(?:[^a-z0-9]|$)")
+        .captures(text)
+        .and_then(|c| c[1].parse().ok())
+}
+
+fn parse_season(text: &str) -> Option<u32> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = regex(
+        &RE,
+        r"(?x)
+        (?:^|[^a-z0-9])s(?P<s>\d{1,2})(?:e\d+)?(?:[^a-z0-9]|$)
+        | season\s*(?P<season>\d{1,2})
+        | (?P<nth>\d{1,2})(?:st|nd|rd|th)\s*season
+        | 第\s*(?P<cjk>[0-9一二三四五六七八九十]+)\s*[季期]
+        ",
+    );
+    let caps = re.captures(text)?;
+    ["s", "season", "nth"]
+        .iter()
+        .find_map(|name| caps.name(name))
+        .and_then(|m| m.as_str().parse().ok())
+        .or_else(|| {
+            caps.name("cjk")
+                .and_then(|m| m.as_str().parse().ok().or_else(|| cjk_number(m.as_str())))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        title: String,
+        expected: ReleaseInfo,
+    }
+
+    #[test]
+    fn parse_fixture_titles() {
+        let fixtures: Vec<Fixture> = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/release_titles.json"
+        )))
+        .unwrap();
+        for fixture in fixtures {
+            assert_eq!(
+                parse_release(&fixture.title),
+                fixture.expected,
+                "{}",
+                fixture.title
+            );
+        }
+    }
+}
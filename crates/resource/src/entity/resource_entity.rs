@@ -1,6 +1,12 @@
 use base32::Alphabet;
+use common::shared::info_hash::InfoHash;
 
-use crate::entity::model::ResourceBaseData;
+use feed::entity::model::{FeedItem, ResourceKind, TorrentFileInfo};
+
+use crate::entity::{
+    model::{ReleaseInfo, ResourceBaseData},
+    resources::Resources,
+};
 
 #[derive(Debug, Clone)]
 pub struct ResourceEntity {
@@ -13,10 +19,20 @@ impl ResourceEntity {
         Self { data }
     }
 
+    /// 将 feed 条目转换为未入库的资源实体，用于试运行匹配
+    pub fn preview(item: FeedItem) -> Self {
+        Self::new(Resources::to_base_data(item, None))
+    }
+
     pub fn id(&self) -> &[u8; 20] {
         &self.data.info_hash
     }
 
+    /// 完整的 info hash，包含 v2 及混合种子的 SHA-256 哈希
+    pub fn info_hash(&self) -> InfoHash {
+        InfoHash::from_id(self.data.info_hash, self.data.info_hash_v2)
+    }
+
     pub fn title(&self) -> &str {
         &self.data.title
     }
@@ -33,26 +49,56 @@ impl ResourceEntity {
         self.data.published_at
     }
 
+    pub fn kind(&self) -> ResourceKind {
+        self.data.kind
+    }
+
+    pub fn is_collection(&self) -> bool {
+        self.data.kind == ResourceKind::Collection
+    }
+
+    /// 合集资源标题中识别出的集数区间
+    pub fn episode_range(&self) -> Option<(u32, u32)> {
+        self.data.release.episode_range
+    }
+
+    /// 标题解析出的发布信息
+    pub fn release(&self) -> &ReleaseInfo {
+        &self.data.release
+    }
+
+    /// 种子内的文件列表，磁力链接资源为空
+    pub fn files(&self) -> &[TorrentFileInfo] {
+        &self.data.files
+    }
+
+    /// 种子内文件的总大小，没有文件列表时使用订阅条目声明的大小，均未知时返回 None
+    pub fn total_size(&self) -> Option<u64> {
+        if self.data.files.is_empty() {
+            return self.data.size;
+        }
+        Some(self.data.files.iter().map(|f| f.size).sum())
+    }
+
     /// 根据 Base32 编码的 info_hash 生成磁力链接。
     ///
-    /// 生成的链接格式为 `magnet:?xt=urn:btih:<info_hash>`。
+    /// 生成的链接格式为 `magnet:?xt=urn:btih:<info_hash>`，纯 v2 种子使用 `magnet_hex` 的格式。
     pub fn magnet_base32(&self) -> String {
+        let info_hash = self.info_hash();
+        let Some(v1) = info_hash.v1 else {
+            return info_hash.magnet_hex();
+        };
         let mut s = String::with_capacity(PREFIX.len() + 40);
         s.push_str(PREFIX);
-        s.push_str(&base32::encode(
-            Alphabet::Rfc4648 { padding: false },
-            &self.data.info_hash,
-        ));
+        s.push_str(&base32::encode(Alphabet::Rfc4648 { padding: false }, &v1));
         s
     }
 
     /// 根据 Hex 编码的 info_hash 生成磁力链接。
     ///
-    /// 生成的链接格式为 `magnet:?xt=urn:btih:<info_hash>`。
+    /// 生成的链接格式为 `magnet:?xt=urn:btih:<info_hash>`，
+    /// v2 及混合种子使用 `urn:btmh:` 携带 SHA-256 哈希。
     pub fn magnet_hex(&self) -> String {
-        let mut s = String::with_capacity(PREFIX.len() + 40);
-        s.push_str(PREFIX);
-        s.push_str(&hex::encode(self.data.info_hash));
-        s
+        self.info_hash().magnet_hex()
     }
 }
@@ -1,23 +1,32 @@
 use std::{pin::Pin, sync::Arc};
 
-use common::shared::{error::Error, str::nfkc_to_lowercase};
-use feed::entity::model::FeedItem;
-use futures::{Stream, StreamExt};
+use common::shared::{cap::TorrentStore, error::Error, str::nfkc_to_lowercase};
+use feed::entity::model::{FeedItem, FeedSeenItem};
+use futures::{Stream, StreamExt, TryStreamExt};
+use tracing::error;
 
 use crate::entity::{
     cap::ResourceRepository,
-    model::{ResourceBaseData, ResourceQuery},
+    model::{
+        ResourceBaseData, ResourceFeedSource, ResourcePrunePolicy, ResourcePruneReport,
+        ResourceQuery,
+    },
+    release_parser::parse_release,
     resource_entity::ResourceEntity,
 };
 
 #[derive(Clone)]
 pub struct Resources {
     repo: Arc<dyn ResourceRepository>,
+    torrent_store: Arc<dyn TorrentStore>,
 }
 
 impl Resources {
-    pub fn new(repo: Arc<dyn ResourceRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn ResourceRepository>, torrent_store: Arc<dyn TorrentStore>) -> Self {
+        Self {
+            repo,
+            torrent_store,
+        }
     }
 
     pub fn stream<'a>(
@@ -32,20 +41,46 @@ impl Resources {
         Box::pin(converted)
     }
 
-    pub async fn just_save(&self, items: Vec<FeedItem>) -> Result<(), Error> {
-        let data = items
-            .into_iter()
-            .map(|i| {
-                let match_title = nfkc_to_lowercase(&i.title);
-                ResourceBaseData {
-                    title: i.title,
-                    match_title,
-                    url: i.resource_url,
-                    info_hash: i.info_hash,
-                    published_at: i.published_at,
-                }
-            })
-            .collect::<Vec<_>>();
+    pub async fn find(&self, id: &[u8; 20]) -> Result<Option<ResourceEntity>, Error> {
+        self.repo
+            .find(id)
+            .await
+            .map(|prop| prop.map(|prop| ResourceEntity::new(prop.data)))
+            .map_err(|e| Error::external("resources find res entity error", e))
+    }
+
+    /// 一次性读取查询结果，调用方需通过 `limit` 控制数量
+    pub async fn list(&self, query: &ResourceQuery) -> Result<Vec<ResourceEntity>, Error> {
+        self.stream(query).try_collect().await
+    }
+
+    /// 按策略清理资源，`keep` 为仍被引用（如订阅剧集）而需保留的资源标识
+    pub async fn prune(
+        &self,
+        policy: &ResourcePrunePolicy,
+        keep: &[[u8; 20]],
+    ) -> Result<ResourcePruneReport, Error> {
+        self.repo
+            .prune(policy, keep)
+            .await
+            .map_err(|e| Error::external(format!("prune resources {:?} failed", policy), e))
+    }
+
+    pub async fn vacuum(&self) -> Result<(), Error> {
+        self.repo
+            .vacuum()
+            .await
+            .map_err(|e| Error::external("vacuum resource database failed", e))
+    }
+
+    /// 将 feed 条目转换为未入库的资源实体，用于试运行匹配
+    pub fn preview(&self, items: Vec<FeedItem>) -> Vec<ResourceEntity> {
+        items.into_iter().map(ResourceEntity::preview).collect()
+    }
+
+    /// 保存 feed 条目，并记录资源在该 feed 中的出现时间
+    pub async fn just_save(&self, feed_id: i64, items: Vec<FeedItem>) -> Result<(), Error> {
+        let data = self.prepare(feed_id, items).await?;
         self.repo
             .insert_or_skip(data)
             .await
@@ -53,22 +88,45 @@ impl Resources {
         Ok(())
     }
 
-    // save
-    // 保存并返回新资源
-    pub async fn save(&self, items: Vec<FeedItem>) -> Result<Vec<ResourceEntity>, Error> {
-        let data = items
+    /// 已入库的条目再次出现在 feed 中，刷新资源在该 feed 中的来源记录
+    pub async fn record_seen(&self, feed_id: i64, seen: Vec<FeedSeenItem>) -> Result<(), Error> {
+        let sources = seen
             .into_iter()
             .map(|i| {
-                let match_title = nfkc_to_lowercase(&i.title);
-                ResourceBaseData {
-                    title: i.title,
-                    match_title,
-                    url: i.resource_url,
-                    info_hash: i.info_hash,
-                    published_at: i.published_at,
-                }
+                (
+                    i.info_hash,
+                    ResourceFeedSource {
+                        feed_id,
+                        source_url: i.source_url,
+                    },
+                )
             })
             .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return Ok(());
+        }
+        self.repo
+            .touch_feed_sources(sources)
+            .await
+            .map_err(|e| Error::external("resources record seen failed", e))
+    }
+
+    /// 删除 feed 后清理资源在该 feed 中的来源记录
+    pub async fn remove_feed(&self, feed_id: i64) -> Result<(), Error> {
+        self.repo
+            .delete_feed_sources(feed_id)
+            .await
+            .map_err(|e| Error::external("resources remove feed sources failed", e))
+    }
+
+    // save
+    // 保存并返回新资源
+    pub async fn save(
+        &self,
+        feed_id: i64,
+        items: Vec<FeedItem>,
+    ) -> Result<Vec<ResourceEntity>, Error> {
+        let data = self.prepare(feed_id, items).await?;
         let res = self
             .repo
             .insert_or_skip_return_new(data)
@@ -79,4 +137,61 @@ impl Resources {
             .map(|i| ResourceEntity::new(i.data))
             .collect())
     }
+
+    /// 转换为入库数据并确定最终的资源标识，下载得到的种子按该标识保存，
+    /// 保证下载时能以资源标识读取到本地种子
+    async fn prepare(
+        &self,
+        feed_id: i64,
+        items: Vec<FeedItem>,
+    ) -> Result<Vec<ResourceBaseData>, Error> {
+        let (mut data, torrents): (Vec<_>, Vec<_>) = items
+            .into_iter()
+            .map(|mut i| {
+                let torrent = i.torrent.take();
+                (Self::to_base_data(i, Some(feed_id)), torrent)
+            })
+            .unzip();
+        self.repo
+            .resolve_ids(&mut data)
+            .await
+            .map_err(|e| Error::external("resources resolve ids failed", e))?;
+
+        for (item, torrent) in data.iter().zip(torrents) {
+            let Some(bytes) = torrent else {
+                continue;
+            };
+            // 保存失败只影响后续下载时能否复用本地种子，不阻断入库
+            if let Err(e) = self.torrent_store.save(item.info_hash, &bytes).await {
+                error!(title = %item.title, url = %item.url, error = %e, "failed to store torrent");
+            }
+        }
+        Ok(data)
+    }
+
+    pub(super) fn to_base_data(item: FeedItem, feed_id: Option<i64>) -> ResourceBaseData {
+        let match_title = nfkc_to_lowercase(&item.title);
+        let mut release = parse_release(&item.title);
+        // 合集的集数区间以 feed 解析结果为准
+        if item.episode_range.is_some() {
+            release.episode = None;
+            release.episode_range = item.episode_range;
+        }
+        ResourceBaseData {
+            title: item.title,
+            match_title,
+            url: item.resource_url,
+            info_hash: item.info_hash,
+            info_hash_v2: item.info_hash_v2,
+            published_at: item.published_at,
+            kind: item.kind,
+            release,
+            files: item.files,
+            size: item.size,
+            source: feed_id.map(|feed_id| ResourceFeedSource {
+                feed_id,
+                source_url: item.source_url,
+            }),
+        }
+    }
 }
@@ -1,7 +1,44 @@
+use std::collections::HashMap;
+
 use anyhow::{Result, anyhow};
+use common::{infra::schema::add_column_if_missing, shared::episode::collection_marker};
 use sqlx::{Pool, QueryBuilder, Row, Sqlite, Transaction, sqlite::SqliteRow};
 
-use crate::entity::model::{ResourceBaseData, ResourceProp};
+use feed::entity::model::{ResourceKind, TorrentFileInfo};
+
+use crate::entity::{
+    model::{
+        AudioCodec, ReleaseInfo, ReleaseSource, ResourceBaseData, ResourceFeedSource, ResourceProp,
+        SubtitleKind, SubtitleLang, VideoCodec,
+    },
+    release_parser::parse_release,
+};
+
+const FILE_BATCH_SIZE: usize = 500;
+
+/// 标题解析规则的版本，调整合集或发布信息的识别后递增，启动时重新解析已有资源
+const TITLE_PARSER_VERSION: i64 = 1;
+
+/// 标题解析出的发布信息列，旧数据库中需补充
+const RELEASE_COLUMN_DEFS: [(&str, &str); 10] = [
+    ("release_group", "TEXT NULL"),
+    ("episode", "REAL NULL"),
+    ("season", "INTEGER NULL"),
+    ("version", "INTEGER NULL"),
+    ("resolution", "INTEGER NULL"),
+    ("video_codec", "TEXT NULL"),
+    ("audio_codec", "TEXT NULL"),
+    ("source", "TEXT NULL"),
+    ("subtitle_langs", "TEXT NULL"),
+    ("subtitle_kind", "TEXT NULL"),
+];
+
+/// resource 表中由 `parse_resource_row` 读取的列
+pub(super) const RESOURCE_COLUMNS: &str = "info_hash, title, match_title, url, published_at, kind, ep_start, ep_end, size, \
+    release_group, episode, season, version, resolution, video_codec, audio_codec, source, subtitle_langs, subtitle_kind, info_hash_v2";
+
+/// 以 JSON 数组聚合资源对应的文件列表：`[[path, size], ...]`
+pub(super) const FILES_COLUMN: &str = "(SELECT json_group_array(json_array(f.path, f.size) ORDER BY f.idx) FROM resource_file f WHERE f.info_hash = resource.info_hash) AS files";
 
 #[derive(Clone)]
 pub struct ResourceSqliteClient {
@@ -31,18 +68,75 @@ impl ResourceSqliteClient {
                 match_title     TEXT    NOT NULL,
                 url             TEXT    NOT NULL,
                 published_at    INTEGER NOT NULL,
+                kind            INTEGER NOT NULL DEFAULT 0,   -- 0=单集, 1=合集
+                ep_start        INTEGER NULL,                 -- 合集集数区间
+                ep_end          INTEGER NULL,
+                size            INTEGER NULL,                 -- 订阅条目声明的大小（字节）
+                release_group   TEXT    NULL,                 -- 以下为标题解析出的发布信息
+                episode         REAL    NULL,
+                season          INTEGER NULL,
+                version         INTEGER NULL,
+                resolution      INTEGER NULL,
+                video_codec     TEXT    NULL,
+                audio_codec     TEXT    NULL,
+                source          TEXT    NULL,
+                subtitle_langs  TEXT    NULL,                 -- 逗号分隔的语言代码
+                subtitle_kind   TEXT    NULL,
+                info_hash_v2    BLOB    NULL,                 -- v2 及混合种子的 SHA-256 哈希
                 created_at      INTEGER NOT NULL DEFAULT (unixepoch())
             );",
         )
         .execute(&mut **tx)
         .await?;
 
+        add_column_if_missing(tx, "resource", "kind", "INTEGER NOT NULL DEFAULT 0").await?;
+        add_column_if_missing(tx, "resource", "ep_start", "INTEGER NULL").await?;
+        add_column_if_missing(tx, "resource", "ep_end", "INTEGER NULL").await?;
+        add_column_if_missing(tx, "resource", "size", "INTEGER NULL").await?;
+        let mut release_added = false;
+        for (column, definition) in RELEASE_COLUMN_DEFS {
+            release_added |= add_column_if_missing(tx, "resource", column, definition).await?;
+        }
+
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS resource_meta (
+                key         TEXT    NOT NULL PRIMARY KEY,
+                value       INTEGER NOT NULL
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+        let parser_version: Option<i64> =
+            sqlx::query_scalar("SELECT value FROM resource_meta WHERE key = 'title_parser'")
+                .fetch_optional(&mut **tx)
+                .await?;
+        // 新增发布信息列或解析规则更新时，为已有资源重新解析标题
+        if release_added || parser_version.unwrap_or(0) < TITLE_PARSER_VERSION {
+            Self::reparse_titles(tx).await?;
+            sqlx::query(
+                "INSERT INTO resource_meta (key, value) VALUES ('title_parser', ?)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            )
+            .bind(TITLE_PARSER_VERSION)
+            .execute(&mut **tx)
+            .await?;
+        }
+        // 需先于 idx_resource_info_hash_v2 补充
+        add_column_if_missing(tx, "resource", "info_hash_v2", "BLOB NULL").await?;
+
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_resource_published_at ON resource(published_at);",
         )
         .execute(&mut **tx)
         .await?;
 
+        sqlx::query(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_resource_info_hash_v2 ON resource(info_hash_v2) WHERE info_hash_v2 IS NOT NULL;",
+        )
+        .execute(&mut **tx)
+        .await?;
+
         sqlx::query(
             "
             CREATE TABLE IF NOT EXISTS resource_url_info_hash (
@@ -54,29 +148,264 @@ impl ResourceSqliteClient {
         .execute(&mut **tx)
         .await?;
 
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS resource_file (
+                info_hash   BLOB    NOT NULL,
+                idx         INTEGER NOT NULL,   -- 文件在种子中的顺序
+                path        TEXT    NOT NULL,
+                size        INTEGER NOT NULL,
+                PRIMARY KEY (info_hash, idx)
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_feed_item_url ON resource_url_info_hash(url);")
             .execute(&mut **tx)
             .await?;
+
+        // 资源来源记录：资源在各 feed 中首次及最近一次出现的时间
+        sqlx::query(
+            "
+            CREATE TABLE IF NOT EXISTS resource_feed (
+                info_hash       BLOB    NOT NULL,
+                feed_id         INTEGER NOT NULL,
+                source_url      TEXT    NOT NULL,   -- 条目详情页
+                first_seen_at   INTEGER NOT NULL DEFAULT (unixepoch()),
+                last_seen_at    INTEGER NOT NULL DEFAULT (unixepoch()),
+                PRIMARY KEY (info_hash, feed_id)
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_resource_feed_feed_id ON resource_feed(feed_id);",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let fts_sql: Option<String> = sqlx::query_scalar(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'resource_title_fts'",
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        // 早期版本按词建立索引，无法覆盖订阅匹配的子串判断，需重建
+        let rebuild_fts = match fts_sql {
+            Some(sql) if !sql.contains("trigram") => {
+                sqlx::query("DROP TABLE resource_title_fts")
+                    .execute(&mut **tx)
+                    .await?;
+                true
+            }
+            Some(_) => false,
+            None => true,
+        };
+
+        // 标题全文索引：以 trigram 分词索引 match_title，检索结果与订阅匹配的子串判断一致
+        // resource 主键为 info_hash，其隐式 rowid 在 VACUUM 后可能变化，
+        // 因此以 UNINDEXED 列保存 info_hash 而非复用 rowid
+        sqlx::query(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS resource_title_fts USING fts5(
+                info_hash UNINDEXED,
+                char_text,
+                tokenize='trigram case_sensitive 1'
+            );",
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        // 首次创建索引时为已有资源补建
+        if rebuild_fts {
+            let rows = sqlx::query("SELECT info_hash, match_title FROM resource")
+                .fetch_all(&mut **tx)
+                .await?;
+            for chunk in rows.chunks(FILE_BATCH_SIZE) {
+                let mut qb =
+                    QueryBuilder::new("INSERT INTO resource_title_fts (info_hash, char_text) ");
+                qb.push_values(chunk, |mut b, row| {
+                    let info_hash: Vec<u8> = row.get("info_hash");
+                    let match_title: String = row.get("match_title");
+                    b.push_bind(info_hash).push_bind(match_title);
+                });
+                qb.build().execute(&mut **tx).await?;
+            }
+        }
         Ok(())
     }
 }
 
 impl ResourceSqliteClient {
+    /// 按当前规则重新识别已有资源的合集类型、集数区间及发布信息，
+    /// 与入库时一致，合集与集数均由标题解析得出
+    async fn reparse_titles(tx: &mut Transaction<'_, Sqlite>) -> Result<()> {
+        let rows = sqlx::query("SELECT info_hash, title FROM resource")
+            .fetch_all(&mut **tx)
+            .await?;
+        for row in rows {
+            let info_hash: Vec<u8> = row.try_get("info_hash")?;
+            let title: String = row.try_get("title")?;
+            let release = parse_release(&title);
+            let (kind, range) = match collection_marker(&title) {
+                Some(_) => (ResourceKind::Collection, release.episode_range),
+                None => (ResourceKind::Episode, None),
+            };
+            sqlx::query(
+                "UPDATE resource SET kind = ?, ep_start = ?, ep_end = ?,
+                    release_group = ?, episode = ?, season = ?, version = ?,
+                    resolution = ?, video_codec = ?, audio_codec = ?, source = ?,
+                    subtitle_langs = ?, subtitle_kind = ?
+                 WHERE info_hash = ?",
+            )
+            .bind(i32::from(kind))
+            .bind(range.map(|r| r.0))
+            .bind(range.map(|r| r.1))
+            .bind(&release.group)
+            .bind(release.episode)
+            .bind(release.season)
+            .bind(release.version)
+            .bind(release.resolution)
+            .bind(release.video_codec.map(|v| v.as_str()))
+            .bind(release.audio_codec.map(|v| v.as_str()))
+            .bind(release.source.map(|v| v.as_str()))
+            .bind(Self::subtitle_langs_column(&release))
+            .bind(release.subtitle_kind.map(|v| v.as_str()))
+            .bind(info_hash)
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 字幕语言以逗号分隔的语言代码保存，为空时存 NULL
+    fn subtitle_langs_column(release: &ReleaseInfo) -> Option<String> {
+        (!release.subtitle_langs.is_empty()).then(|| {
+            release
+                .subtitle_langs
+                .iter()
+                .map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+    }
+
+    /// 追加标题过滤条件：`term` 须为 match_title 的子串，调用方需先以 `nfkc_to_lowercase` 处理。
+    /// trigram 索引只能检索不少于 3 个字符的文本，更短时逐行比较
+    pub(super) fn push_title_filter(qb: &mut QueryBuilder<Sqlite>, term: String) {
+        if term.chars().count() >= 3 {
+            qb.push("info_hash IN (SELECT info_hash FROM resource_title_fts WHERE resource_title_fts MATCH ");
+            qb.push_bind(format!("\"{}\"", term.replace('"', "\"\"")));
+            qb.push(")");
+        } else {
+            qb.push("instr(match_title, ");
+            qb.push_bind(term);
+            qb.push(") > 0");
+        }
+    }
+
     pub(super) fn parse_resource_row(row: &SqliteRow) -> Result<ResourceBaseData> {
         let info_hash_blob: Vec<u8> = row.try_get("info_hash")?;
         let info_hash: [u8; 20] = info_hash_blob
             .try_into()
             .map_err(|_| anyhow!("info_hash length is not 20"))?;
 
+        let kind: i32 = row.try_get("kind")?;
+        let kind = ResourceKind::try_from(kind).map_err(|e| anyhow!("{}", e))?;
+        let ep_start: Option<u32> = row.try_get("ep_start")?;
+        let ep_end: Option<u32> = row.try_get("ep_end")?;
+        let size: Option<i64> = row.try_get("size")?;
+        let info_hash_v2: Option<Vec<u8>> = row.try_get("info_hash_v2")?;
+        let info_hash_v2 = info_hash_v2
+            .map(|v| <[u8; 32]>::try_from(v).map_err(|_| anyhow!("info_hash_v2 length is not 32")))
+            .transpose()?;
+
         Ok(ResourceBaseData {
             info_hash,
+            info_hash_v2,
             title: row.try_get("title")?,
             match_title: row.try_get("match_title")?,
             url: row.try_get("url")?,
             published_at: row.try_get("published_at")?,
+            kind,
+            release: Self::parse_release_columns(row, ep_start.zip(ep_end))?,
+            files: vec![],
+            size: size.map(|v| v.max(0) as u64),
+            source: None,
+        })
+    }
+
+    fn parse_release_columns(
+        row: &SqliteRow,
+        episode_range: Option<(u32, u32)>,
+    ) -> Result<ReleaseInfo> {
+        let code = |name: &str| -> Result<Option<String>> { Ok(row.try_get(name)?) };
+        let subtitle_langs = code("subtitle_langs")?
+            .map(|v| {
+                v.split(',')
+                    .filter_map(SubtitleLang::from_code)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        Ok(ReleaseInfo {
+            group: row.try_get("release_group")?,
+            episode: row.try_get("episode")?,
+            episode_range,
+            season: row.try_get("season")?,
+            version: row.try_get("version")?,
+            resolution: row.try_get("resolution")?,
+            video_codec: code("video_codec")?.and_then(|v| VideoCodec::from_code(&v)),
+            audio_codec: code("audio_codec")?.and_then(|v| AudioCodec::from_code(&v)),
+            source: code("source")?.and_then(|v| ReleaseSource::from_code(&v)),
+            subtitle_langs,
+            subtitle_kind: code("subtitle_kind")?.and_then(|v| SubtitleKind::from_code(&v)),
         })
     }
 
+    /// 解析 `FILES_COLUMN` 聚合出的文件列表
+    pub(super) fn parse_files_column(row: &SqliteRow) -> Result<Vec<TorrentFileInfo>> {
+        let files: Option<String> = row.try_get("files")?;
+        let Some(files) = files else {
+            return Ok(vec![]);
+        };
+        let files: Vec<(String, u64)> = serde_json::from_str(&files)?;
+        Ok(files
+            .into_iter()
+            .map(|(path, size)| TorrentFileInfo { path, size })
+            .collect())
+    }
+
+    pub(super) async fn batch_insert_files(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        chunk: &[ResourceBaseData],
+    ) -> Result<()> {
+        let files = chunk
+            .iter()
+            .flat_map(|item| {
+                item.files
+                    .iter()
+                    .enumerate()
+                    .map(move |(idx, file)| (&item.info_hash, idx as i64, file))
+            })
+            .collect::<Vec<_>>();
+
+        // 单个种子可能包含大量文件，按行数分批避免超出绑定参数上限
+        for files in files.chunks(FILE_BATCH_SIZE) {
+            let mut qb = QueryBuilder::new(
+                "INSERT OR IGNORE INTO resource_file (info_hash, idx, path, size) ",
+            );
+            qb.push_values(files, |mut b, (info_hash, idx, file)| {
+                b.push_bind(&info_hash[..])
+                    .push_bind(*idx)
+                    .push_bind(&file.path)
+                    .push_bind(file.size as i64);
+            });
+            qb.build().execute(&mut *tx).await?;
+        }
+        Ok(())
+    }
+
     pub(super) async fn batch_insert_url_hash(
         &self,
         tx: &mut sqlx::SqliteConnection,
@@ -97,42 +426,200 @@ impl ResourceSqliteClient {
         qb.build().execute(&mut *tx).await?;
         Ok(())
     }
+    /// 混合种子可能分别以 v1、v2 哈希出现在不同来源，v2 哈希已知的条目统一使用
+    /// 已入库资源或同批次混合种子的标识，避免同一资源被保存两次
+    pub(super) async fn resolve_info_hash_v2(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        items: &mut [ResourceBaseData],
+    ) -> Result<()> {
+        let mut v2s = items
+            .iter()
+            .filter_map(|item| item.info_hash_v2)
+            .collect::<Vec<_>>();
+        if v2s.is_empty() {
+            return Ok(());
+        }
+        v2s.sort();
+        v2s.dedup();
+
+        // 同批次中混合种子的 v1 标识优先于纯 v2 的截断哈希
+        let mut ids = items
+            .iter()
+            .filter_map(|item| {
+                item.info_hash_v2
+                    .filter(|v2| v2[..20] != item.info_hash)
+                    .map(|v2| (v2, item.info_hash))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // 已入库的资源标识优先
+        for chunk in v2s.chunks(FILE_BATCH_SIZE) {
+            let mut qb = QueryBuilder::new(
+                "SELECT info_hash, info_hash_v2 FROM resource WHERE info_hash_v2 IN (",
+            );
+            let mut separated = qb.separated(", ");
+            for v2 in chunk {
+                separated.push_bind(&v2[..]);
+            }
+            qb.push(")");
+            for row in qb.build().fetch_all(&mut *tx).await? {
+                let id: Vec<u8> = row.try_get("info_hash")?;
+                let v2: Vec<u8> = row.try_get("info_hash_v2")?;
+                let id: [u8; 20] = id
+                    .try_into()
+                    .map_err(|_| anyhow!("info_hash length is not 20"))?;
+                let v2: [u8; 32] = v2
+                    .try_into()
+                    .map_err(|_| anyhow!("info_hash_v2 length is not 32"))?;
+                ids.insert(v2, id);
+            }
+        }
+
+        for item in items.iter_mut() {
+            if let Some(id) = item.info_hash_v2.and_then(|v2| ids.get(&v2)) {
+                item.info_hash = *id;
+            }
+        }
+        Ok(())
+    }
+
+    /// 为先以 v1 哈希入库的混合种子补充 v2 哈希
+    pub(super) async fn batch_fill_info_hash_v2(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        chunk: &[ResourceBaseData],
+    ) -> Result<()> {
+        for item in chunk {
+            let Some(v2) = item.info_hash_v2 else {
+                continue;
+            };
+            sqlx::query(
+                "UPDATE OR IGNORE resource SET info_hash_v2 = ? WHERE info_hash = ? AND info_hash_v2 IS NULL",
+            )
+            .bind(&v2[..])
+            .bind(&item.info_hash[..])
+            .execute(&mut *tx)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 记录资源来源，已存在的记录只刷新最近出现时间及详情页
+    pub(super) async fn batch_upsert_feed_source(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        chunk: &[ResourceBaseData],
+    ) -> Result<()> {
+        let sources = chunk
+            .iter()
+            .filter_map(|item| item.source.as_ref().map(|s| (&item.info_hash, s)))
+            .collect::<Vec<_>>();
+        Self::upsert_feed_sources(tx, sources).await
+    }
+
+    pub(super) async fn upsert_feed_sources(
+        tx: &mut sqlx::SqliteConnection,
+        sources: Vec<(&[u8; 20], &ResourceFeedSource)>,
+    ) -> Result<()> {
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let mut qb =
+            QueryBuilder::new("INSERT INTO resource_feed (info_hash, feed_id, source_url) ");
+        qb.push_values(sources, |mut b, (info_hash, source)| {
+            b.push_bind(&info_hash[..])
+                .push_bind(source.feed_id)
+                .push_bind(&source.source_url);
+        });
+        qb.push(
+            " ON CONFLICT (info_hash, feed_id) DO UPDATE SET source_url = excluded.source_url, last_seen_at = unixepoch()",
+        );
+        qb.build().execute(&mut *tx).await?;
+        Ok(())
+    }
+
+    /// 排除仍出现在订阅源中的资源：最近一次出现时间在所属订阅源最近一次列出条目前的 `listed_window` 秒内
+    pub(super) fn push_unlisted(qb: &mut QueryBuilder<Sqlite>, listed_window: Option<i64>) {
+        if let Some(window) = listed_window {
+            qb.push(
+                " AND info_hash NOT IN (SELECT rf.info_hash FROM resource_feed rf JOIN (SELECT feed_id, MAX(last_seen_at) AS latest FROM resource_feed GROUP BY feed_id) f ON rf.feed_id = f.feed_id WHERE rf.last_seen_at >= f.latest - ",
+            );
+            qb.push_bind(window);
+            qb.push(")");
+        }
+    }
+
+    /// 仅为本次新插入的资源建立标题索引
+    pub(super) async fn batch_insert_title_fts(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        inserted: &[ResourceProp],
+    ) -> Result<()> {
+        if inserted.is_empty() {
+            return Ok(());
+        }
+
+        let mut qb = QueryBuilder::new("INSERT INTO resource_title_fts (info_hash, char_text) ");
+        qb.push_values(inserted, |mut b, item| {
+            b.push_bind(&item.data.info_hash[..])
+                .push_bind(&item.data.match_title);
+        });
+        qb.build().execute(&mut *tx).await?;
+        Ok(())
+    }
+
+    /// 插入资源并返回实际新增的行，已存在的资源被跳过
     pub(super) async fn batch_insert_resource(
         &self,
         tx: &mut sqlx::SqliteConnection, // 改为接收事务连接
         chunk: &[ResourceBaseData],
-        need_return: bool,
     ) -> Result<Vec<ResourceProp>> {
         if chunk.is_empty() {
             return Ok(Vec::new());
         }
 
-        let mut qb = QueryBuilder::new(
-            "INSERT OR IGNORE INTO resource (info_hash, title, match_title, url, published_at) ",
-        );
+        let mut qb = QueryBuilder::new("INSERT OR IGNORE INTO resource (");
+        qb.push(RESOURCE_COLUMNS);
+        qb.push(") ");
 
         qb.push_values(chunk, |mut b, item| {
+            let release = &item.release;
             b.push_bind(&item.info_hash[..])
                 .push_bind(&item.title)
                 .push_bind(&item.match_title)
                 .push_bind(&item.url)
-                .push_bind(item.published_at);
+                .push_bind(item.published_at)
+                .push_bind(i32::from(item.kind))
+                .push_bind(release.episode_range.map(|r| r.0))
+                .push_bind(release.episode_range.map(|r| r.1))
+                .push_bind(item.size.map(|v| v as i64))
+                .push_bind(&release.group)
+                .push_bind(release.episode)
+                .push_bind(release.season)
+                .push_bind(release.version)
+                .push_bind(release.resolution)
+                .push_bind(release.video_codec.map(|v| v.as_str()))
+                .push_bind(release.audio_codec.map(|v| v.as_str()))
+                .push_bind(release.source.map(|v| v.as_str()))
+                .push_bind(Self::subtitle_langs_column(release))
+                .push_bind(release.subtitle_kind.map(|v| v.as_str()))
+                .push_bind(item.info_hash_v2.as_ref().map(|v| &v[..]));
         });
 
-        if need_return {
-            qb.push(" RETURNING info_hash, title, match_title, url, published_at");
-            // 使用 &mut *tx 执行查询
-            let rows = qb.build().fetch_all(&mut *tx).await?;
-            let mut props = Vec::with_capacity(rows.len());
-            for row in rows {
-                props.push(ResourceProp {
-                    data: Self::parse_resource_row(&row)?,
-                });
+        qb.push(" RETURNING ");
+        qb.push(RESOURCE_COLUMNS);
+        // 使用 &mut *tx 执行查询
+        let rows = qb.build().fetch_all(&mut *tx).await?;
+        let mut props = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut data = Self::parse_resource_row(&row)?;
+            if let Some(item) = chunk.iter().find(|i| i.info_hash == data.info_hash) {
+                data.files = item.files.clone();
             }
-            Ok(props)
-        } else {
-            qb.build().execute(&mut *tx).await?;
-            Ok(Vec::new())
+            props.push(ResourceProp { data });
         }
+        Ok(props)
     }
 }
@@ -3,6 +3,7 @@ use std::{collections::HashMap, pin::Pin};
 use anyhow::Result;
 use async_stream::try_stream;
 use async_trait::async_trait;
+use common::shared::str::nfkc_to_lowercase;
 use feed::infra::feed::FeedItemRepository;
 use futures::{Stream, StreamExt};
 use sqlx::{QueryBuilder, Row};
@@ -10,9 +11,12 @@ use sqlx::{QueryBuilder, Row};
 use crate::{
     entity::{
         cap::ResourceRepository,
-        model::{ResourceBaseData, ResourceProp, ResourceQuery},
+        model::{
+            ResourceBaseData, ResourceFeedSource, ResourceProp, ResourcePrunePolicy,
+            ResourcePruneReport, ResourceQuery,
+        },
     },
-    infra::repository::client::ResourceSqliteClient,
+    infra::repository::client::{FILES_COLUMN, RESOURCE_COLUMNS, ResourceSqliteClient},
 };
 
 const BATCH_SIZE: usize = 100;
@@ -24,9 +28,11 @@ impl ResourceRepository for ResourceSqliteClient {
         query: &'a ResourceQuery,
     ) -> Pin<Box<dyn Stream<Item = Result<ResourceProp>> + Send + 'a>> {
         let stream = try_stream! {
-            let mut qb = QueryBuilder::new(
-                "SELECT info_hash, title, match_title, url, published_at FROM resource WHERE 1=1"
-            );
+            let mut qb = QueryBuilder::new("SELECT ");
+            qb.push(RESOURCE_COLUMNS);
+            qb.push(", ");
+            qb.push(FILES_COLUMN);
+            qb.push(" FROM resource WHERE 1=1");
 
             // 1. 优先拼接能走 B-Tree 索引的范围过滤
             if let Some(start_at) = query.start_at {
@@ -38,18 +44,26 @@ impl ResourceRepository for ResourceSqliteClient {
                 qb.push_bind(end_at);
             }
 
-            // 2. 无法利用普通索引的模糊检索后置
-            if let Some(ref keywords) = query.keywords {
-                for kw in keywords {
-                    if !kw.trim().is_empty() {
-                        let pattern = format!("%{}%", kw.trim());
-                        qb.push(" AND (title LIKE ");
-                        // 注意：这里必须传值 (Owned String) 以交出所有权，绝对不能传 &pattern 引用
-                        qb.push_bind(pattern.clone());
-                        qb.push(" OR match_title LIKE ");
-                        qb.push_bind(pattern);
-                        qb.push(")");
+            // 2. 关键字检索走标题全文索引：keywords 需全部命中，any_titles 命中任一即可
+            // 空文本是任意标题的子串，不参与过滤
+            for kw in query.keywords.iter().flatten() {
+                let kw = nfkc_to_lowercase(kw);
+                if !kw.is_empty() {
+                    qb.push(" AND ");
+                    Self::push_title_filter(&mut qb, kw);
+                }
+            }
+            if let Some(titles) = &query.any_titles {
+                let titles = titles.iter().map(|t| nfkc_to_lowercase(t)).collect::<Vec<_>>();
+                if !titles.is_empty() && titles.iter().all(|t| !t.is_empty()) {
+                    qb.push(" AND (");
+                    for (i, title) in titles.into_iter().enumerate() {
+                        if i > 0 {
+                            qb.push(" OR ");
+                        }
+                        Self::push_title_filter(&mut qb, title);
                     }
+                    qb.push(")");
                 }
             }
 
@@ -70,7 +84,8 @@ impl ResourceRepository for ResourceSqliteClient {
 
             while let Some(row_res) = rows.next().await {
                 let row = row_res?;
-                let data = Self::parse_resource_row(&row)?;
+                let mut data = Self::parse_resource_row(&row)?;
+                data.files = Self::parse_files_column(&row)?;
                 yield ResourceProp { data };
             }
         };
@@ -78,15 +93,40 @@ impl ResourceRepository for ResourceSqliteClient {
         Box::pin(stream)
     }
 
-    async fn insert_or_skip(&self, items: Vec<ResourceBaseData>) -> Result<()> {
+    async fn find(&self, id: &[u8; 20]) -> Result<Option<ResourceProp>> {
+        let mut qb = QueryBuilder::new("SELECT ");
+        qb.push(RESOURCE_COLUMNS);
+        qb.push(", ");
+        qb.push(FILES_COLUMN);
+        qb.push(" FROM resource WHERE info_hash = ");
+        qb.push_bind(id.as_slice());
+        let Some(row) = qb.build().fetch_optional(&self.pool).await? else {
+            return Ok(None);
+        };
+        let mut data = Self::parse_resource_row(&row)?;
+        data.files = Self::parse_files_column(&row)?;
+        Ok(Some(ResourceProp { data }))
+    }
+
+    async fn resolve_ids(&self, items: &mut [ResourceBaseData]) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.resolve_info_hash_v2(&mut conn, items).await
+    }
+
+    async fn insert_or_skip(&self, mut items: Vec<ResourceBaseData>) -> Result<()> {
         if items.is_empty() {
             return Ok(());
         }
         let mut tx = self.pool.begin().await?;
+        self.resolve_info_hash_v2(&mut tx, &mut items).await?;
 
         for chunk in items.chunks(BATCH_SIZE) {
-            self.batch_insert_resource(&mut tx, chunk, false).await?;
+            let inserted = self.batch_insert_resource(&mut tx, chunk).await?;
+            self.batch_insert_title_fts(&mut tx, &inserted).await?;
+            self.batch_fill_info_hash_v2(&mut tx, chunk).await?;
+            self.batch_upsert_feed_source(&mut tx, chunk).await?;
             self.batch_insert_url_hash(&mut tx, chunk).await?;
+            self.batch_insert_files(&mut tx, chunk).await?;
         }
 
         tx.commit().await?;
@@ -95,7 +135,7 @@ impl ResourceRepository for ResourceSqliteClient {
 
     async fn insert_or_skip_return_new(
         &self,
-        items: Vec<ResourceBaseData>,
+        mut items: Vec<ResourceBaseData>,
     ) -> Result<Vec<ResourceProp>> {
         if items.is_empty() {
             return Ok(Vec::new());
@@ -104,9 +144,14 @@ impl ResourceRepository for ResourceSqliteClient {
         let mut results = Vec::with_capacity(items.len());
 
         let mut tx = self.pool.begin().await?;
+        self.resolve_info_hash_v2(&mut tx, &mut items).await?;
         for chunk in items.chunks(BATCH_SIZE) {
-            let inserted = self.batch_insert_resource(&mut tx, chunk, true).await?;
+            let inserted = self.batch_insert_resource(&mut tx, chunk).await?;
+            self.batch_insert_title_fts(&mut tx, &inserted).await?;
+            self.batch_fill_info_hash_v2(&mut tx, chunk).await?;
+            self.batch_upsert_feed_source(&mut tx, chunk).await?;
             self.batch_insert_url_hash(&mut tx, chunk).await?;
+            self.batch_insert_files(&mut tx, chunk).await?;
 
             results.extend(inserted);
         }
@@ -114,6 +159,105 @@ impl ResourceRepository for ResourceSqliteClient {
         tx.commit().await?;
         Ok(results)
     }
+
+    async fn touch_feed_sources(&self, sources: Vec<([u8; 20], ResourceFeedSource)>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for chunk in sources.chunks(BATCH_SIZE) {
+            let chunk = chunk.iter().map(|(id, s)| (id, s)).collect();
+            Self::upsert_feed_sources(&mut tx, chunk).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_feed_sources(&self, feed_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM resource_feed WHERE feed_id = ?")
+            .bind(feed_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn prune(
+        &self,
+        policy: &ResourcePrunePolicy,
+        keep: &[[u8; 20]],
+    ) -> Result<ResourcePruneReport> {
+        // 需保留的资源写入临时表，避免拼接过长的 IN 列表
+        const UNREFERENCED: &str = " AND info_hash NOT IN (SELECT info_hash FROM temp.prune_keep)";
+
+        let mut tx = self.pool.begin().await?;
+        let mut report = ResourcePruneReport::default();
+
+        sqlx::query("CREATE TEMP TABLE IF NOT EXISTS prune_keep (info_hash BLOB PRIMARY KEY)")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM temp.prune_keep")
+            .execute(&mut *tx)
+            .await?;
+        for chunk in keep.chunks(BATCH_SIZE) {
+            let mut qb = QueryBuilder::new("INSERT OR IGNORE INTO temp.prune_keep (info_hash) ");
+            qb.push_values(chunk, |mut b, id| {
+                b.push_bind(&id[..]);
+            });
+            qb.build().execute(&mut *tx).await?;
+        }
+
+        if let Some(before) = policy.published_before {
+            let mut qb = QueryBuilder::new("DELETE FROM resource WHERE published_at < ");
+            qb.push_bind(before);
+            qb.push(UNREFERENCED);
+            report.resources += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+        if let Some(max_rows) = policy.max_rows {
+            let mut qb = QueryBuilder::new(
+                "DELETE FROM resource WHERE info_hash IN (SELECT info_hash FROM resource ORDER BY published_at DESC LIMIT -1 OFFSET ",
+            );
+            qb.push_bind(max_rows as i64);
+            qb.push(")");
+            qb.push(UNREFERENCED);
+            report.resources += qb.build().execute(&mut *tx).await?.rows_affected();
+        }
+
+        if report.resources > 0 {
+            report.files = sqlx::query(
+                "DELETE FROM resource_file WHERE info_hash NOT IN (SELECT info_hash FROM resource)",
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            sqlx::query(
+                "DELETE FROM resource_title_fts WHERE info_hash NOT IN (SELECT info_hash FROM resource)",
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            // 仍出现在订阅源中的条目保留 url 及来源记录，来源记录的出现时间随轮询刷新，
+            // 条目从订阅源中消失后才会在之后的清理中一并删除
+            let mut qb = QueryBuilder::new(
+                "DELETE FROM resource_url_info_hash WHERE info_hash NOT IN (SELECT info_hash FROM resource)",
+            );
+            Self::push_unlisted(&mut qb, policy.listed_window);
+            report.url_hashes = qb.build().execute(&mut *tx).await?.rows_affected();
+
+            let mut qb = QueryBuilder::new(
+                "DELETE FROM resource_feed WHERE info_hash NOT IN (SELECT info_hash FROM resource)",
+            );
+            Self::push_unlisted(&mut qb, policy.listed_window);
+            qb.build().execute(&mut *tx).await?;
+        }
+
+        sqlx::query("DROP TABLE temp.prune_keep")
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -147,3 +291,649 @@ impl FeedItemRepository for ResourceSqliteClient {
         Ok(map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use feed::entity::model::{ResourceKind, TorrentFileInfo};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::entity::model::{ReleaseInfo, ResourceFeedSource};
+
+    fn resource(n: u8, published_at: i64) -> ResourceBaseData {
+        ResourceBaseData {
+            title: format!("[Group] Title - {:02}", n),
+            match_title: format!("[group]title-{:02}", n),
+            url: format!("https://example.com/{}.torrent", n),
+            info_hash: [n; 20],
+            info_hash_v2: None,
+            published_at,
+            kind: ResourceKind::Episode,
+            release: ReleaseInfo::default(),
+            files: vec![TorrentFileInfo {
+                path: format!("{}.mkv", n),
+                size: 1,
+            }],
+            size: None,
+            source: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn init_migrates_existing_resource_table() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE resource (
+                info_hash       BLOB    NOT NULL PRIMARY KEY,
+                title           TEXT    NOT NULL,
+                match_title     TEXT    NOT NULL,
+                url             TEXT    NOT NULL,
+                published_at    INTEGER NOT NULL,
+                created_at      INTEGER NOT NULL DEFAULT (unixepoch())
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO resource (info_hash, title, match_title, url, published_at) VALUES (?, ?, '', '', 1)",
+        )
+        .bind(&[1u8; 20][..])
+        .bind("[LoliHouse] Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕]")
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO resource (info_hash, title, match_title, url, published_at) VALUES (?, ?, '', '', 2)",
+        )
+        .bind(&[2u8; 20][..])
+        .bind("[Group] Sousou no Frieren (01v2-28v2) [1080p]")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let client = ResourceSqliteClient::new(pool.clone());
+        client.init().await.unwrap();
+        // 重复初始化不会再次修改表结构
+        client.init().await.unwrap();
+        // 解析规则更新后重新识别已有资源
+        sqlx::query("UPDATE resource SET kind = 0, ep_start = NULL, ep_end = NULL")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM resource_meta")
+            .execute(&pool)
+            .await
+            .unwrap();
+        client.init().await.unwrap();
+
+        let query = ResourceQuery {
+            keywords: None,
+            any_titles: None,
+            start_at: None,
+            end_at: None,
+            limit: None,
+            offset: None,
+        };
+        let data = client
+            .stream(&query)
+            .map(|i| i.unwrap().data)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(data.len(), 2);
+        let episode = data.iter().find(|i| i.info_hash == [1; 20]).unwrap();
+        assert_eq!(episode.kind, ResourceKind::Episode);
+        assert_eq!(episode.info_hash_v2, None);
+        assert_eq!(episode.release.group.as_deref(), Some("LoliHouse"));
+        assert_eq!(episode.release.episode, Some(5.0));
+        assert_eq!(episode.release.resolution, Some(1080));
+        let collection = data.iter().find(|i| i.info_hash == [2; 20]).unwrap();
+        assert_eq!(collection.kind, ResourceKind::Collection);
+        assert_eq!(collection.release.episode_range, Some((1, 28)));
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_referenced_resources() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = ResourceSqliteClient::new(pool.clone());
+        client.init().await.unwrap();
+
+        client
+            .insert_or_skip(
+                (1..=5)
+                    .map(|n| ResourceBaseData {
+                        source: Some(ResourceFeedSource {
+                            feed_id: 1,
+                            source_url: format!("https://example.com/view/{}", n),
+                        }),
+                        ..resource(n, n as i64 * 100)
+                    })
+                    .collect(),
+            )
+            .await
+            .unwrap();
+        // 2 仍出现在订阅源中，3 已从订阅源中消失
+        sqlx::query("UPDATE resource_feed SET last_seen_at = ? WHERE info_hash = ?")
+            .bind(10)
+            .bind(&[3u8; 20][..])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // 按时间清理 1、2，其中 1 被剧集引用而保留；再按条数保留最新的 2 条，清理 3
+        let report = client
+            .prune(
+                &ResourcePrunePolicy {
+                    published_before: Some(250),
+                    max_rows: Some(2),
+                    listed_window: Some(100),
+                },
+                &[[1u8; 20]],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ResourcePruneReport {
+                resources: 2,
+                url_hashes: 1,
+                files: 2,
+            }
+        );
+
+        let query = ResourceQuery {
+            keywords: None,
+            any_titles: None,
+            start_at: None,
+            end_at: None,
+            limit: None,
+            offset: None,
+        };
+        let left = client
+            .stream(&query)
+            .map(|i| i.unwrap().data.info_hash[0])
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(left, vec![5, 4, 1]);
+
+        // 仍被列出的 2 保留 url 记录，下次轮询不会被当作新资源
+        let known = client
+            .get_url_info_hash(vec![
+                "https://example.com/2.torrent",
+                "https://example.com/3.torrent",
+            ])
+            .await
+            .unwrap();
+        assert_eq!(known.len(), 1);
+        assert!(known.contains_key("https://example.com/2.torrent"));
+        client.vacuum().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stream_matches_title_index() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = ResourceSqliteClient::new(pool);
+        client.init().await.unwrap();
+
+        let titles = [
+            "[Group] 葬送的芙莉莲 - 01 [1080p]",
+            "[Group] Sousou no Frieren - 02 [1080p]",
+            "[Group] 药屋少女的呢喃 - 03 [720p]",
+        ];
+        let items = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| ResourceBaseData {
+                title: title.to_string(),
+                match_title: nfkc_to_lowercase(title),
+                ..resource(i as u8 + 1, i as i64 + 1)
+            })
+            .collect::<Vec<_>>();
+        client.insert_or_skip(items.clone()).await.unwrap();
+        // 重复插入不会产生重复索引
+        client.insert_or_skip(items).await.unwrap();
+
+        let find = |keywords: Option<Vec<&str>>, any_titles: Option<Vec<&str>>| {
+            let query = ResourceQuery {
+                keywords: keywords.map(|v| v.into_iter().map(String::from).collect()),
+                any_titles: any_titles.map(|v| v.into_iter().map(String::from).collect()),
+                start_at: None,
+                end_at: None,
+                limit: None,
+                offset: None,
+            };
+            let client = client.clone();
+            async move {
+                client
+                    .stream(&query)
+                    .map(|i| i.unwrap().data.info_hash[0])
+                    .collect::<Vec<_>>()
+                    .await
+            }
+        };
+
+        assert_eq!(find(Some(vec!["芙莉莲"]), None).await, vec![1]);
+        assert_eq!(find(Some(vec!["芙莉莲", "1080"]), None).await, vec![1]);
+        assert_eq!(
+            find(Some(vec!["芙莉莲", "720"]), None).await,
+            Vec::<u8>::new()
+        );
+        assert_eq!(find(Some(vec!["1080P"]), None).await, vec![2, 1]);
+        // 非连续的字不构成匹配
+        assert_eq!(find(Some(vec!["葬芙"]), None).await, Vec::<u8>::new());
+        assert_eq!(
+            find(None, Some(vec!["葬送的芙莉莲", "Sousou no Frieren"])).await,
+            vec![2, 1]
+        );
+        assert_eq!(
+            find(
+                Some(vec!["720p"]),
+                Some(vec!["葬送的芙莉莲", "药屋少女的呢喃"])
+            )
+            .await,
+            vec![3]
+        );
+        assert_eq!(find(Some(vec!["-"]), None).await, vec![3, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn title_filter_agrees_with_keyword_match() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = ResourceSqliteClient::new(pool);
+        client.init().await.unwrap();
+
+        let titles = [
+            "[Group] SousouNoFrieren - 01 [1080p]",
+            "[Group] Sousou-no-Frieren - 02",
+            "[Group] 葬送的芙莉莲 第二季 03",
+            "[Group] Re:Zero kara Hajimeru - 04",
+            "[Group] ＦＲＩＥＲＥＮ \"Special\" 05",
+        ];
+        let items = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| ResourceBaseData {
+                title: title.to_string(),
+                match_title: nfkc_to_lowercase(title),
+                ..resource(i as u8 + 1, i as i64 + 1)
+            })
+            .collect::<Vec<_>>();
+        client.insert_or_skip(items.clone()).await.unwrap();
+
+        // 订阅匹配的关键字判断：去除空白并小写化后的子串
+        for keyword in [
+            "Frieren",
+            "Sousou no Frieren",
+            "no-Frieren",
+            "rieren",
+            "葬送",
+            "芙莉莲 第二季",
+            "Re:Zero",
+            "ReZero",
+            "\"Special\"",
+            "05",
+        ] {
+            let expected = items
+                .iter()
+                .rev()
+                .filter(|item| item.match_title.contains(&nfkc_to_lowercase(keyword)))
+                .map(|item| item.info_hash[0])
+                .collect::<Vec<_>>();
+            let query = ResourceQuery {
+                keywords: None,
+                any_titles: Some(vec![keyword.to_string()]),
+                start_at: None,
+                end_at: None,
+                limit: None,
+                offset: None,
+            };
+            let found = client
+                .stream(&query)
+                .map(|i| i.unwrap().data.info_hash[0])
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(found, expected, "keyword {}", keyword);
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_records_feed_source() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = ResourceSqliteClient::new(pool.clone());
+        client.init().await.unwrap();
+
+        let seen = |feed_id: i64, url: &str| ResourceBaseData {
+            source: Some(ResourceFeedSource {
+                feed_id,
+                source_url: url.to_string(),
+            }),
+            ..resource(1, 100)
+        };
+        let inserted = client
+            .insert_or_skip_return_new(vec![seen(1, "https://mikan.example/1")])
+            .await
+            .unwrap();
+        assert_eq!(inserted.len(), 1);
+        sqlx::query("UPDATE resource_feed SET first_seen_at = 10, last_seen_at = 10")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // 同一资源在其他 feed 出现时仅追加来源，在原 feed 再次出现时刷新最近时间
+        let inserted = client
+            .insert_or_skip_return_new(vec![
+                seen(2, "https://nyaa.example/1"),
+                seen(1, "https://mikan.example/1?v=2"),
+            ])
+            .await
+            .unwrap();
+        assert!(inserted.is_empty());
+
+        let rows: Vec<(i64, String, i64, bool)> = sqlx::query_as(
+            "SELECT feed_id, source_url, first_seen_at, last_seen_at > 10 FROM resource_feed ORDER BY feed_id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            (rows[0].0, rows[0].1.as_str(), rows[0].2, rows[0].3),
+            (1, "https://mikan.example/1?v=2", 10, true)
+        );
+        assert_eq!(
+            (rows[1].0, rows[1].1.as_str()),
+            (2, "https://nyaa.example/1")
+        );
+
+        // 轮询中再次出现的已入库条目同样追加来源，删除 feed 时清理其来源
+        client
+            .touch_feed_sources(vec![(
+                [1; 20],
+                ResourceFeedSource {
+                    feed_id: 3,
+                    source_url: "https://dmhy.example/1".to_string(),
+                },
+            )])
+            .await
+            .unwrap();
+        client.delete_feed_sources(1).await.unwrap();
+        let feeds: Vec<i64> =
+            sqlx::query_scalar("SELECT feed_id FROM resource_feed ORDER BY feed_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(feeds, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn dedupe_hybrid_by_v2_hash() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = ResourceSqliteClient::new(pool);
+        client.init().await.unwrap();
+
+        let v2 = [7u8; 32];
+        let truncated: [u8; 20] = v2[..20].try_into().unwrap();
+        let with_hash =
+            |n: u8, info_hash: [u8; 20], info_hash_v2: Option<[u8; 32]>| ResourceBaseData {
+                info_hash,
+                info_hash_v2,
+                ..resource(n, n as i64)
+            };
+
+        // 先以 v1 磁力入库，再出现携带 v2 的混合种子：补充 v2 哈希而不新增资源
+        let inserted = client
+            .insert_or_skip_return_new(vec![with_hash(1, [1; 20], None)])
+            .await
+            .unwrap();
+        assert_eq!(inserted.len(), 1);
+        let inserted = client
+            .insert_or_skip_return_new(vec![with_hash(2, [1; 20], Some(v2))])
+            .await
+            .unwrap();
+        assert!(inserted.is_empty());
+
+        // 仅有 v2 哈希的来源复用混合种子的 v1 标识
+        let inserted = client
+            .insert_or_skip_return_new(vec![with_hash(3, truncated, Some(v2))])
+            .await
+            .unwrap();
+        assert!(inserted.is_empty());
+
+        // 同批次中纯 v2 与混合种子合并为一条
+        let other = [8u8; 32];
+        let other_truncated: [u8; 20] = other[..20].try_into().unwrap();
+        let inserted = client
+            .insert_or_skip_return_new(vec![
+                with_hash(4, other_truncated, Some(other)),
+                with_hash(5, [5; 20], Some(other)),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(inserted.len(), 1);
+        assert_eq!(inserted[0].data.info_hash, [5; 20]);
+
+        let query = ResourceQuery {
+            keywords: None,
+            any_titles: None,
+            start_at: None,
+            end_at: None,
+            limit: None,
+            offset: None,
+        };
+        let left = client
+            .stream(&query)
+            .map(|i| {
+                let data = i.unwrap().data;
+                (data.info_hash[0], data.info_hash_v2.map(|v| v[0]))
+            })
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(left, vec![(5, Some(8)), (1, Some(7))]);
+    }
+
+    #[tokio::test]
+    async fn torrent_saved_under_resolved_id() {
+        use std::sync::Arc;
+
+        use common::{infra::torrent_store::FsTorrentStore, shared::cap::TorrentStore};
+        use feed::entity::model::FeedItem;
+
+        use crate::entity::resources::Resources;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = Arc::new(ResourceSqliteClient::new(pool));
+        client.init().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(FsTorrentStore::new(dir.path()));
+        let resources = Resources::new(client.clone(), store.clone());
+
+        let v2 = [7u8; 32];
+        let truncated: [u8; 20] = v2[..20].try_into().unwrap();
+        client
+            .insert_or_skip(vec![ResourceBaseData {
+                info_hash_v2: Some(v2),
+                ..resource(1, 1)
+            }])
+            .await
+            .unwrap();
+
+        // 纯 v2 种子入库时复用混合种子的 v1 标识，种子文件也按该标识保存
+        let saved = resources
+            .save(
+                1,
+                vec![FeedItem {
+                    title: "[Group] Title - 01".to_string(),
+                    source_url: "https://example.com/view/1".to_string(),
+                    resource_url: "https://example.com/v2.torrent".to_string(),
+                    published_at: 2,
+                    info_hash: truncated,
+                    info_hash_v2: Some(v2),
+                    kind: ResourceKind::Episode,
+                    episode_range: None,
+                    files: vec![],
+                    size: None,
+                    torrent: Some(b"d4:infod4:name1:aee".to_vec()),
+                }],
+            )
+            .await
+            .unwrap();
+        assert!(saved.is_empty());
+        assert!(store.load([1; 20]).await.unwrap().is_some());
+        assert!(store.load(truncated).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_items_of_unchanged_feed() {
+        use std::sync::Arc;
+
+        use common::{infra::http::base_client_builder, infra::torrent_store::FsTorrentStore};
+        use feed::{
+            entity::{cap::FeedFetcher, model::FeedRequestOptions},
+            infra::{feed::HttpFeedFetcher, repository::client::FeedSqliteClient},
+        };
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::entity::resources::Resources;
+
+        const BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="1.0" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+  <channel>
+    <title>Nyaa</title>
+    <link>https://nyaa.si/</link>
+    <item>
+      <title>[Group] Title - 01 [1080p]</title>
+      <comments>https://nyaa.si/view/1</comments>
+      <pubDate>Wed, 08 Jan 2025 12:00:00 +0000</pubDate>
+      <link>http://127.0.0.1:9117/dl/nyaa/?file=1</link>
+      <torznab:attr name="seeders" value="12" />
+      <torznab:attr name="infohash" value="0123456789abcdef0123456789abcdef01234567" />
+    </item>
+  </channel>
+</rss>"#;
+
+        // 首次请求返回内容，之后携带 ETag 的请求均返回 304
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                let response = if request.contains("if-none-match") {
+                    "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        BODY.len(),
+                        BODY
+                    )
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        let url = format!(
+            "http://{}/api/v2.0/indexers/all/results/torznab/api?t=search",
+            addr
+        );
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let client = Arc::new(ResourceSqliteClient::new(pool.clone()));
+        client.init().await.unwrap();
+        let feed_client = FeedSqliteClient::new(pool.clone());
+        feed_client.init().await.unwrap();
+        let fetcher = HttpFeedFetcher::new(
+            base_client_builder().build().unwrap(),
+            client.clone(),
+            Arc::new(feed_client),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let resources = Resources::new(client.clone(), Arc::new(FsTorrentStore::new(dir.path())));
+        let options = FeedRequestOptions::default();
+
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert_eq!(data.items.len(), 1);
+        resources.just_save(1, data.items).await.unwrap();
+        fetcher.save_cache(data.cache.as_ref().unwrap()).await;
+        // 早先出现过、已从订阅源中消失的条目
+        client
+            .insert_or_skip(vec![ResourceBaseData {
+                source: Some(ResourceFeedSource {
+                    feed_id: 1,
+                    source_url: "https://nyaa.si/view/2".to_string(),
+                }),
+                ..resource(2, 1)
+            }])
+            .await
+            .unwrap();
+
+        // 订阅源持续返回 304 超过保留窗口，每次轮询仍刷新仍被列出的条目的出现时间
+        sqlx::query("UPDATE resource_feed SET last_seen_at = last_seen_at - 8 * 86400")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert!(data.items.is_empty());
+        resources.record_seen(1, data.seen).await.unwrap();
+
+        let report = client
+            .prune(
+                &ResourcePrunePolicy {
+                    published_before: Some(i64::MAX),
+                    max_rows: None,
+                    listed_window: Some(7 * 86400),
+                },
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.resources, 2);
+        assert_eq!(report.url_hashes, 1);
+        let known = client
+            .get_url_info_hash(vec!["https://example.com/2.torrent"])
+            .await
+            .unwrap();
+        assert!(known.is_empty());
+
+        // 订阅源内容变化后，仍被列出的条目不会作为新资源再次入库
+        sqlx::query("UPDATE feed_http_cache SET etag = NULL, body_hash = ''")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let data = fetcher.fetch_url(&url, None, &options).await.unwrap();
+        assert!(data.items.is_empty());
+        assert_eq!(data.seen.len(), 1);
+    }
+}
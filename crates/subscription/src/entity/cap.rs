@@ -5,10 +5,11 @@ use crate::entity::model::{
 use crate::entity::model::{MatchResult, Rule, RuleBaseData, RuleQuery};
 use anyhow::Result;
 use async_trait::async_trait;
+use resource::entity::resource_entity::ResourceEntity;
 
 #[async_trait]
 pub trait SpaceRuleMatcher: Send + Sync {
-    fn is_match(&self, text: &str) -> MatchResult;
+    fn is_match(&self, res: &ResourceEntity) -> MatchResult;
 }
 
 #[async_trait]
@@ -27,6 +28,8 @@ pub trait SubAnimeRepository: Send + Sync {
     async fn list_eps(&self, sub_anime_id: i64) -> Result<Vec<EpisodeProp>>;
     async fn find_epsiode(&self, ep_id: i64) -> Result<Option<EpisodeProp>>;
     async fn get_one_undownload_ep(&self) -> Result<Option<EpisodeProp>>;
+    // 获取所有剧集引用的资源标识
+    async fn list_referenced_resource_ids(&self) -> Result<Vec<[u8; 20]>>;
     async fn update_epsiode_status(&self, data: &EpisodeBaseData) -> Result<()>;
     async fn update_epsiodes_status(&self, data: &[EpisodeBaseData]) -> Result<()>;
     async fn update_sub_anime_progress(
@@ -40,7 +43,7 @@ pub trait SubAnimeRepository: Send + Sync {
 
 #[async_trait]
 pub trait RuleMatcher: Send + Sync {
-    fn is_match(&self, pattern: &str, text: &str) -> bool;
+    fn is_match(&self, pattern: &str, res: &ResourceEntity) -> bool;
     fn validate(&self, pattern: &str) -> Result<()>;
 }
 
@@ -38,10 +38,20 @@ impl EpsiodeEntity {
         self.data.ep.ep_num
     }
 
+    /// 合集资源覆盖的最后一集
+    pub fn ep_end(&self) -> Option<f64> {
+        self.data.ep.ep_end
+    }
+
     pub fn sub_anime_id(&self) -> i64 {
         self.data.ep.sub_anime_id
     }
 
+    /// 最早提供该资源的 feed
+    pub fn feed_id(&self) -> Option<i64> {
+        self.extend.feed_id
+    }
+
     pub fn space_id(&self) -> i64 {
         self.extend.space_id
     }
@@ -50,6 +60,11 @@ impl EpsiodeEntity {
         &self.data.ep.resource_id
     }
 
+    /// 被该修正版替换的旧资源
+    pub fn supersedes(&self) -> &[[u8; 20]] {
+        &self.data.ep.supersedes
+    }
+
     pub fn status(&self) -> EpsiodeStatus {
         self.data.ep.status.clone()
     }
@@ -76,8 +91,24 @@ impl EpsiodeEntity {
         Ok(res)
     }
 
+    /// 删除被该修正版替换的全部旧任务，单个任务删除失败不影响其余任务
+    pub async fn remove_superseded(&self, downloader: &dyn Downloader) -> Result<bool, Error> {
+        let mut removed = false;
+        let mut failed = None;
+        for old in &self.data.ep.supersedes {
+            match downloader.remove_superseded(*old).await {
+                Ok(v) => removed |= v,
+                Err(e) => failed = Some(e),
+            }
+        }
+        failed.map_or(Ok(removed), Err)
+    }
+
+    /// 重置下载状态，已被修正版替换的剧集保持不变
     pub fn reset_download(&mut self) {
-        self.data.ep.status = EpsiodeStatus::Pending;
+        if self.data.ep.status != EpsiodeStatus::Superseded {
+            self.data.ep.status = EpsiodeStatus::Pending;
+        }
     }
 }
 
@@ -94,3 +125,75 @@ impl EpsiodeEntity {
         PathBuf::from(anime_name).join(season_dir)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::entity::{
+        model::{
+            Episode, EpisodeNumbering, SubAnimeBaseData, SubAnimeExtendData, SubAnimeSearchStatus,
+        },
+        sub_anime_entity::SubAnimeEntity,
+    };
+
+    #[test]
+    fn numbering_maps_episode_within_season_dir() {
+        let sub_anime = |numbering| {
+            SubAnimeEntity::new(
+                SubAnimeBaseData {
+                    id: 1,
+                    anime_id: 1,
+                    space_id: 1,
+                    rule_id: None,
+                    search_status: SubAnimeSearchStatus::NotSearch,
+                    progress: 0,
+                    accept_collection: false,
+                    numbering,
+                    episode_offset: 12,
+                },
+                SubAnimeExtendData {
+                    eps: 12,
+                    rule_name: None,
+                    titles: vec![],
+                    air_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                },
+            )
+        };
+        let episode = |ep_num| {
+            EpsiodeEntity::new(
+                EpisodeBaseData {
+                    id: 1,
+                    ep: Episode {
+                        sub_anime_id: 1,
+                        resource_id: [1; 20],
+                        status: EpsiodeStatus::Pending,
+                        ep_num: Some(ep_num),
+                        ep_end: None,
+                        supersedes: vec![],
+                    },
+                },
+                EpisodeExtendData {
+                    title: "[Group] Title - 13 [1080p]".to_string(),
+                    url: String::new(),
+                    season: 2,
+                    anime_origin_title: "Title: Part 2".to_string(),
+                    space_id: 1,
+                    feed_id: None,
+                },
+            )
+        };
+
+        // 标题中的累计集数 13 按编号方式换算为本季集数，下载目录始终为番剧名下的季度目录
+        for (numbering, expected) in [
+            (EpisodeNumbering::Relative, 13.0),
+            (EpisodeNumbering::Absolute, 1.0),
+            (EpisodeNumbering::Auto, 1.0),
+        ] {
+            let ep = episode(sub_anime(numbering).to_season_episode(13.0));
+            assert_eq!(ep.ep_num(), Some(expected), "{:?}", numbering);
+            assert_eq!(ep.build_download_path(), PathBuf::from("Title Part 2/S02"));
+        }
+    }
+}
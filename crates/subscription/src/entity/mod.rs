@@ -2,6 +2,7 @@ pub mod cap;
 pub mod episode_entity;
 pub mod model;
 pub mod rule_entity;
+pub mod rule_matchers;
 pub mod rules;
 pub mod search_mandate_entity;
 pub mod search_mandates;
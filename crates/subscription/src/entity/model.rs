@@ -1,5 +1,6 @@
 use chrono::NaiveDate;
 use feed::entity::model::FeedItem;
+use resource::entity::model::SubtitleLang;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
@@ -45,6 +46,41 @@ impl TryFrom<i32> for SubAnimeSearchStatus {
     }
 }
 
+/// 资源标题中集数的编号方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EpisodeNumbering {
+    /// 按本季从 1 开始编号
+    #[default]
+    Relative,
+    /// 按全系列累计编号，减去偏移量得到本季集数
+    Absolute,
+    /// 集数超过本季总集数时视为累计编号
+    Auto,
+}
+
+impl From<EpisodeNumbering> for i32 {
+    fn from(numbering: EpisodeNumbering) -> Self {
+        match numbering {
+            EpisodeNumbering::Relative => 0,
+            EpisodeNumbering::Absolute => 1,
+            EpisodeNumbering::Auto => 2,
+        }
+    }
+}
+
+impl TryFrom<i32> for EpisodeNumbering {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EpisodeNumbering::Relative),
+            1 => Ok(EpisodeNumbering::Absolute),
+            2 => Ok(EpisodeNumbering::Auto),
+            _ => Err(format!("unknown episode numbering type: {}", value)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SubAnimeStatus {
     Enable,
@@ -81,6 +117,11 @@ pub struct SubAnimeBaseData {
 
     pub search_status: SubAnimeSearchStatus,
     pub progress: u32,
+    /// 是否接受合集资源补全缺失剧集
+    pub accept_collection: bool,
+    pub numbering: EpisodeNumbering,
+    /// 本季之前的累计集数，按累计编号时从资源集数中减去
+    pub episode_offset: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -105,10 +146,70 @@ pub struct Rule {
     pub name: String,
     /// 规则顺序，值越小优先级越高。
     pub order: i64,
-    /// 可用于匹配资源标题的表达式。
+    /// 规则类型，决定 `pattern` 的解释方式
+    pub kind: RuleKind,
+    /// 正则规则为匹配资源标题的表达式，结构化规则为 `StructuredRule` 的 JSON
     pub pattern: String,
 }
 
+/// 规则类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RuleKind {
+    /// 正则表达式匹配资源标题
+    #[default]
+    Regex,
+    /// 按关键字、发布组、分辨率、字幕语言及大小等条件匹配
+    Structured,
+}
+
+impl From<RuleKind> for i32 {
+    fn from(kind: RuleKind) -> Self {
+        match kind {
+            RuleKind::Regex => 0,
+            RuleKind::Structured => 1,
+        }
+    }
+}
+
+impl TryFrom<i32> for RuleKind {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RuleKind::Regex),
+            1 => Ok(RuleKind::Structured),
+            _ => Err(format!("unknown rule kind: {}", value)),
+        }
+    }
+}
+
+/// 创建规则时的匹配定义
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleDefinition {
+    Regex(String),
+    Structured(StructuredRule),
+}
+
+/// 结构化规则，所有非空条件同时满足时命中
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StructuredRule {
+    /// 标题须包含全部关键字，不区分大小写及全半角
+    pub include: Vec<String>,
+    /// 标题包含任一关键字时不命中
+    pub exclude: Vec<String>,
+    /// 发布组，须为其中之一
+    pub groups: Vec<String>,
+    /// 纵向分辨率，如 1080，须为其中之一
+    pub resolutions: Vec<u32>,
+    /// 字幕语言，须包含其中之一
+    pub subtitle_langs: Vec<SubtitleLang>,
+    /// 资源大小下限（字节），大小未知的资源不受限制
+    pub min_size: Option<u64>,
+    /// 资源大小上限（字节），大小未知的资源不受限制
+    pub max_size: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct RuleQuery {
     pub space_id: Option<i64>,
@@ -136,10 +237,65 @@ pub struct MatchResult {
     pub rule_order: i64,
 }
 
+/// 订阅匹配资源的单个检查步骤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchStep {
+    /// 合集资源是否被订阅接受
+    Collection { accepted: bool },
+    /// 发布时间是否处于匹配时间窗口，合集不限制结束时间
+    TimeWindow {
+        published_at: i64,
+        start: i64,
+        end: Option<i64>,
+        passed: bool,
+    },
+    /// 规则的匹配结果，未绑定规则时为空间内首个命中的规则
+    Rule(MatchResult),
+    /// 命中的订阅关键字
+    Keyword { matched: Option<String> },
+    /// 与当前候选规则比较，`replaced` 表示新规则优先级更高并替换了候选规则
+    Candidate {
+        rule_id: Option<i64>,
+        rule_order: Option<i64>,
+        replaced: bool,
+    },
+}
+
+/// 订阅匹配资源的最终结论
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchDecision {
+    Matched,
+    /// 资源已在本次匹配中被选中
+    AlreadyMatched,
+    /// 订阅未开启合集下载
+    CollectionRejected,
+    OutOfTimeRange,
+    RuleNotMatched,
+    KeywordNotMatched,
+    /// 命中的规则与订阅绑定的规则不一致
+    BoundRuleConflict {
+        bound_rule_id: i64,
+        rule_id: i64,
+    },
+    /// 已有优先级更高或同级的候选规则，资源被忽略
+    CandidateKept {
+        candidate_rule_id: i64,
+    },
+}
+
+/// 订阅匹配资源的决策过程，`steps` 按检查顺序记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchTrace {
+    pub steps: Vec<MatchStep>,
+    pub decision: MatchDecision,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EpsiodeStatus {
     Pending,
     Downloaded,
+    /// 已被同一发布组的修正版替换，不再下载
+    Superseded,
 }
 
 impl From<EpsiodeStatus> for i32 {
@@ -147,6 +303,7 @@ impl From<EpsiodeStatus> for i32 {
         match status {
             EpsiodeStatus::Pending => 0,
             EpsiodeStatus::Downloaded => 1,
+            EpsiodeStatus::Superseded => 2,
         }
     }
 }
@@ -158,6 +315,7 @@ impl TryFrom<i32> for EpsiodeStatus {
         match value {
             0 => Ok(EpsiodeStatus::Pending),
             1 => Ok(EpsiodeStatus::Downloaded),
+            2 => Ok(EpsiodeStatus::Superseded),
             _ => Err(format!("unknown sub anime episode status type: {}", value)),
         }
     }
@@ -169,6 +327,10 @@ pub struct Episode {
     pub resource_id: [u8; 20],
     pub status: EpsiodeStatus,
     pub ep_num: Option<f64>,
+    /// 合集资源覆盖的最后一集，单集资源为空
+    pub ep_end: Option<f64>,
+    /// 被该修正版替换的旧资源
+    pub supersedes: Vec<[u8; 20]>,
 }
 
 #[derive(Debug, Clone)]
@@ -184,6 +346,8 @@ pub struct EpisodeExtendData {
     pub season: u32,
     pub anime_origin_title: String,
     pub space_id: i64,
+    /// 最早提供该资源的 feed，资源来源未记录时为空
+    pub feed_id: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -198,6 +362,8 @@ pub struct MatchedEpisode {
     pub resource_id: [u8; 20],
     pub status: EpsiodeStatus,
     pub title: String,
+    /// 合集资源覆盖的集数区间，单集资源为空
+    pub episode_range: Option<(u32, u32)>,
 }
 
 #[derive(Debug, Clone)]
@@ -216,6 +382,10 @@ pub struct Mandate {
     pub anime_id: i64,
     pub feed_id: i64,
     pub url: String,
+    pub parser: Option<String>,
+    pub min_spacing: Option<i64>,
+    /// 加密后的 feed 请求选项
+    pub request_options: Option<String>,
 }
 
 #[derive(Debug, Clone)]
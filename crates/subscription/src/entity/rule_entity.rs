@@ -1,19 +1,20 @@
 use async_trait::async_trait;
+use resource::entity::resource_entity::ResourceEntity;
 
 use crate::entity::{
-    cap::{RuleMatcher, SpaceRuleMatcher},
-    model::{MatchResult, RuleBaseData},
+    cap::SpaceRuleMatcher,
+    model::{MatchResult, RuleBaseData, RuleKind, StructuredRule},
+    rule_matchers::RuleMatchers,
 };
-use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct RuleEntity {
     data: RuleBaseData,
-    matcher: Arc<dyn RuleMatcher>,
+    matcher: RuleMatchers,
 }
 
 impl RuleEntity {
-    pub(super) fn new(rule: RuleBaseData, matcher: Arc<dyn RuleMatcher>) -> Self {
+    pub(super) fn new(rule: RuleBaseData, matcher: RuleMatchers) -> Self {
         Self {
             data: rule,
             matcher,
@@ -36,16 +37,28 @@ impl RuleEntity {
         self.data.metadata.order
     }
 
+    pub fn kind(&self) -> RuleKind {
+        self.data.metadata.kind
+    }
+
     pub fn pattern(&self) -> &str {
         &self.data.metadata.pattern
     }
 
+    /// 结构化规则的条件，正则规则返回 None
+    pub fn conditions(&self) -> Option<StructuredRule> {
+        match self.kind() {
+            RuleKind::Regex => None,
+            RuleKind::Structured => serde_json::from_str(self.pattern()).ok(),
+        }
+    }
+
     pub fn active(&self) -> bool {
         self.data.active
     }
 
-    pub fn is_match(&self, text: &str) -> bool {
-        self.matcher.is_match(&self.data.metadata.pattern, text)
+    pub fn is_match(&self, res: &ResourceEntity) -> bool {
+        self.matcher.is_match(&self.data.metadata, res)
     }
 
     pub fn set_order(&mut self, order: i64) {
@@ -61,9 +74,9 @@ impl RuleEntity {
 
 #[async_trait]
 impl SpaceRuleMatcher for RuleEntity {
-    fn is_match(&self, text: &str) -> MatchResult {
+    fn is_match(&self, res: &ResourceEntity) -> MatchResult {
         MatchResult {
-            matched: self.is_match(text),
+            matched: self.is_match(res),
             rule_id: self.id(),
             rule_order: self.order(),
         }
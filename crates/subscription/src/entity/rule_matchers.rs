@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use resource::entity::resource_entity::ResourceEntity;
+
+use crate::entity::{
+    cap::RuleMatcher,
+    model::{Rule, RuleKind},
+};
+
+/// 按规则类型选择对应的匹配器
+#[derive(Clone)]
+pub struct RuleMatchers {
+    regex: Arc<dyn RuleMatcher>,
+    structured: Arc<dyn RuleMatcher>,
+}
+
+impl RuleMatchers {
+    pub fn new(regex: Arc<dyn RuleMatcher>, structured: Arc<dyn RuleMatcher>) -> Self {
+        Self { regex, structured }
+    }
+
+    fn get(&self, kind: RuleKind) -> &dyn RuleMatcher {
+        match kind {
+            RuleKind::Regex => self.regex.as_ref(),
+            RuleKind::Structured => self.structured.as_ref(),
+        }
+    }
+
+    pub(super) fn is_match(&self, rule: &Rule, res: &ResourceEntity) -> bool {
+        self.get(rule.kind).is_match(&rule.pattern, res)
+    }
+
+    pub(super) fn validate(&self, rule: &Rule) -> Result<()> {
+        self.get(rule.kind).validate(&rule.pattern)
+    }
+}
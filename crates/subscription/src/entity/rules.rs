@@ -3,19 +3,20 @@ use std::sync::Arc;
 use common::shared::error::Error;
 
 use crate::entity::{
-    cap::{RuleMatcher, RuleRepository},
-    model::{Rule, RuleQuery},
+    cap::RuleRepository,
+    model::{Rule, RuleBaseData, RuleDefinition, RuleKind, RuleQuery},
     rule_entity::RuleEntity,
+    rule_matchers::RuleMatchers,
 };
 
 #[derive(Clone)]
 pub struct Rules {
     repo: Arc<dyn RuleRepository>,
-    matcher: Arc<dyn RuleMatcher>,
+    matcher: RuleMatchers,
 }
 
 impl Rules {
-    pub fn new(repo: Arc<dyn RuleRepository>, matcher: Arc<dyn RuleMatcher>) -> Self {
+    pub fn new(repo: Arc<dyn RuleRepository>, matcher: RuleMatchers) -> Self {
         Self { repo, matcher }
     }
 
@@ -23,29 +24,67 @@ impl Rules {
         &self,
         name: &str,
         space_id: i64,
-        pattern: &str,
+        definition: RuleDefinition,
         order: i64,
     ) -> Result<RuleEntity, Error> {
+        let rule = self.build(name, space_id, definition, order)?;
+        let data = self
+            .repo
+            .insert(&rule)
+            .await
+            .map_err(|e| Error::external("rules create rule failed", e))?;
+        let entity = RuleEntity::new(data, self.matcher.clone());
+        Ok(entity)
+    }
+
+    /// 构造未保存的规则用于试运行，规则 ID 为 0
+    pub fn preview(
+        &self,
+        name: &str,
+        space_id: i64,
+        definition: RuleDefinition,
+        order: i64,
+    ) -> Result<RuleEntity, Error> {
+        let rule = self.build(name, space_id, definition, order)?;
+        Ok(RuleEntity::new(
+            RuleBaseData {
+                id: 0,
+                active: true,
+                metadata: rule,
+            },
+            self.matcher.clone(),
+        ))
+    }
+
+    fn build(
+        &self,
+        name: &str,
+        space_id: i64,
+        definition: RuleDefinition,
+        order: i64,
+    ) -> Result<Rule, Error> {
+        let (kind, pattern) = match definition {
+            RuleDefinition::Regex(pattern) => (RuleKind::Regex, pattern),
+            RuleDefinition::Structured(rule) => (
+                RuleKind::Structured,
+                serde_json::to_string(&rule)
+                    .map_err(|e| Error::external("serialize structured rule failed", e))?,
+            ),
+        };
         let rule = Rule {
             space_id,
             name: name.to_string(),
             order,
-            pattern: pattern.to_string(),
+            kind,
+            pattern,
         };
-        self.matcher.validate(&rule.pattern).map_err(|e| {
+        self.matcher.validate(&rule).map_err(|e| {
             Error::external(
-                format!("vaildate pattern failed, pattern {}", &rule.pattern),
+                format!("vaildate pattern failed, pattern {}", rule.pattern),
                 e,
             )
         })?;
-
-        let data = self
-            .repo
-            .insert(&rule)
-            .await
-            .map_err(|e| Error::external("rules create rule failed", e))?;
-        let entity = RuleEntity::new(data, self.matcher.clone());
-        Ok(entity)
+        Ok(rule)
     }
 
     pub async fn list(&self, query: &RuleQuery) -> Result<Vec<RuleEntity>, Error> {
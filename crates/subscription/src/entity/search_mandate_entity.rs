@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
-use common::shared::error::Error;
+use chrono::Utc;
+
+use common::shared::{cap::CryptoProvider, error::Error};
 use feed::entity::{
     cap::{FeedAccessPolicy, FeedFetcher},
-    model::{FeedFetchError, FeedFetchResult},
+    model::{FeedCacheMark, FeedFetchError, FeedFetchResult, FeedRequestKind, FeedRequestOptions},
 };
 
 use crate::entity::model::SearchMandateBaseData;
@@ -13,6 +15,7 @@ pub struct SearchMandateEntity {
     data: SearchMandateBaseData,
     fetch_cap: Arc<dyn FeedFetcher>,
     access_policy: Arc<dyn FeedAccessPolicy>,
+    crypto_provider: Arc<dyn CryptoProvider>,
     completed: bool,
 }
 
@@ -21,11 +24,13 @@ impl SearchMandateEntity {
         data: SearchMandateBaseData,
         fetch_cap: Arc<dyn FeedFetcher>,
         access_policy: Arc<dyn FeedAccessPolicy>,
+        crypto_provider: Arc<dyn CryptoProvider>,
     ) -> Self {
         Self {
             data,
             fetch_cap,
             access_policy,
+            crypto_provider,
             completed: false,
         }
     }
@@ -40,6 +45,10 @@ impl SearchMandateEntity {
         self.completed
     }
 
+    pub fn feed_id(&self) -> i64 {
+        self.data.mandata.feed_id
+    }
+
     pub fn anime_id(&self) -> i64 {
         self.data.mandata.anime_id
     }
@@ -47,15 +56,33 @@ impl SearchMandateEntity {
 
 impl SearchMandateEntity {
     pub async fn fetch(&mut self) -> Result<FeedFetchResult, Error> {
-        if !self.access_policy.is_access(self.data.mandata.feed_id) {
+        let feed_id = self.data.mandata.feed_id;
+        if !self.access_policy.is_access(feed_id)
+            || !self.access_policy.is_spaced(
+                feed_id,
+                self.data.mandata.min_spacing,
+                Utc::now().timestamp(),
+            )
+        {
             return Ok(FeedFetchResult::Denied);
         }
 
-        let data = self.fetch_cap.fetch_url(&self.data.mandata.url).await;
+        let request = FeedRequestOptions::decrypt(
+            self.data.mandata.request_options.as_deref(),
+            self.crypto_provider.as_ref(),
+        )?;
+        let data = self
+            .fetch_cap
+            .fetch_url(
+                &self.data.mandata.url,
+                self.data.mandata.parser.as_deref(),
+                &request,
+            )
+            .await;
         let res = match data {
             Ok(data) => {
                 self.completed = true;
-                Ok(FeedFetchResult::Success(data.items))
+                Ok(FeedFetchResult::Success(data))
             }
             Err(FeedFetchError::Inaccessible(v)) => {
                 self.completed = true;
@@ -66,15 +93,24 @@ impl SearchMandateEntity {
                 format!(
                     "search mandate {} fetch {} failed",
                     self.id(),
-                    &self.data.mandata.url
+                    self.data.mandata.url
                 ),
                 e,
             )),
         };
         if let Ok(res) = &res {
-            self.access_policy.note(self.data.mandata.feed_id, res);
+            self.access_policy
+                .note(self.data.mandata.feed_id, FeedRequestKind::Search, res)
+                .await;
         }
 
         res
     }
+
+    /// 搜索结果保存成功后调用，写入本次响应的缓存校验信息
+    pub async fn commit(&self, cache: Option<&FeedCacheMark>) {
+        if let Some(mark) = cache {
+            self.fetch_cap.save_cache(mark).await;
+        }
+    }
 }
@@ -1,17 +1,21 @@
 use std::sync::Arc;
 
-use common::shared::{error::Error, model::SearchUrls};
+use common::shared::{cap::CryptoProvider, error::Error, model::SearchUrls};
 use feed::entity::cap::{FeedAccessPolicy, FeedFetcher};
 
 use crate::entity::{
     cap::SearchMandateRepository, model::Mandate, search_mandate_entity::SearchMandateEntity,
 };
 
+/// 单部番剧一次搜索最多创建的委托数量
+const MAX_MANDATES_PER_ANIME: usize = 12;
+
 #[derive(Clone)]
 pub struct SearchMandates {
     repo: Arc<dyn SearchMandateRepository>,
     fetch_cap: Arc<dyn FeedFetcher>,
     access_policy: Arc<dyn FeedAccessPolicy>,
+    crypto_provider: Arc<dyn CryptoProvider>,
 }
 
 impl SearchMandates {
@@ -19,11 +23,13 @@ impl SearchMandates {
         repo: Arc<dyn SearchMandateRepository>,
         fetch_cap: Arc<dyn FeedFetcher>,
         access_policy: Arc<dyn FeedAccessPolicy>,
+        crypto_provider: Arc<dyn CryptoProvider>,
     ) -> Self {
         Self {
             repo,
             fetch_cap,
             access_policy,
+            crypto_provider,
         }
     }
 }
@@ -43,6 +49,7 @@ impl SearchMandates {
                 prop.data,
                 self.fetch_cap.clone(),
                 self.access_policy.clone(),
+                self.crypto_provider.clone(),
             )))
         } else {
             Ok(None)
@@ -66,23 +73,45 @@ impl SearchMandates {
         Ok(count == 0)
     }
 
+    // create_from_search_urls
+    // 由各 feed 的搜索地址创建委托，地址按关键字优先级排列，
+    // 各 feed 按优先级轮流取地址，总数不超过 MAX_MANDATES_PER_ANIME
     pub async fn create_from_search_urls(
         &self,
         anime_id: i64,
         urls: Vec<SearchUrls>,
     ) -> Result<Vec<SearchMandateEntity>, Error> {
         let mut mandates = vec![];
-        for i in urls {
-            let list = i
-                .urls
-                .into_iter()
-                .map(|url| Mandate {
+        let mut feeds = urls
+            .into_iter()
+            .map(|i| {
+                let SearchUrls {
+                    feed_id,
+                    parser,
+                    min_spacing,
+                    request_options,
+                    urls,
+                } = i;
+                urls.into_iter().map(move |url| Mandate {
                     anime_id,
-                    feed_id: i.feed_id,
+                    feed_id,
                     url,
+                    parser: parser.clone(),
+                    min_spacing,
+                    request_options: request_options.clone(),
                 })
+            })
+            .collect::<Vec<_>>();
+        while mandates.len() < MAX_MANDATES_PER_ANIME {
+            let round = feeds
+                .iter_mut()
+                .filter_map(Iterator::next)
                 .collect::<Vec<_>>();
-            mandates.extend(list);
+            if round.is_empty() {
+                break;
+            }
+            let rest = MAX_MANDATES_PER_ANIME - mandates.len();
+            mandates.extend(round.into_iter().take(rest));
         }
         self.create(&mandates).await
     }
@@ -102,6 +131,7 @@ impl SearchMandates {
                     prop.data,
                     self.fetch_cap.clone(),
                     self.access_policy.clone(),
+                    self.crypto_provider.clone(),
                 )
             })
             .collect())
@@ -1,20 +1,20 @@
-use std::sync::Arc;
-
 use async_trait::async_trait;
+use resource::entity::resource_entity::ResourceEntity;
 
 use crate::entity::{
-    cap::{RuleMatcher, SpaceRuleMatcher},
+    cap::SpaceRuleMatcher,
     model::{MatchResult, RuleBaseData},
+    rule_matchers::RuleMatchers,
 };
 
 #[derive(Clone)]
 pub struct SpaceRules {
     data: Vec<RuleBaseData>,
-    matcher: Arc<dyn RuleMatcher>,
+    matcher: RuleMatchers,
 }
 
 impl SpaceRules {
-    pub(super) fn new(mut data: Vec<RuleBaseData>, matcher: Arc<dyn RuleMatcher>) -> Self {
+    pub(super) fn new(mut data: Vec<RuleBaseData>, matcher: RuleMatchers) -> Self {
         data.sort_by_key(|x| x.metadata.order);
         Self { data, matcher }
     }
@@ -22,9 +22,9 @@ impl SpaceRules {
 
 #[async_trait]
 impl SpaceRuleMatcher for SpaceRules {
-    fn is_match(&self, text: &str) -> MatchResult {
+    fn is_match(&self, res: &ResourceEntity) -> MatchResult {
         for i in &self.data {
-            if self.matcher.is_match(&i.metadata.pattern, text) {
+            if self.matcher.is_match(&i.metadata, res) {
                 return MatchResult {
                     matched: true,
                     rule_id: i.id,
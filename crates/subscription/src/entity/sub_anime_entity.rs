@@ -1,11 +1,20 @@
-use chrono::{Duration, Months, NaiveDateTime};
-use common::shared::{cap::FeedSearchUrlProvider, error::Error, model::SearchUrls};
-
-use crate::entity::model::{
-    ClaimResult::{self},
-    Episode, SubAnimeBaseData, SubAnimeExtendData,
-    SubAnimeSearchStatus::{self},
-    SubAnimeStatus,
+use chrono::{Datelike, Duration, Months, NaiveDateTime};
+use common::shared::{
+    cap::FeedSearchUrlProvider,
+    error::Error,
+    model::{SearchQuery, SearchUrls},
+};
+
+use resource::entity::{release_parser::parse_release, resource_entity::ResourceEntity};
+
+use crate::entity::{
+    model::{
+        ClaimResult::{self},
+        Episode, EpisodeNumbering, EpsiodeStatus, SubAnimeBaseData, SubAnimeExtendData,
+        SubAnimeSearchStatus::{self},
+        SubAnimeStatus,
+    },
+    sub_anime_matcher::find_keyword,
 };
 
 pub trait SubAnimeEntityMatcher {
@@ -29,7 +38,7 @@ impl SubAnimeEntity {
         &self.data
     }
 
-    pub(super) fn get_rule_id(&self) -> Option<i64> {
+    pub fn get_rule_id(&self) -> Option<i64> {
         self.data.rule_id
     }
 
@@ -49,10 +58,7 @@ impl SubAnimeEntity {
     }
 
     pub(super) fn update_progress(&mut self, eps: &[Episode]) {
-        let mut eps_numbers = eps.iter().filter_map(|i| i.ep_num).collect::<Vec<_>>();
-        eps_numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        eps_numbers.dedup();
-        self.data.progress = eps_numbers.len() as u32;
+        self.data.progress = covered_episode_numbers(eps).len() as u32;
     }
 
     pub fn space_id(&self) -> i64 {
@@ -67,6 +73,16 @@ impl SubAnimeEntity {
             .collect()
     }
 
+    /// 资源标题是否包含番剧的任一标题，与订阅匹配时的关键字判断一致
+    pub fn is_keyword_match(&self, res: &ResourceEntity) -> bool {
+        find_keyword(&self.keywords(), res).is_some()
+    }
+
+    /// 番剧标题及别名
+    pub fn titles(&self) -> &[String] {
+        &self.extend.titles
+    }
+
     pub(super) fn eps_number(&self) -> u32 {
         self.extend.eps
     }
@@ -109,6 +125,69 @@ impl SubAnimeEntity {
         self.data.search_status
     }
 
+    pub fn accept_collection(&self) -> bool {
+        self.data.accept_collection
+    }
+
+    pub fn set_accept_collection(&mut self, accept: bool) -> bool {
+        if self.data.accept_collection == accept {
+            return false;
+        }
+        self.data.accept_collection = accept;
+        true
+    }
+
+    pub fn numbering(&self) -> EpisodeNumbering {
+        self.data.numbering
+    }
+
+    pub fn episode_offset(&self) -> u32 {
+        self.data.episode_offset
+    }
+
+    pub fn set_numbering(&mut self, numbering: EpisodeNumbering, episode_offset: u32) -> bool {
+        if self.data.numbering == numbering && self.data.episode_offset == episode_offset {
+            return false;
+        }
+        self.data.numbering = numbering;
+        self.data.episode_offset = episode_offset;
+        true
+    }
+
+    /// 将资源标题中的集数换算为本季集数，不大于偏移量的集数视为已按本季编号
+    pub(super) fn to_season_episode(&self, num: f64) -> f64 {
+        let offset = self.data.episode_offset as f64;
+        let absolute = match self.data.numbering {
+            EpisodeNumbering::Relative => false,
+            EpisodeNumbering::Absolute => true,
+            EpisodeNumbering::Auto => self.extend.eps > 0 && num > self.extend.eps as f64,
+        };
+        if absolute && num > offset {
+            num - offset
+        } else {
+            num
+        }
+    }
+
+    /// 将合集的集数区间整体换算为本季集数，区间起始不大于偏移量时视为已按本季编号，
+    /// 起始集数大于结束集数的区间无效
+    pub(super) fn to_season_range(&self, start: u32, end: u32) -> Option<(u32, u32)> {
+        if start > end {
+            return None;
+        }
+        let offset = self.data.episode_offset;
+        let absolute = match self.data.numbering {
+            EpisodeNumbering::Relative => false,
+            EpisodeNumbering::Absolute => true,
+            EpisodeNumbering::Auto => self.extend.eps > 0 && end > self.extend.eps,
+        };
+        if absolute && start > offset {
+            Some((start - offset, end - offset))
+        } else {
+            Some((start, end))
+        }
+    }
+
     pub fn enable_search(&mut self) -> bool {
         if self.data.search_status == SubAnimeSearchStatus::NotSearch {
             self.data.search_status = SubAnimeSearchStatus::Pending;
@@ -127,24 +206,33 @@ impl SubAnimeEntity {
         }
     }
 
+    /// 按优先级排列的搜索关键字
     pub(super) fn search_keywords(&self) -> Vec<String> {
-        let mut keywords: Vec<String> = self
-            .extend
-            .titles
-            .iter()
-            .flat_map(|i| common::shared::str::to_search_keywords(i))
-            .collect();
-        keywords.sort();
-        keywords.dedup();
-        keywords
+        common::shared::str::to_search_queries(&self.extend.titles)
     }
 
     pub fn get_search_urls(
         &self,
         search_url_provider: &dyn FeedSearchUrlProvider,
     ) -> Vec<SearchUrls> {
-        let keywords = self.search_keywords();
-        search_url_provider.made_search_url(&keywords)
+        let season = self
+            .extend
+            .titles
+            .iter()
+            .find_map(|i| parse_release(i).season);
+        // 下一集为已覆盖的集数之后的一集，已完结时不指定
+        let episode = Some(self.data.progress + 1).filter(|ep| *ep <= self.extend.eps);
+        let queries = self
+            .search_keywords()
+            .into_iter()
+            .map(|keyword| SearchQuery {
+                keyword,
+                season,
+                episode,
+                year: Some(self.extend.air_date.year()),
+            })
+            .collect::<Vec<_>>();
+        search_url_provider.made_search_url(&queries)
     }
 }
 
@@ -213,3 +301,80 @@ impl SubAnimeEntityMatcher for SubAnimeEntity {
         start..end
     }
 }
+
+/// 计算剧集覆盖的集数（去重排序），合集资源按区间展开
+pub(super) fn covered_episode_numbers(eps: &[Episode]) -> Vec<f64> {
+    let mut eps_numbers = vec![];
+    for ep in eps {
+        if ep.status == EpsiodeStatus::Superseded {
+            continue;
+        }
+        let Some(start) = ep.ep_num else {
+            continue;
+        };
+        match ep.ep_end {
+            Some(end) => {
+                let mut num = start;
+                while num <= end {
+                    eps_numbers.push(num);
+                    num += 1.0;
+                }
+            }
+            None => eps_numbers.push(start),
+        }
+    }
+    eps_numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    eps_numbers.dedup();
+    eps_numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn map_to_season_episode() {
+        let entity = |numbering, episode_offset| {
+            SubAnimeEntity::new(
+                SubAnimeBaseData {
+                    id: 1,
+                    anime_id: 1,
+                    space_id: 1,
+                    rule_id: None,
+                    search_status: SubAnimeSearchStatus::NotSearch,
+                    progress: 0,
+                    accept_collection: false,
+                    numbering,
+                    episode_offset,
+                },
+                SubAnimeExtendData {
+                    eps: 12,
+                    rule_name: None,
+                    titles: vec![],
+                    air_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                },
+            )
+        };
+        let relative = entity(EpisodeNumbering::Relative, 12);
+        assert_eq!(relative.to_season_episode(13.0), 13.0);
+
+        let absolute = entity(EpisodeNumbering::Absolute, 12);
+        assert_eq!(absolute.to_season_episode(13.0), 1.0);
+        assert_eq!(absolute.to_season_episode(24.0), 12.0);
+        assert_eq!(absolute.to_season_episode(3.0), 3.0);
+
+        let auto = entity(EpisodeNumbering::Auto, 12);
+        assert_eq!(auto.to_season_episode(12.0), 12.0);
+        assert_eq!(auto.to_season_episode(18.5), 6.5);
+
+        // 区间整体换算，不会把跨越偏移量的区间拆成起止颠倒的区间
+        assert_eq!(auto.to_season_range(13, 24), Some((1, 12)));
+        assert_eq!(auto.to_season_range(10, 14), Some((10, 14)));
+        assert_eq!(auto.to_season_range(1, 12), Some((1, 12)));
+        assert_eq!(absolute.to_season_range(13, 24), Some((1, 12)));
+        assert_eq!(relative.to_season_range(13, 24), Some((13, 24)));
+        assert_eq!(auto.to_season_range(12, 1), None);
+    }
+}
@@ -1,12 +1,20 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use common::shared::error::Error;
+use common::shared::{
+    episode::{EpisodeNumber, parse_episode_number},
+    error::Error,
+    str::nfkc_to_lowercase,
+};
+use resource::entity::{model::SubtitleLang, release_parser::parse_release};
 
 use crate::entity::{
     cap::SubAnimeRepository,
     episode_entity::EpsiodeEntity,
-    model::{Episode, MatchedEpisode},
-    sub_anime_entity::SubAnimeEntity,
+    model::{Episode, EpsiodeStatus, MatchedEpisode},
+    sub_anime_entity::{SubAnimeEntity, covered_episode_numbers},
 };
 
 #[derive(Clone)]
@@ -43,16 +51,33 @@ impl SubAnimeEpsiodes {
         rule_id: i64,
         eps: Vec<MatchedEpisode>,
     ) -> Result<(), Error> {
+        let mut entity = self.load_sub_anime().await?;
+        // 尝试绑定规则
+        entity.auto_bind_rule(rule_id)?;
+        self.update_eps(entity, eps).await
+    }
+
+    /// 按订阅当前的编号方式重新计算已有剧集的集数及进度
+    pub async fn renumber(&self) -> Result<(), Error> {
+        let entity = self.load_sub_anime().await?;
+        self.update_eps(entity, vec![]).await
+    }
+
+    async fn load_sub_anime(&self) -> Result<SubAnimeEntity, Error> {
         let prop = self
             .repo
             .find_sub_anime(self.sub_anime_id)
             .await
             .map_err(|e| Error::external("sub anime eps load entity failed", e))?
             .ok_or_else(|| Error::not_found("sub anime not found"))?;
-        let mut entity = SubAnimeEntity::new(prop.data, prop.extend);
-        // 尝试绑定规则
-        entity.auto_bind_rule(rule_id)?;
+        Ok(SubAnimeEntity::new(prop.data, prop.extend))
+    }
 
+    async fn update_eps(
+        &self,
+        mut entity: SubAnimeEntity,
+        eps: Vec<MatchedEpisode>,
+    ) -> Result<(), Error> {
         // 计算剧集编号
         let entity_eps = self.list().await?;
         let mut entity_eps_matched = entity_eps
@@ -62,34 +87,119 @@ impl SubAnimeEpsiodes {
                 resource_id: *i.resource_id(),
                 title: i.title().into(),
                 status: i.status(),
+                episode_range: i
+                    .ep_num()
+                    .zip(i.ep_end())
+                    .map(|(start, end)| (start as u32, end as u32)),
             })
             .collect::<Vec<_>>();
+        let mut new_collections = vec![];
         for i in eps {
-            if !entity_eps_matched
+            if entity_eps_matched
                 .iter()
                 .any(|item| item.resource_id == i.resource_id)
             {
-                tracing::info!(
-                    "sub anime matcher matched resource, sub_anime_id: {}, resource title: {}",
-                    i.sub_anime_id,
-                    i.title
-                );
-                entity_eps_matched.push(i);
+                continue;
             }
+            if i.episode_range.is_some() {
+                new_collections.push(i);
+                continue;
+            }
+            tracing::info!(
+                "sub anime matcher matched resource, sub_anime_id: {}, resource title: {}",
+                i.sub_anime_id,
+                i.title
+            );
+            entity_eps_matched.push(i);
         }
-        let eps_map = extract_episode_number(&entity_eps_matched);
-        let new_eps = entity_eps_matched
+        // 合集资源的集数直接取区间，不参与标题数字列推断
+        let singles = entity_eps_matched
+            .iter()
+            .filter(|i| i.episode_range.is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        let eps_map = extract_episode_number(&singles);
+        // 同一发布组的修正版替换旧版本，旧版本沿用修正版的集数且不再下载
+        let revisions = find_revisions(&singles);
+        let stored = entity_eps
+            .iter()
+            .map(|i| *i.resource_id())
+            .collect::<HashSet<_>>();
+        // 合集区间优先按标题重新识别，识别不到时使用入库时的区间，两者均整体换算为本季集数
+        let season_range = |i: &MatchedEpisode| {
+            let (start, end) = match parse_episode_number(&i.title) {
+                Some(EpisodeNumber::Range(start, end)) => (start, end),
+                _ => i.episode_range?,
+            };
+            entity.to_season_range(start, end)
+        };
+        let mut new_eps = entity_eps_matched
             .into_iter()
-            .map(|i| {
-                let num = eps_map.get(&i.resource_id).copied();
-                Episode {
+            .map(|i| match season_range(&i) {
+                Some((start, end)) => Episode {
                     sub_anime_id: i.sub_anime_id,
                     resource_id: i.resource_id,
                     status: i.status,
-                    ep_num: num,
+                    ep_num: Some(start as f64),
+                    ep_end: Some(end as f64),
+                    supersedes: vec![],
+                },
+                None => {
+                    let replaced_by = revisions.get(&i.resource_id);
+                    if replaced_by.is_some() {
+                        tracing::info!(
+                            "sub anime episode superseded by revision, sub_anime_id: {}, resource title: {}",
+                            i.sub_anime_id,
+                            i.title
+                        );
+                    }
+                    Episode {
+                        sub_anime_id: i.sub_anime_id,
+                        resource_id: i.resource_id,
+                        status: match replaced_by {
+                            Some(_) => EpsiodeStatus::Superseded,
+                            None => i.status,
+                        },
+                        ep_num: eps_map
+                            .get(replaced_by.unwrap_or(&i.resource_id))
+                            .map(|num| entity.to_season_episode(*num)),
+                        ep_end: None,
+                        supersedes: superseded_by(&revisions, &stored, &i.resource_id),
+                    }
                 }
             })
             .collect::<Vec<_>>();
+
+        // 合集只在能补全缺失剧集时才接受，避免重复下载
+        for i in new_collections {
+            let Some((start, end)) = season_range(&i) else {
+                continue;
+            };
+            let covered = covered_episode_numbers(&new_eps);
+            if (start..=end).all(|n| covered.contains(&(n as f64))) {
+                tracing::debug!(
+                    "sub anime matcher skip collection, sub_anime_id: {}, resource title: {}",
+                    i.sub_anime_id,
+                    i.title
+                );
+                continue;
+            }
+            tracing::info!(
+                "sub anime matcher matched collection, sub_anime_id: {}, resource title: {}, range: {}-{}",
+                i.sub_anime_id,
+                i.title,
+                start,
+                end
+            );
+            new_eps.push(Episode {
+                sub_anime_id: i.sub_anime_id,
+                resource_id: i.resource_id,
+                status: i.status,
+                ep_num: Some(start as f64),
+                ep_end: Some(end as f64),
+                supersedes: vec![],
+            });
+        }
         entity.update_progress(&new_eps);
         self.repo
             .update_sub_anime_progress(entity.get_base_data(), &new_eps)
@@ -104,7 +214,11 @@ impl SubAnimeEpsiodes {
             .await
             .map_err(|e| Error::external("sub anime eps get epsiode failed", e))?
             .into_iter()
-            .filter_map(|i| i.data.ep.ep_num.map(|v| v as i64))
+            .map(|i| i.data.ep)
+            .collect::<Vec<_>>();
+        let eps = covered_episode_numbers(&eps)
+            .into_iter()
+            .map(|v| v as i64)
             .collect::<Vec<_>>();
 
         Ok(check_missing_episodes(&eps, self.eps))
@@ -186,7 +300,76 @@ fn check_missing_episodes(episodes: &[i64], total_episodes: u32) -> bool {
     missing > 0 && missing <= total_episodes as i64
 }
 
+/// 修正版的识别键：发布组、季度、集数、分辨率及字幕语言均相同
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct RevisionKey {
+    group: String,
+    season: Option<u32>,
+    episode: u64,
+    resolution: Option<u32>,
+    subtitle_langs: Vec<SubtitleLang>,
+}
+
+/// 标题中的修正版本号，`05v2` 取 2，标注修正或 repack 的视为至少第 2 版
+fn revision_number(title: &str, version: Option<u32>) -> u32 {
+    let title = nfkc_to_lowercase(title);
+    let corrected = title.contains("修正") || title.contains("repack");
+    version.unwrap_or(1).max(if corrected { 2 } else { 1 })
+}
+
+/// 找出被同一发布组修正版替换的单集资源，返回 旧资源 → 修正版资源 的映射，
+/// 已被替换的剧集不再参与比较
+fn find_revisions(eps: &[MatchedEpisode]) -> HashMap<[u8; 20], [u8; 20]> {
+    let mut releases: HashMap<RevisionKey, Vec<(u32, [u8; 20])>> = HashMap::new();
+    for ep in eps.iter().filter(|i| i.status != EpsiodeStatus::Superseded) {
+        let info = parse_release(&ep.title);
+        let (Some(group), Some(episode)) = (info.group.as_deref(), info.episode) else {
+            continue;
+        };
+        let key = RevisionKey {
+            group: nfkc_to_lowercase(group),
+            season: info.season,
+            episode: episode.to_bits(),
+            resolution: info.resolution,
+            subtitle_langs: info.subtitle_langs,
+        };
+        releases
+            .entry(key)
+            .or_default()
+            .push((revision_number(&ep.title, info.version), ep.resource_id));
+    }
+
+    let mut result = HashMap::new();
+    for list in releases.into_values() {
+        let Some(&(latest, latest_id)) = list.iter().max_by_key(|(revision, _)| *revision) else {
+            continue;
+        };
+        for (revision, id) in list {
+            if revision < latest {
+                result.insert(id, latest_id);
+            }
+        }
+    }
+    result
+}
+
+/// 优先按标题格式识别集数，无法识别的标题再按数字列推断
 fn extract_episode_number(eps: &[MatchedEpisode]) -> HashMap<[u8; 20], f64> {
+    let mut result = HashMap::new();
+    let mut unresolved = vec![];
+    for ep in eps {
+        match parse_episode_number(&ep.title) {
+            Some(EpisodeNumber::Single(num)) => {
+                result.insert(ep.resource_id, num);
+            }
+            _ => unresolved.push(ep),
+        }
+    }
+    result.extend(extract_episode_number_by_column(&unresolved));
+    result
+}
+
+fn extract_episode_number_by_column(eps: &[&MatchedEpisode]) -> HashMap<[u8; 20], f64> {
     // 1. 解析每个标题中的所有数字
     let number_lists: Vec<Vec<f64>> = eps
         .iter()
@@ -298,3 +481,114 @@ fn find_best_column(columns: &[Vec<f64>]) -> Option<usize> {
 
     best_idx
 }
+
+/// 被修正版替换的全部旧资源，只记录已入库的旧版本，本次同时匹配到的旧版本未曾下载
+fn superseded_by(
+    revisions: &HashMap<[u8; 20], [u8; 20]>,
+    stored: &HashSet<[u8; 20]>,
+    resource_id: &[u8; 20],
+) -> Vec<[u8; 20]> {
+    let mut ids = revisions
+        .iter()
+        .filter(|(old, new)| *new == resource_id && stored.contains(*old))
+        .map(|(old, _)| *old)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_revisions_from_same_group() {
+        let ep = |id: u8, title: &str, status: EpsiodeStatus| MatchedEpisode {
+            sub_anime_id: 1,
+            resource_id: [id; 20],
+            status,
+            title: title.to_string(),
+            episode_range: None,
+        };
+        let eps = vec![
+            ep(
+                1,
+                "[ANi] Title - 05 [1080P][CHT]",
+                EpsiodeStatus::Downloaded,
+            ),
+            ep(2, "[ANi] Title - 05v2 [1080P][CHT]", EpsiodeStatus::Pending),
+            ep(3, "[ANi] Title - 05 [720P][CHT]", EpsiodeStatus::Downloaded),
+            ep(
+                4,
+                "[Other] Title - 05 [1080P][CHT]",
+                EpsiodeStatus::Downloaded,
+            ),
+            ep(
+                5,
+                "[ANi] Title - 06 [1080P][CHT]",
+                EpsiodeStatus::Downloaded,
+            ),
+            ep(
+                6,
+                "[ANi] Title - 06 修正版 [1080P][CHT]",
+                EpsiodeStatus::Pending,
+            ),
+            ep(
+                7,
+                "[ANi] Title - 04 [1080P][CHT]",
+                EpsiodeStatus::Superseded,
+            ),
+            ep(
+                8,
+                "[ANi] Title - 04v2 [1080P][CHT]",
+                EpsiodeStatus::Downloaded,
+            ),
+        ];
+        let revisions = find_revisions(&eps);
+        assert_eq!(
+            revisions,
+            HashMap::from([([1; 20], [2; 20]), ([5; 20], [6; 20])])
+        );
+    }
+
+    #[test]
+    fn superseded_by_keeps_every_stored_release() {
+        let revisions = HashMap::from([([1; 20], [4; 20]), ([2; 20], [4; 20]), ([3; 20], [4; 20])]);
+        let stored = HashSet::from([[2; 20], [1; 20]]);
+        assert_eq!(
+            superseded_by(&revisions, &stored, &[4; 20]),
+            vec![[1; 20], [2; 20]]
+        );
+        assert!(superseded_by(&revisions, &stored, &[1; 20]).is_empty());
+    }
+
+    #[test]
+    fn extract_episode_number_with_fallback() {
+        let ep = |id: u8, title: &str| MatchedEpisode {
+            sub_anime_id: 1,
+            resource_id: [id; 20],
+            status: EpsiodeStatus::Pending,
+            title: title.to_string(),
+            episode_range: None,
+        };
+        let eps = vec![
+            ep(1, "[ANi] Title 2024 - 05v2 [1080P][CHT]"),
+            ep(2, "[Group] Title 第六话 [1080P]"),
+            ep(3, "[Raw] Title 2024 07 1080p"),
+            ep(4, "[Raw] Title 2024 08 1080p"),
+        ];
+        assert_eq!(
+            extract_episode_number(&eps),
+            HashMap::from([
+                ([1; 20], 5.0),
+                ([2; 20], 6.0),
+                ([3; 20], 7.0),
+                ([4; 20], 8.0)
+            ])
+        );
+        assert_eq!(
+            extract_episode_number(&[ep(5, "[ANi] Title - 12 [1080P]")]),
+            HashMap::from([([5; 20], 12.0)])
+        );
+    }
+}
@@ -6,9 +6,17 @@ use resource::entity::resource_entity::ResourceEntity;
 
 use crate::entity::{
     cap::SpaceRuleMatcher,
-    model::{EpsiodeStatus, MatchedEpisode as Epsiode},
+    model::{EpsiodeStatus, MatchDecision, MatchStep, MatchTrace, MatchedEpisode as Epsiode},
 };
 
+/// 返回资源标题包含的首个关键字，关键字需已按 `nfkc_to_lowercase` 归一化
+pub(super) fn find_keyword<'a>(keywords: &'a [String], res: &ResourceEntity) -> Option<&'a str> {
+    keywords
+        .iter()
+        .map(String::as_str)
+        .find(|i| res.match_title().contains(i))
+}
+
 #[derive(Clone)]
 pub struct SubAnimeMatcher {
     id: i64,
@@ -19,6 +27,7 @@ pub struct SubAnimeMatcher {
     eps: Vec<Epsiode>,
     eps_num: u32,
     time_range: std::ops::Range<i64>,
+    accept_collection: bool,
 
     matcher: Arc<dyn SpaceRuleMatcher>,
 }
@@ -31,6 +40,7 @@ impl SubAnimeMatcher {
         keywords: Vec<String>,
         matcher: Arc<dyn SpaceRuleMatcher>,
         time_range: std::ops::Range<NaiveDateTime>,
+        accept_collection: bool,
     ) -> Self {
         let start = time_range.start.and_utc().timestamp();
         let end = time_range.end.and_utc().timestamp();
@@ -43,6 +53,7 @@ impl SubAnimeMatcher {
             keywords,
             eps: vec![],
             time_range: start..end,
+            accept_collection,
             matcher,
         }
     }
@@ -51,7 +62,7 @@ impl SubAnimeMatcher {
         &self.eps
     }
 
-    pub(super) fn get_rule_id(&self) -> Option<i64> {
+    pub fn get_rule_id(&self) -> Option<i64> {
         self.rule_id.or(self.candidate_rule_id)
     }
 
@@ -59,68 +70,118 @@ impl SubAnimeMatcher {
         self.eps_num
     }
 
+    /// 当前已匹配的资源
+    pub fn matched_resource_ids(&self) -> impl Iterator<Item = &[u8; 20]> {
+        self.eps.iter().map(|i| &i.resource_id)
+    }
+
     pub fn sub_anime_id(&self) -> i64 {
         self.id
     }
 
     pub fn match_resource(&mut self, res: &ResourceEntity) -> Result<bool, Error> {
-        // 时间范围过滤
-        if !self.time_range.contains(&res.published_at()) {
-            return Ok(false);
+        match self.trace_resource(res).decision {
+            MatchDecision::Matched
+            | MatchDecision::AlreadyMatched
+            | MatchDecision::CandidateKept { .. } => Ok(true),
+            MatchDecision::BoundRuleConflict {
+                bound_rule_id,
+                rule_id,
+            } => Err(Error::conflict(format!(
+                "sub anime matcher match resource failed, got matched rule id {}, but bind {}",
+                rule_id, bound_rule_id
+            ))),
+            _ => Ok(false),
+        }
+    }
+
+    /// 匹配资源并记录每一步的检查结果，与 `match_resource` 共用同一流程
+    pub fn trace_resource(&mut self, res: &ResourceEntity) -> MatchTrace {
+        let mut steps = vec![];
+        let done = |steps, decision| MatchTrace { steps, decision };
+
+        // 合集资源需要订阅显式开启
+        if res.is_collection() {
+            steps.push(MatchStep::Collection {
+                accepted: self.accept_collection,
+            });
+            if !self.accept_collection {
+                return done(steps, MatchDecision::CollectionRejected);
+            }
+        }
+
+        // 时间范围过滤，合集通常在完结后发布，只限制起始时间
+        let in_range = if res.is_collection() {
+            res.published_at() >= self.time_range.start
+        } else {
+            self.time_range.contains(&res.published_at())
+        };
+        steps.push(MatchStep::TimeWindow {
+            published_at: res.published_at(),
+            start: self.time_range.start,
+            end: (!res.is_collection()).then_some(self.time_range.end),
+            passed: in_range,
+        });
+        if !in_range {
+            return done(steps, MatchDecision::OutOfTimeRange);
         }
 
         // 判断是否已经匹配过
-        if self
-            .eps
-            .iter()
-            .find(|i| &i.resource_id == res.id())
-            .is_some()
-        {
-            return Ok(true);
+        if self.eps.iter().any(|i| &i.resource_id == res.id()) {
+            return done(steps, MatchDecision::AlreadyMatched);
         }
 
-        let result = self.matcher.is_match(res.title());
+        let result = self.matcher.is_match(res);
+        steps.push(MatchStep::Rule(result.clone()));
         if !result.matched {
-            return Ok(result.matched);
+            return done(steps, MatchDecision::RuleNotMatched);
         }
         let rule_id = result.rule_id;
         let order = result.rule_order;
-        let mut title_match = false;
 
         // 匹配关键字
-        for i in &self.keywords {
-            if res.match_title().contains(i) {
-                title_match = true;
-                break;
-            }
-        }
+        let keyword = find_keyword(&self.keywords, res).map(String::from);
+        let title_match = keyword.is_some();
+        steps.push(MatchStep::Keyword { matched: keyword });
         if !title_match {
-            return Ok(title_match);
+            return done(steps, MatchDecision::KeywordNotMatched);
         }
 
         if let Some(current_rule_id) = self.rule_id {
             if rule_id != current_rule_id {
-                return Err(Error::conflict(format!(
-                    "sub anime matcher match resource failed, got matched rule id {}, but bind {}",
-                    rule_id, current_rule_id
-                )));
+                return done(
+                    steps,
+                    MatchDecision::BoundRuleConflict {
+                        bound_rule_id: current_rule_id,
+                        rule_id,
+                    },
+                );
             }
         } else {
-            if let Some(candidate_order) = self.candidate_rule_order {
-                if order < candidate_order {
-                    self.eps.clear();
+            steps.push(MatchStep::Candidate {
+                rule_id: self.candidate_rule_id,
+                rule_order: self.candidate_rule_order,
+                replaced: self.candidate_rule_order.is_some_and(|c| order < c),
+            });
+            match (self.candidate_rule_id, self.candidate_rule_order) {
+                (Some(candidate_id), Some(candidate_order)) => {
+                    if order < candidate_order {
+                        self.eps.clear();
+                        self.candidate_rule_id = Some(rule_id);
+                        self.candidate_rule_order = Some(order);
+                    } else if order > candidate_order || candidate_id != rule_id {
+                        return done(
+                            steps,
+                            MatchDecision::CandidateKept {
+                                candidate_rule_id: candidate_id,
+                            },
+                        );
+                    }
+                }
+                _ => {
                     self.candidate_rule_id = Some(rule_id);
                     self.candidate_rule_order = Some(order);
-                } else if order > candidate_order {
-                    return Ok(true);
-                } else {
-                    if self.candidate_rule_id != Some(rule_id) {
-                        return Ok(true);
-                    }
                 }
-            } else {
-                self.candidate_rule_id = Some(rule_id);
-                self.candidate_rule_order = Some(order);
             }
         }
         self.eps.push(Epsiode {
@@ -128,8 +189,194 @@ impl SubAnimeMatcher {
             resource_id: *res.id(),
             title: res.title().into(),
             status: EpsiodeStatus::Pending,
+            // 未能识别区间的合集视为覆盖整季
+            episode_range: res
+                .is_collection()
+                .then(|| res.episode_range().unwrap_or((1, self.eps_num.max(1)))),
         });
 
-        Ok(true)
+        done(steps, MatchDecision::Matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use feed::entity::model::{FeedItem, ResourceKind};
+
+    use super::*;
+    use crate::entity::model::{MatchResult, MatchStep};
+
+    /// 按列表顺序返回首个标题包含 `pattern` 的规则
+    struct StubRules(Vec<(&'static str, i64, i64)>);
+
+    impl SpaceRuleMatcher for StubRules {
+        fn is_match(&self, res: &ResourceEntity) -> MatchResult {
+            self.0
+                .iter()
+                .find(|(pattern, _, _)| res.match_title().contains(pattern))
+                .map(|(_, rule_id, rule_order)| MatchResult {
+                    matched: true,
+                    rule_id: *rule_id,
+                    rule_order: *rule_order,
+                })
+                .unwrap_or(MatchResult {
+                    matched: false,
+                    rule_id: 0,
+                    rule_order: 0,
+                })
+        }
+    }
+
+    fn matcher(rule_id: Option<i64>, rules: Vec<(&'static str, i64, i64)>) -> SubAnimeMatcher {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        SubAnimeMatcher::new(
+            1,
+            rule_id,
+            12,
+            vec!["title".to_string()],
+            Arc::new(StubRules(rules)),
+            start.and_hms_opt(0, 0, 0).unwrap()..end.and_hms_opt(0, 0, 0).unwrap(),
+            false,
+        )
+    }
+
+    fn resource(id: u8, title: &str, published_at: i64, kind: ResourceKind) -> ResourceEntity {
+        ResourceEntity::preview(FeedItem {
+            title: title.to_string(),
+            source_url: format!("https://example.com/view/{}", id),
+            resource_url: format!("https://example.com/{}.torrent", id),
+            published_at,
+            info_hash: [id; 20],
+            info_hash_v2: None,
+            kind,
+            episode_range: None,
+            files: vec![],
+            size: None,
+            torrent: None,
+        })
+    }
+
+    const IN_RANGE: i64 = 1736467200; // 2025-01-10
+    const OUT_OF_RANGE: i64 = 1767225600; // 2026-01-01
+
+    /// 分别以 `match_resource` 与 `trace_resource` 匹配，检查两者结论及匹配状态一致
+    fn decide(matcher: &mut SubAnimeMatcher, res: &ResourceEntity) -> MatchTrace {
+        let mut other = matcher.clone();
+        let matched = other.match_resource(res);
+        let trace = matcher.trace_resource(res);
+        match &trace.decision {
+            MatchDecision::Matched
+            | MatchDecision::AlreadyMatched
+            | MatchDecision::CandidateKept { .. } => assert!(matched.unwrap()),
+            MatchDecision::BoundRuleConflict { .. } => assert!(matched.is_err()),
+            _ => assert!(!matched.unwrap()),
+        }
+        assert_eq!(other.get_rule_id(), matcher.get_rule_id());
+        assert!(
+            other
+                .matched_resource_ids()
+                .eq(matcher.matched_resource_ids())
+        );
+        trace
+    }
+
+    #[test]
+    fn trace_decisions_agree_with_match_resource() {
+        let mut m = matcher(None, vec![("1080", 1, 10)]);
+
+        let collection = resource(1, "Title 01-12 1080", IN_RANGE, ResourceKind::Collection);
+        let trace = decide(&mut m, &collection);
+        assert_eq!(trace.decision, MatchDecision::CollectionRejected);
+        assert_eq!(trace.steps, vec![MatchStep::Collection { accepted: false }]);
+
+        let late = resource(2, "Title - 01 1080", OUT_OF_RANGE, ResourceKind::Episode);
+        assert_eq!(
+            decide(&mut m, &late).decision,
+            MatchDecision::OutOfTimeRange
+        );
+
+        let other_rule = resource(3, "Title - 01 720", IN_RANGE, ResourceKind::Episode);
+        assert_eq!(
+            decide(&mut m, &other_rule).decision,
+            MatchDecision::RuleNotMatched
+        );
+
+        let other_anime = resource(4, "Other - 01 1080", IN_RANGE, ResourceKind::Episode);
+        let trace = decide(&mut m, &other_anime);
+        assert_eq!(trace.decision, MatchDecision::KeywordNotMatched);
+        assert_eq!(
+            trace.steps.last(),
+            Some(&MatchStep::Keyword { matched: None })
+        );
+
+        let episode = resource(5, "Title - 01 1080", IN_RANGE, ResourceKind::Episode);
+        let trace = decide(&mut m, &episode);
+        assert_eq!(trace.decision, MatchDecision::Matched);
+        assert_eq!(
+            trace.steps.last(),
+            Some(&MatchStep::Candidate {
+                rule_id: None,
+                rule_order: None,
+                replaced: false,
+            })
+        );
+        assert_eq!(
+            decide(&mut m, &episode).decision,
+            MatchDecision::AlreadyMatched
+        );
+        assert_eq!(m.get_rule_id(), Some(1));
+    }
+
+    #[test]
+    fn higher_priority_rule_replaces_candidate() {
+        let mut m = matcher(None, vec![("1080", 1, 10), ("720", 2, 20)]);
+
+        let low = resource(1, "Title - 01 720", IN_RANGE, ResourceKind::Episode);
+        assert_eq!(decide(&mut m, &low).decision, MatchDecision::Matched);
+        assert_eq!(m.get_rule_id(), Some(2));
+
+        let high = resource(2, "Title - 01 1080", IN_RANGE, ResourceKind::Episode);
+        let trace = decide(&mut m, &high);
+        assert_eq!(trace.decision, MatchDecision::Matched);
+        assert_eq!(
+            trace.steps.last(),
+            Some(&MatchStep::Candidate {
+                rule_id: Some(2),
+                rule_order: Some(20),
+                replaced: true,
+            })
+        );
+        assert_eq!(m.get_rule_id(), Some(1));
+        assert!(m.matched_resource_ids().eq([&[2; 20]]));
+
+        let kept = resource(3, "Title - 02 720", IN_RANGE, ResourceKind::Episode);
+        assert_eq!(
+            decide(&mut m, &kept).decision,
+            MatchDecision::CandidateKept {
+                candidate_rule_id: 1
+            }
+        );
+        assert!(m.matched_resource_ids().eq([&[2; 20]]));
+    }
+
+    #[test]
+    fn bound_rule_conflict() {
+        let mut m = matcher(Some(1), vec![("1080", 1, 10), ("720", 2, 20)]);
+
+        let other = resource(1, "Title - 01 720", IN_RANGE, ResourceKind::Episode);
+        assert_eq!(
+            decide(&mut m, &other).decision,
+            MatchDecision::BoundRuleConflict {
+                bound_rule_id: 1,
+                rule_id: 2,
+            }
+        );
+        assert_eq!(m.matched_resource_ids().count(), 0);
+
+        let bound = resource(2, "Title - 01 1080", IN_RANGE, ResourceKind::Episode);
+        assert_eq!(decide(&mut m, &bound).decision, MatchDecision::Matched);
+        assert_eq!(m.get_rule_id(), Some(1));
     }
 }
@@ -1,13 +1,17 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, pin::pin, sync::Arc};
 
 use anyhow::Context;
 use common::shared::error::Error;
+use futures::{Stream, StreamExt};
+use resource::entity::resource_entity::ResourceEntity;
+use tracing::error;
 
 use crate::entity::{
-    cap::{RuleMatcher, RuleRepository, SpaceRuleMatcher, SubAnimeRepository},
+    cap::{RuleRepository, SpaceRuleMatcher, SubAnimeRepository},
     episode_entity::EpsiodeEntity,
-    model::{RuleQuery, SubAnimeListQuery},
+    model::{RuleQuery, SubAnimeListQuery, SubAnimeSearchStatus, SubAnimeStatus},
     rule_entity::RuleEntity,
+    rule_matchers::RuleMatchers,
     space_rules::SpaceRules,
     sub_anime_entity::{SubAnimeEntity, SubAnimeEntityMatcher},
     sub_anime_episode::SubAnimeEpsiodes,
@@ -18,14 +22,14 @@ use crate::entity::{
 pub struct SubAnimes {
     repo: Arc<dyn SubAnimeRepository>,
     rule_repo: Arc<dyn RuleRepository>,
-    matcher: Arc<dyn RuleMatcher>,
+    matcher: RuleMatchers,
 }
 
 impl SubAnimes {
     pub fn new(
         repo: Arc<dyn SubAnimeRepository>,
         rule_repo: Arc<dyn RuleRepository>,
-        matcher: Arc<dyn RuleMatcher>,
+        matcher: RuleMatchers,
     ) -> Self {
         Self {
             repo,
@@ -133,6 +137,69 @@ impl SubAnimes {
         Ok(sub_anime_eps)
     }
 
+    /// 将资源与所有已启用且未处于搜索状态的订阅匹配并保存匹配到的剧集，
+    /// 轮询订阅源与外部推送共用；返回各订阅及其匹配器，可据此查询资源匹配到的订阅
+    pub async fn match_active<S>(
+        &self,
+        resources: S,
+    ) -> Result<Vec<(SubAnimeEntity, SubAnimeMatcher)>, Error>
+    where
+        S: Stream<Item = Result<ResourceEntity, Error>>,
+    {
+        let sub_anime_entity_list = self
+            .list(&SubAnimeListQuery {
+                anime_id: None,
+                space_id: None,
+                search_status: Some(SubAnimeSearchStatus::NotSearch),
+                sub_status: Some(SubAnimeStatus::Enable),
+                limit: None,
+            })
+            .await?;
+        if sub_anime_entity_list.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut matchers = vec![];
+        for entity in sub_anime_entity_list {
+            match self.as_matcher(&entity).await {
+                Ok(matcher) => matchers.push((entity, matcher)),
+                Err(e) => error!("sub anime {} as matcher failed, {}", entity.id(), e),
+            }
+        }
+
+        let mut resources = pin!(resources);
+        while let Some(res) = resources.next().await {
+            let res = match res {
+                Ok(res) => res,
+                Err(e) => {
+                    error!("match active sub animes get resource failed, {}", e);
+                    continue;
+                }
+            };
+            for (_, matcher) in &mut matchers {
+                if let Err(e) = matcher.match_resource(&res) {
+                    error!(
+                        "sub anime {} match {} failed, {}",
+                        matcher.sub_anime_id(),
+                        res.title(),
+                        e
+                    );
+                }
+            }
+        }
+
+        for (_, matcher) in &matchers {
+            if let Err(e) = self.save_matcher(matcher).await {
+                error!(
+                    "sub anime {} save matcher failed, {}",
+                    matcher.sub_anime_id(),
+                    e
+                );
+            }
+        }
+        Ok(matchers)
+    }
+
     pub async fn as_eps(&self, entity: &SubAnimeEntity) -> SubAnimeEpsiodes {
         SubAnimeEpsiodes::new(entity.id(), entity.eps_number(), self.repo.clone())
     }
@@ -175,6 +242,7 @@ impl SubAnimes {
             entity.keywords(),
             matcher,
             entity.match_time_range(),
+            entity.accept_collection(),
         ))
     }
 }
@@ -188,4 +256,12 @@ impl SubAnimes {
             .map_err(|e| Error::external("get one undownload ep failed", e))?;
         Ok(ep.map(|i| EpsiodeEntity::new(i.data, i.extend)))
     }
+
+    /// 剧集引用的资源标识，清理资源时需保留
+    pub async fn referenced_resource_ids(&self) -> Result<Vec<[u8; 20]>, Error> {
+        self.repo
+            .list_referenced_resource_ids()
+            .await
+            .map_err(|e| Error::external("list referenced resource ids failed", e))
+    }
 }
@@ -1,2 +1,3 @@
 pub mod regex;
 pub mod repository;
+pub mod structured;
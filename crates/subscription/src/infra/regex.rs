@@ -4,6 +4,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use regex::Regex;
+use resource::entity::resource_entity::ResourceEntity;
 
 use crate::entity::cap::RuleMatcher;
 
@@ -24,7 +25,8 @@ impl RegexRuleMatcher {
 
 #[async_trait]
 impl RuleMatcher for RegexRuleMatcher {
-    fn is_match(&self, pattern: &str, text: &str) -> bool {
+    fn is_match(&self, pattern: &str, res: &ResourceEntity) -> bool {
+        let text = res.title();
         if let Some(re) = self.cache.get(pattern) {
             return re.is_match(text);
         }
@@ -1,14 +1,15 @@
 use anyhow::{Context, Result, anyhow};
 use chrono::NaiveDate;
+use common::infra::schema::add_column_if_missing;
 use sqlx::{Pool, Row, Sqlite, Transaction, sqlite::SqliteRow};
 
 use crate::{
     entity::model::{
-        Episode, EpisodeBaseData, EpisodeExtendData, EpisodeProp, EpsiodeStatus, Mandate, Rule,
-        RuleBaseData, SearchMandateBaseData, SearchMandateProp, SubAnimeBaseData,
-        SubAnimeExtendData, SubAnimeProps, SubAnimeSearchStatus,
+        Episode, EpisodeBaseData, EpisodeExtendData, EpisodeNumbering, EpisodeProp, EpsiodeStatus,
+        Mandate, Rule, RuleBaseData, RuleKind, SearchMandateBaseData, SearchMandateProp,
+        SubAnimeBaseData, SubAnimeExtendData, SubAnimeProps, SubAnimeSearchStatus,
     },
-    infra::regex::RegexRuleMatcher,
+    infra::{regex::RegexRuleMatcher, structured::StructuredRuleMatcher},
 };
 
 #[derive(Clone)]
@@ -40,6 +41,9 @@ impl SubAnimeSqliteClient {
                 rule_id         INTEGER NULL,
                 search_status   INTEGER NOT NULL DEFAULT 0,
                 progress        INTEGER NOT NULL DEFAULT 0,
+                accept_collection INTEGER NOT NULL DEFAULT 0,
+                numbering       INTEGER NOT NULL DEFAULT 0,
+                episode_offset  INTEGER NOT NULL DEFAULT 0,
                 created_at      INTEGER NOT NULL DEFAULT (unixepoch()),
                 updated_at      INTEGER NOT NULL DEFAULT (unixepoch()),
                 CONSTRAINT uk_space_anime UNIQUE (space_id, anime_id)
@@ -49,6 +53,22 @@ impl SubAnimeSqliteClient {
         .execute(&mut **tx)
         .await?;
 
+        add_column_if_missing(
+            tx,
+            "sub_anime",
+            "accept_collection",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+        add_column_if_missing(tx, "sub_anime", "numbering", "INTEGER NOT NULL DEFAULT 0").await?;
+        add_column_if_missing(
+            tx,
+            "sub_anime",
+            "episode_offset",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_sub_anime_space ON sub_anime(space_id);")
             .execute(&mut **tx)
             .await?;
@@ -71,6 +91,8 @@ impl SubAnimeSqliteClient {
                 resource_id     BLOB NOT NULL,
                 status          INTEGER NOT NULL DEFAULT 0,
                 ep_num          REAL NULL,
+                ep_end          REAL NULL,
+                supersedes      BLOB NULL,               -- 被替换的旧资源标识，每 20 字节一个
                 created_at      INTEGER NOT NULL DEFAULT (unixepoch()),
                 updated_at      INTEGER NOT NULL DEFAULT (unixepoch()),
                 CONSTRAINT uk_sub_anime_resource UNIQUE (sub_anime_id, resource_id)
@@ -80,6 +102,9 @@ impl SubAnimeSqliteClient {
         .execute(&mut **tx)
         .await?;
 
+        add_column_if_missing(tx, "sub_anime_episode", "ep_end", "REAL NULL").await?;
+        add_column_if_missing(tx, "sub_anime_episode", "supersedes", "BLOB NULL").await?;
+
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_episode_pending ON sub_anime_episode(status) WHERE status = 0;",
         )
@@ -101,11 +126,15 @@ impl SubAnimeSqliteClient {
         se.resource_id,
         se.status,
         se.ep_num,
+        se.ep_end,
+        se.supersedes,
         r.title,
         r.url,
         sa.space_id,
         ase.season_number AS season,
-        at.name AS anime_origin_title
+        at.name AS anime_origin_title,
+        (SELECT rf.feed_id FROM resource_feed rf WHERE rf.info_hash = se.resource_id
+            ORDER BY rf.first_seen_at, rf.feed_id LIMIT 1) AS feed_id
     FROM sub_anime_episode se
     JOIN resource r ON r.info_hash = se.resource_id
     JOIN sub_anime sa ON sa.id = se.sub_anime_id
@@ -124,12 +153,30 @@ impl SubAnimeSqliteClient {
         let status = EpsiodeStatus::try_from(status).map_err(|e| anyhow!("{}", e))?;
 
         let ep_num: Option<f64> = row.try_get("ep_num")?;
+        let ep_end: Option<f64> = row.try_get("ep_end")?;
+        let supersedes = row
+            .try_get::<Option<Vec<u8>>, _>("supersedes")?
+            .unwrap_or_default();
+        if supersedes.len() % 20 != 0 {
+            return Err(anyhow!(
+                "invalid supersedes length: expected a multiple of 20"
+            ));
+        }
+        let supersedes = supersedes
+            .chunks_exact(20)
+            .map(|id| {
+                let mut old = [0u8; 20];
+                old.copy_from_slice(id);
+                old
+            })
+            .collect();
 
         let title: String = row.try_get("title")?;
         let url: String = row.try_get("url")?;
         let season: u32 = row.try_get("season")?;
         let space_id: i64 = row.try_get("space_id")?;
         let anime_origin_title: String = row.try_get("anime_origin_title")?;
+        let feed_id: Option<i64> = row.try_get("feed_id")?;
 
         Ok(EpisodeProp {
             data: EpisodeBaseData {
@@ -139,6 +186,8 @@ impl SubAnimeSqliteClient {
                     resource_id,
                     status,
                     ep_num,
+                    ep_end,
+                    supersedes,
                 },
             },
             extend: EpisodeExtendData {
@@ -147,6 +196,7 @@ impl SubAnimeSqliteClient {
                 season,
                 anime_origin_title,
                 space_id,
+                feed_id,
             },
         })
     }
@@ -160,6 +210,9 @@ impl SubAnimeSqliteClient {
         sa.rule_id,
         sa.search_status,
         sa.progress,
+        sa.accept_collection,
+        sa.numbering,
+        sa.episode_offset,
         COALESCE(
             (SELECT planned_ep_count FROM anime_season
              WHERE anime_id = sa.anime_id AND target_source = 'Bangumi'),
@@ -180,6 +233,8 @@ impl SubAnimeSqliteClient {
         let search_status: i32 = row.try_get("search_status")?;
         let search_status =
             SubAnimeSearchStatus::try_from(search_status).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let numbering = EpisodeNumbering::try_from(row.try_get::<i32, _>("numbering")?)
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
 
         let base_data = SubAnimeBaseData {
             id: row.try_get("id")?,
@@ -188,6 +243,9 @@ impl SubAnimeSqliteClient {
             rule_id: row.try_get("rule_id")?,
             search_status,
             progress: row.try_get::<i32, _>("progress")? as u32,
+            accept_collection: row.try_get("accept_collection")?,
+            numbering,
+            episode_offset: row.try_get::<i64, _>("episode_offset")? as u32,
         };
 
         let air_date_str: String = row.try_get("air_date")?;
@@ -217,13 +275,27 @@ impl SubAnimeSqliteClient {
 pub struct RuleSqliteClient {
     pub(super) pool: Pool<Sqlite>,
     pub regex_cache: RegexRuleMatcher,
+    pub structured_cache: StructuredRuleMatcher,
 }
 
 impl RuleSqliteClient {
-    pub fn new(pool: Pool<Sqlite>, matcher: RegexRuleMatcher) -> Self {
+    pub fn new(
+        pool: Pool<Sqlite>,
+        matcher: RegexRuleMatcher,
+        structured: StructuredRuleMatcher,
+    ) -> Self {
         Self {
             pool,
             regex_cache: matcher,
+            structured_cache: structured,
+        }
+    }
+
+    /// 修改或删除规则时清除对应匹配器的缓存
+    pub(super) fn remove_cache(&self, rule: &Rule) {
+        match rule.kind {
+            RuleKind::Regex => self.regex_cache.remove(&rule.pattern),
+            RuleKind::Structured => self.structured_cache.remove(&rule.pattern),
         }
     }
 }
@@ -244,6 +316,7 @@ impl RuleSqliteClient {
                 name        TEXT NOT NULL,
                 `order`       INTEGER NOT NULL,
                 space_id    INTEGER NOT NULL,
+                kind        INTEGER NOT NULL DEFAULT 0,
                 pattern     TEXT NOT NULL,
                 deleted_at  INTEGER
             );
@@ -252,6 +325,8 @@ impl RuleSqliteClient {
         .execute(&mut **tx)
         .await?;
 
+        add_column_if_missing(tx, "rule", "kind", "INTEGER NOT NULL DEFAULT 0").await?;
+
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_rule_space_id ON rule(space_id);")
             .execute(&mut **tx)
             .await?;
@@ -268,6 +343,8 @@ impl RuleSqliteClient {
         let name: String = row.try_get("name")?;
         let order: i64 = row.try_get("order")?;
         let space_id: i64 = row.try_get("space_id")?;
+        let kind: i32 = row.try_get("kind")?;
+        let kind = RuleKind::try_from(kind).map_err(|e| sqlx::Error::Decode(e.into()))?;
         let pattern: String = row.try_get("pattern")?;
         let deleted_at: Option<i64> = row.try_get("deleted_at")?;
 
@@ -278,6 +355,7 @@ impl RuleSqliteClient {
                 space_id,
                 name,
                 order,
+                kind,
                 pattern,
             },
         })
@@ -354,6 +432,13 @@ impl SearchMandateSqliteClient {
             .context("missing column 'anime_id'")?;
         let feed_id: i64 = row.try_get("feed_id").context("missing column 'feed_id'")?;
         let url: String = row.try_get("url").context("missing column 'url'")?;
+        let parser: Option<String> = row.try_get("parser").context("missing column 'parser'")?;
+        let min_spacing: Option<i64> = row
+            .try_get("min_spacing")
+            .context("missing column 'min_spacing'")?;
+        let request_options: Option<String> = row
+            .try_get("request_options")
+            .context("missing column 'request_options'")?;
 
         Ok(SearchMandateProp {
             data: SearchMandateBaseData {
@@ -362,6 +447,9 @@ impl SearchMandateSqliteClient {
                     anime_id,
                     feed_id,
                     url,
+                    parser,
+                    min_spacing,
+                    request_options,
                 },
             },
         })
@@ -13,7 +13,7 @@ use crate::{
 impl RuleRepository for RuleSqliteClient {
     async fn list(&self, query: &RuleQuery) -> Result<Vec<RuleBaseData>> {
         let mut builder = sqlx::QueryBuilder::new(
-            r#"SELECT id, name, "order", space_id, pattern, deleted_at FROM rule WHERE 1=1"#,
+            r#"SELECT id, name, "order", space_id, kind, pattern, deleted_at FROM rule WHERE 1=1"#,
         );
 
         // 按空间 ID 过滤
@@ -47,7 +47,7 @@ impl RuleRepository for RuleSqliteClient {
 
     async fn find(&self, id: i64) -> Result<Option<RuleBaseData>> {
         let row = sqlx::query(
-            r#"SELECT id, name, "order", space_id, pattern, deleted_at FROM rule WHERE id = ?"#,
+            r#"SELECT id, name, "order", space_id, kind, pattern, deleted_at FROM rule WHERE id = ?"#,
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -62,14 +62,15 @@ impl RuleRepository for RuleSqliteClient {
     async fn insert(&self, rule: &Rule) -> Result<RuleBaseData> {
         let row = sqlx::query(
             r#"
-            INSERT INTO rule (name, "order", space_id, pattern, deleted_at)
-            VALUES (?, ?, ?, ?, NULL)
-            RETURNING id, name, "order", space_id, pattern, deleted_at
+            INSERT INTO rule (name, "order", space_id, kind, pattern, deleted_at)
+            VALUES (?, ?, ?, ?, ?, NULL)
+            RETURNING id, name, "order", space_id, kind, pattern, deleted_at
             "#,
         )
         .bind(&rule.name)
         .bind(rule.order)
         .bind(rule.space_id)
+        .bind(i32::from(rule.kind))
         .bind(&rule.pattern)
         .fetch_one(&self.pool)
         .await?;
@@ -79,7 +80,7 @@ impl RuleRepository for RuleSqliteClient {
 
     async fn save(&self, rule: &RuleBaseData) -> Result<()> {
         // 修改规则一律清除对应的缓存
-        self.regex_cache.remove(&rule.metadata.pattern);
+        self.remove_cache(&rule.metadata);
         let active_flag = if rule.active { 1 } else { 0 };
 
         sqlx::query(
@@ -88,6 +89,7 @@ impl RuleRepository for RuleSqliteClient {
             SET name = ?,
                 "order" = ?,
                 space_id = ?,
+                kind = ?,
                 pattern = ?,
                 deleted_at = CASE
                     WHEN ? = 1 THEN NULL
@@ -99,6 +101,7 @@ impl RuleRepository for RuleSqliteClient {
         .bind(&rule.metadata.name)
         .bind(rule.metadata.order)
         .bind(rule.metadata.space_id)
+        .bind(i32::from(rule.metadata.kind))
         .bind(&rule.metadata.pattern)
         .bind(active_flag)
         .bind(rule.id)
@@ -114,7 +117,7 @@ impl RuleRepository for RuleSqliteClient {
 
         // 获取锁的同时拿到 pattern；如果 rule 本身都不存在，直接返回（保证接口幂等性）
         let row = sqlx::query(
-            r#"SELECT id, name, "order", space_id, pattern, deleted_at FROM rule WHERE id = ?"#,
+            r#"SELECT id, name, "order", space_id, kind, pattern, deleted_at FROM rule WHERE id = ?"#,
         )
         .bind(id)
         .fetch_optional(&mut *tx)
@@ -143,7 +146,7 @@ impl RuleRepository for RuleSqliteClient {
             .await?;
         }
         tx.commit().await?;
-        self.regex_cache.remove(&rule.metadata.pattern);
+        self.remove_cache(&rule.metadata);
         Ok(())
     }
 }
@@ -13,9 +13,10 @@ use sqlx::{QueryBuilder, Row};
 impl SearchMandateRepository for SearchMandateSqliteClient {
     async fn get_one(&self, block_feed_ids: &[i64]) -> Result<Option<SearchMandateProp>> {
         let mut qb = QueryBuilder::new(
-            "SELECT p.id, m.anime_id, p.feed_id, p.url 
+            "SELECT p.id, m.anime_id, p.feed_id, p.url, f.parser, f.min_spacing, f.request_options 
             FROM search_pool p 
-            JOIN search_mandate m ON p.search_mandate_id = m.id",
+            JOIN search_mandate m ON p.search_mandate_id = m.id
+            LEFT JOIN feed f ON p.feed_id = f.id",
         );
 
         if !block_feed_ids.is_empty() {
@@ -165,6 +166,9 @@ impl SearchMandateRepository for SearchMandateSqliteClient {
                         anime_id: m.anime_id,
                         feed_id: m.feed_id,
                         url: m.url.clone(),
+                        parser: m.parser.clone(),
+                        min_spacing: m.min_spacing,
+                        request_options: m.request_options.clone(),
                     },
                 },
             });
@@ -6,8 +6,8 @@ use crate::{
     entity::{
         cap::SubAnimeRepository,
         model::{
-            Episode, EpisodeBaseData, EpisodeProp, SubAnimeBaseData, SubAnimeListQuery,
-            SubAnimeProps, SubAnimeStatus,
+            Episode, EpisodeBaseData, EpisodeProp, EpsiodeStatus, SubAnimeBaseData,
+            SubAnimeListQuery, SubAnimeProps, SubAnimeStatus,
         },
     },
     infra::repository::client::SubAnimeSqliteClient,
@@ -107,6 +107,36 @@ impl SubAnimeRepository for SubAnimeSqliteClient {
                 .push(" THEN ")
                 .push_bind(item.progress as i32);
         }
+        builder.push(" END, ");
+
+        builder.push("accept_collection = CASE id");
+        for item in data {
+            builder
+                .push(" WHEN ")
+                .push_bind(item.id)
+                .push(" THEN ")
+                .push_bind(item.accept_collection);
+        }
+        builder.push(" END, ");
+
+        builder.push("numbering = CASE id");
+        for item in data {
+            builder
+                .push(" WHEN ")
+                .push_bind(item.id)
+                .push(" THEN ")
+                .push_bind(i32::from(item.numbering));
+        }
+        builder.push(" END, ");
+
+        builder.push("episode_offset = CASE id");
+        for item in data {
+            builder
+                .push(" WHEN ")
+                .push_bind(item.id)
+                .push(" THEN ")
+                .push_bind(item.episode_offset as i64);
+        }
         builder.push(" END WHERE id IN (");
 
         let mut separated = builder.separated(", ");
@@ -248,6 +278,19 @@ impl SubAnimeRepository for SubAnimeSqliteClient {
         row.map(|r| Self::row_to_episode_prop(&r)).transpose()
     }
 
+    async fn list_referenced_resource_ids(&self) -> Result<Vec<[u8; 20]>> {
+        let rows: Vec<Vec<u8>> =
+            sqlx::query_scalar("SELECT DISTINCT resource_id FROM sub_anime_episode")
+                .fetch_all(&self.pool)
+                .await?;
+        rows.into_iter()
+            .map(|id| {
+                id.try_into()
+                    .map_err(|_| anyhow!("invalid resource_id length: expected 20"))
+            })
+            .collect()
+    }
+
     async fn update_epsiode_status(&self, data: &EpisodeBaseData) -> Result<()> {
         let sql = "UPDATE sub_anime_episode 
             SET status = ?, updated_at = (unixepoch())
@@ -303,16 +346,26 @@ impl SubAnimeRepository for SubAnimeSqliteClient {
 
         if !eps.is_empty() {
             let mut builder = QueryBuilder::new(
-                "INSERT INTO sub_anime_episode (sub_anime_id, resource_id, status, ep_num) ",
+                "INSERT INTO sub_anime_episode (sub_anime_id, resource_id, status, ep_num, ep_end, supersedes) ",
             );
             builder.push_values(eps, |mut b, ep| {
                 b.push_bind(ep.sub_anime_id)
                     .push_bind(ep.resource_id.as_slice())
                     .push_bind(i32::from(ep.status.clone()))
-                    .push_bind(ep.ep_num);
+                    .push_bind(ep.ep_num)
+                    .push_bind(ep.ep_end)
+                    // 多个旧资源的标识依次拼接保存
+                    .push_bind((!ep.supersedes.is_empty()).then(|| ep.supersedes.concat()));
             });
+            // 已有剧集的下载状态可能被并发更新，仅写入替换标记
+            builder.push(
+                " ON CONFLICT (sub_anime_id, resource_id) DO UPDATE SET ep_num = excluded.ep_num, ep_end = excluded.ep_end, \
+                status = CASE WHEN excluded.status = ",
+            );
+            builder.push_bind(i32::from(EpsiodeStatus::Superseded));
             builder.push(
-                " ON CONFLICT (sub_anime_id, resource_id) DO UPDATE SET ep_num = excluded.ep_num",
+                " THEN excluded.status ELSE sub_anime_episode.status END, \
+                supersedes = COALESCE(excluded.supersedes, sub_anime_episode.supersedes)",
             );
 
             builder.build().execute(&mut *tx).await?;
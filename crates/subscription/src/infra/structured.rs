@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use common::shared::str::nfkc_to_lowercase;
+use dashmap::DashMap;
+use resource::entity::{model::ReleaseInfo, resource_entity::ResourceEntity};
+
+use crate::entity::{cap::RuleMatcher, model::StructuredRule};
+
+/// 结构化规则的匹配器，规则按 JSON 文本缓存解析结果
+#[derive(Clone, Default)]
+pub struct StructuredRuleMatcher {
+    cache: Arc<DashMap<String, Arc<CompiledRule>>>,
+}
+
+impl StructuredRuleMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remove(&self, pattern: &str) {
+        self.cache.remove(pattern);
+    }
+
+    fn compile(&self, pattern: &str) -> Result<Arc<CompiledRule>> {
+        if let Some(rule) = self.cache.get(pattern) {
+            return Ok(rule.clone());
+        }
+        let rule = Arc::new(CompiledRule::new(serde_json::from_str(pattern)?)?);
+        self.cache.insert(pattern.to_string(), rule.clone());
+        Ok(rule)
+    }
+}
+
+#[async_trait]
+impl RuleMatcher for StructuredRuleMatcher {
+    fn is_match(&self, pattern: &str, res: &ResourceEntity) -> bool {
+        match self.compile(pattern) {
+            Ok(rule) => rule.is_match(res.match_title(), res.release(), res.total_size()),
+            Err(_) => false,
+        }
+    }
+
+    fn validate(&self, pattern: &str) -> Result<()> {
+        self.compile(pattern).map(|_| ())
+    }
+}
+
+/// 关键字及发布组预先按资源的 `match_title` 格式归一化
+#[derive(Debug)]
+struct CompiledRule {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    groups: Vec<String>,
+    rule: StructuredRule,
+}
+
+impl CompiledRule {
+    fn new(rule: StructuredRule) -> Result<Self> {
+        if let (Some(min), Some(max)) = (rule.min_size, rule.max_size)
+            && min > max
+        {
+            bail!("min_size {} is greater than max_size {}", min, max);
+        }
+        let normalize = |list: &[String]| {
+            list.iter()
+                .map(|i| nfkc_to_lowercase(i))
+                .filter(|i| !i.is_empty())
+                .collect::<Vec<_>>()
+        };
+        Ok(Self {
+            include: normalize(&rule.include),
+            exclude: normalize(&rule.exclude),
+            groups: normalize(&rule.groups),
+            rule,
+        })
+    }
+
+    fn is_match(&self, match_title: &str, release: &ReleaseInfo, size: Option<u64>) -> bool {
+        if !self
+            .include
+            .iter()
+            .all(|i| match_title.contains(i.as_str()))
+        {
+            return false;
+        }
+        if self
+            .exclude
+            .iter()
+            .any(|i| match_title.contains(i.as_str()))
+        {
+            return false;
+        }
+        if !self.groups.is_empty()
+            && !release
+                .group
+                .as_deref()
+                .map(nfkc_to_lowercase)
+                .is_some_and(|g| self.groups.contains(&g))
+        {
+            return false;
+        }
+        if !self.rule.resolutions.is_empty()
+            && !release
+                .resolution
+                .is_some_and(|r| self.rule.resolutions.contains(&r))
+        {
+            return false;
+        }
+        if !self.rule.subtitle_langs.is_empty()
+            && !release
+                .subtitle_langs
+                .iter()
+                .any(|l| self.rule.subtitle_langs.contains(l))
+        {
+            return false;
+        }
+        if let Some(size) = size
+            && (self.rule.min_size.is_some_and(|min| size < min)
+                || self.rule.max_size.is_some_and(|max| size > max))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource::entity::{model::SubtitleLang, release_parser::parse_release};
+
+    use super::*;
+
+    #[test]
+    fn match_structured_rule() {
+        let matcher = StructuredRuleMatcher::new();
+        let rule = matcher
+            .compile(
+                r#"{
+                    "include": ["Title"],
+                    "exclude": ["繁體", "HEVC"],
+                    "groups": ["ANi", "Lilith-Raws"],
+                    "resolutions": [1080],
+                    "subtitle_langs": ["chs", "cht"],
+                    "max_size": 2147483648
+                }"#,
+            )
+            .unwrap();
+        let is_match = |title: &str, size: Option<u64>| {
+            rule.is_match(&nfkc_to_lowercase(title), &parse_release(title), size)
+        };
+
+        let title = "[ANi] Title - 05 [1080P][Baha][WEB-DL][AAC AVC][CHT]";
+        assert!(is_match(title, None));
+        assert!(is_match(title, Some(1 << 30)));
+        assert!(!is_match(title, Some(3 << 30)));
+        assert!(!is_match("[ANi] Other - 05 [1080P][CHT]", None));
+        assert!(!is_match("[ANi] Title - 05 [720P][CHT]", None));
+        assert!(!is_match("[ANi] Title - 05 [1080P][HEVC][CHT]", None));
+        assert!(!is_match("[Other] Title - 05 [1080P][CHT]", None));
+        assert!(!is_match("[ANi] Title - 05 [1080P]", None));
+
+        let jpn_only = CompiledRule::new(StructuredRule {
+            subtitle_langs: vec![SubtitleLang::Jpn],
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!jpn_only.is_match("title", &ReleaseInfo::default(), None));
+
+        assert!(matcher.validate(r#"{"resolutions": ["1080p"]}"#).is_err());
+        assert!(
+            matcher
+                .validate(r#"{"min_size": 2, "max_size": 1}"#)
+                .is_err()
+        );
+    }
+}
@@ -2,9 +2,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use crate::entity::model::{DownloadTask, DownloaderConfig, UserBaseData, UserProps, UserRole};
+use crate::entity::model::{
+    DownloadSource, DownloadTask, DownloaderConfig, UserBaseData, UserProps, UserRole,
+};
 use anyhow::Result;
 
+pub use common::shared::cap::CryptoProvider;
+
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn find(&self, id: i64) -> Result<Option<UserProps>>;
@@ -26,8 +30,13 @@ pub trait UserRepository: Send + Sync {
 pub trait DownloadProvider: Send + Sync {
     fn name(&self) -> &str;
     async fn stop(&self);
-    
-    async fn download(&self, url: &str, path: &str, hash: [u8; 20]) -> Result<bool>;
+
+    async fn download(
+        &self,
+        source: DownloadSource<'_>,
+        path: &str,
+        hash: [u8; 20],
+    ) -> Result<bool>;
     async fn list_task(&self) -> Result<Vec<DownloadTask>>;
     async fn get_task(&self, hash: [u8; 20]) -> Result<Option<DownloadTask>>;
     async fn pause_task(&self, hash: [u8; 20]) -> Result<()>;
@@ -45,8 +54,3 @@ pub trait DownloaderManager: Send + Sync {
 
     async fn validate_config(&self, config: &DownloaderConfig) -> Result<()>;
 }
-
-pub trait CryptoProvider: Send + Sync {
-    fn encrypt(&self, plain: &str) -> anyhow::Result<String>;
-    fn decrypt(&self, cipher: &str) -> anyhow::Result<String>;
-}
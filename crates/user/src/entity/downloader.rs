@@ -2,27 +2,36 @@ use std::{path::Path, sync::Arc};
 
 use anyhow::Context;
 use async_trait::async_trait;
-use common::shared::{cap, error::Error};
+use common::shared::{
+    cap::{self, TorrentStore},
+    error::Error,
+};
 
-use crate::entity::cap::DownloadProvider;
+use crate::entity::{cap::DownloadProvider, model::DownloadSource};
 
 #[derive(Clone)]
 pub struct Downloader {
     user_id: i64,
     base_path: String,
+    remove_superseded: bool,
     downloader: Arc<dyn DownloadProvider>,
+    torrent_store: Arc<dyn TorrentStore>,
 }
 
 impl Downloader {
     pub fn new(
         user_id: i64,
         base_path: String,
+        remove_superseded: bool,
         download_provider: Arc<dyn DownloadProvider>,
+        torrent_store: Arc<dyn TorrentStore>,
     ) -> Self {
         Self {
             user_id,
             base_path,
+            remove_superseded,
             downloader: download_provider,
+            torrent_store,
         }
     }
 
@@ -39,9 +48,26 @@ impl cap::Downloader for Downloader {
             .to_str()
             .context("not found download path")
             .map_err(|e| Error::conflict(e.to_string()))?;
+        // 优先使用本地保存的种子，避免下载器再次从站点获取
+        let torrent = match self.torrent_store.load(hash).await {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::warn!(
+                    "user {} load local torrent {} failed, fallback to url, {}",
+                    self.user_id,
+                    hex::encode(hash),
+                    e
+                );
+                None
+            }
+        };
+        let source = match &torrent {
+            Some(bytes) => DownloadSource::Torrent(bytes),
+            None => DownloadSource::Url(url),
+        };
         let ok = self
             .downloader
-            .download(url, download_path, hash)
+            .download(source, download_path, hash)
             .await
             .map_err(|e| {
                 Error::external(
@@ -57,4 +83,28 @@ impl cap::Downloader for Downloader {
             })?;
         Ok(ok)
     }
+
+    async fn remove_superseded(&self, hash: [u8; 20]) -> Result<bool, Error> {
+        if !self.remove_superseded {
+            return Ok(false);
+        }
+        let remove = async {
+            if self.downloader.get_task(hash).await?.is_none() {
+                return Ok(false);
+            }
+            self.downloader.delete_task(hash).await?;
+            Ok(true)
+        };
+        remove.await.map_err(|e: anyhow::Error| {
+            Error::external(
+                format!(
+                    "user {} use {} remove superseded task {} failed",
+                    self.user_id,
+                    self.downloader.name(),
+                    hex::encode(hash)
+                ),
+                e,
+            )
+        })
+    }
 }
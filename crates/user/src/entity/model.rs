@@ -29,6 +29,15 @@ impl From<UserRole> for u8 {
     }
 }
 
+/// 下载任务来源
+#[derive(Debug, Clone, Copy)]
+pub enum DownloadSource<'a> {
+    /// 磁力链接或种子地址，由下载器自行获取
+    Url(&'a str),
+    /// 本地保存的种子文件内容
+    Torrent(&'a [u8]),
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Hash, Eq, PartialEq)]
 pub enum DownloaderConfig {
     Qbit(DownloadConfig<QbitConfig>),
@@ -50,6 +59,13 @@ impl DownloaderConfig {
         }
     }
 
+    pub fn remove_superseded(&self) -> bool {
+        match self {
+            DownloaderConfig::Qbit(download_config) => download_config.remove_superseded,
+            DownloaderConfig::Default(download_config) => download_config.remove_superseded,
+        }
+    }
+
     pub fn name(&self) -> &str {
         match self {
             DownloaderConfig::Qbit(download_config) => &download_config.name,
@@ -106,6 +122,9 @@ pub struct DownloadConfig<T> {
     pub name: String,
     pub active: bool,
     pub base_path: String,
+    /// 修正版下载完成后删除被替换的旧任务
+    #[serde(default)]
+    pub remove_superseded: bool,
     pub config: T,
 }
 
@@ -1,4 +1,4 @@
-use common::shared::error::Error;
+use common::shared::{cap::TorrentStore, error::Error};
 use std::sync::Arc;
 
 use crate::entity::{
@@ -13,6 +13,7 @@ pub struct Users {
     repo: Arc<dyn UserRepository>,
     downloader_manager: Arc<dyn DownloaderManager>,
     crypto_provider: Arc<dyn CryptoProvider>,
+    torrent_store: Arc<dyn TorrentStore>,
 }
 
 impl Users {
@@ -20,11 +21,13 @@ impl Users {
         repo: Arc<dyn UserRepository>,
         downloader_manager: Arc<dyn DownloaderManager>,
         crypto_provider: Arc<dyn CryptoProvider>,
+        torrent_store: Arc<dyn TorrentStore>,
     ) -> Self {
         Self {
             repo,
             downloader_manager,
             crypto_provider,
+            torrent_store,
         }
     }
 }
@@ -154,7 +157,9 @@ impl Users {
         Ok(Some(Downloader::new(
             user_id,
             config.base_path().to_string(),
+            config.remove_superseded(),
             provider,
+            self.torrent_store.clone(),
         )))
     }
 }
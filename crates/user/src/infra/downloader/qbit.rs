@@ -2,11 +2,17 @@ use std::collections::HashMap;
 
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use reqwest::{Client, ClientBuilder, StatusCode, Url, multipart::Form};
+use reqwest::{
+    Client, ClientBuilder, StatusCode, Url,
+    multipart::{Form, Part},
+};
 use serde::{Deserialize, Serialize};
 use tokio::time;
 
-use crate::entity::cap::DownloadProvider;
+use crate::entity::{
+    cap::DownloadProvider,
+    model::{DownloadSource, DownloadState, DownloadTask},
+};
 
 #[derive(Debug, Clone)]
 pub struct Qbit {
@@ -80,6 +86,48 @@ pub struct QbitTorrent {
     // pub upspeed: i64,
 }
 
+/// `api/v2/torrents/info` 返回的单个任务
+#[derive(Default, Debug, Clone, Deserialize)]
+struct QbitTorrentInfo {
+    hash: String,
+    name: String,
+    state: String,
+    progress: f64,
+    total_size: u64,
+    dlspeed: u64,
+    upspeed: u64,
+    ratio: f64,
+    seeding_time: Option<u64>,
+}
+
+impl QbitTorrentInfo {
+    fn into_task(self) -> Option<DownloadTask> {
+        let hash = hex::decode(&self.hash).ok()?.try_into().ok()?;
+        let is_seeding = matches!(
+            self.state.as_str(),
+            "uploading" | "stalledUP" | "queuedUP" | "forcedUP"
+        );
+        let state = match self.state.as_str() {
+            "error" | "missingFiles" => DownloadState::Error(self.state),
+            "pausedDL" | "stoppedDL" => DownloadState::Paused,
+            s if s.ends_with("UP") || s == "uploading" => DownloadState::Completed,
+            _ => DownloadState::Downloading,
+        };
+        Some(DownloadTask {
+            hash,
+            name: self.name,
+            state,
+            progress: self.progress,
+            total_size: self.total_size,
+            download_speed: self.dlspeed,
+            is_seeding,
+            upload_speed: self.upspeed,
+            seed_ratio: self.ratio,
+            seed_duration: is_seeding.then_some(self.seeding_time).flatten(),
+        })
+    }
+}
+
 impl Qbit {
     pub async fn new(url: String, username: String, password: String) -> Result<Self> {
         let client = Qbit {
@@ -131,14 +179,13 @@ impl Qbit {
         }
     }
 
-    pub async fn add(&self, magnet: &str, save_path: &str, hash: &str) -> Result<bool, Error> {
+    /// `form` 中需已包含 `urls` 或 `torrents` 字段
+    pub async fn add(&self, form: Form, save_path: &str, hash: &str) -> Result<bool, Error> {
         let rsp = self
             .client
             .post(Url::parse(self.config.url.as_str())?.join("api/v2/torrents/add")?)
             .multipart(
-                Form::new()
-                    .text("urls", magnet.to_string())
-                    .text("autoTMM", "false")
+                form.text("autoTMM", "false")
                     .text("savepath", save_path.to_string())
                     .text("paused", "false")
                     .text("stopCondition", "None")
@@ -182,6 +229,28 @@ impl Qbit {
         Ok(rsp.json().await?)
     }
 
+    /// 查询任务信息，`hashes` 为空时返回全部任务
+    async fn torrents_info(&self, hashes: Option<&str>) -> Result<Vec<DownloadTask>> {
+        self.check_and_login().await?;
+        let mut url = Url::parse(self.config.url.as_str())?.join("api/v2/torrents/info")?;
+        if let Some(hashes) = hashes {
+            url.query_pairs_mut().append_pair("hashes", hashes);
+        }
+        let rsp = self.client.get(url).send().await?;
+        if rsp.status() != StatusCode::OK {
+            return Err(Error::msg(format!(
+                "get qbit torrents info failed, http status code is {}",
+                rsp.status()
+            )));
+        }
+        Ok(rsp
+            .json::<Vec<QbitTorrentInfo>>()
+            .await?
+            .into_iter()
+            .filter_map(QbitTorrentInfo::into_task)
+            .collect())
+    }
+
     // 当前实现没有重载配置的需求，后续如有需要可启用
     // pub async fn load_new_config(&mut self, config: &QbitConfig) -> Result<(), Error> {
     //     if config.url.is_empty() || config.username.is_empty() || config.password.is_empty() {
@@ -198,11 +267,30 @@ impl Qbit {
 
 #[async_trait]
 impl DownloadProvider for Qbit {
-    async fn download(&self, url: &str, path: &str, hash: [u8; 20]) -> Result<bool> {
-        tracing::info!("qbit will download {} to {}", url, path);
-        self.check_and_login().await?;
+    async fn download(
+        &self,
+        source: DownloadSource<'_>,
+        path: &str,
+        hash: [u8; 20],
+    ) -> Result<bool> {
         let hash = hex::encode(hash).to_lowercase();
-        Ok(self.add(url, path, &hash).await?)
+        self.check_and_login().await?;
+        let form = match source {
+            DownloadSource::Url(url) => {
+                tracing::info!("qbit will download {} to {}", url, path);
+                Form::new().text("urls", url.to_string())
+            }
+            DownloadSource::Torrent(bytes) => {
+                tracing::info!("qbit will download torrent {} to {}", hash, path);
+                Form::new().part(
+                    "torrents",
+                    Part::bytes(bytes.to_vec())
+                        .file_name(format!("{}.torrent", hash))
+                        .mime_str("application/x-bittorrent")?,
+                )
+            }
+        };
+        Ok(self.add(form, path, &hash).await?)
     }
 
     fn name(&self) -> &str {
@@ -211,15 +299,13 @@ impl DownloadProvider for Qbit {
 
     async fn stop(&self) {}
 
-    async fn list_task(&self) -> Result<Vec<crate::entity::model::DownloadTask>> {
-        anyhow::bail!("operation not supported for external downloader")
+    async fn list_task(&self) -> Result<Vec<DownloadTask>> {
+        self.torrents_info(None).await
     }
 
-    async fn get_task(
-        &self,
-        _hash: [u8; 20],
-    ) -> Result<Option<crate::entity::model::DownloadTask>> {
-        anyhow::bail!("operation not supported for external downloader")
+    async fn get_task(&self, hash: [u8; 20]) -> Result<Option<DownloadTask>> {
+        let tasks = self.torrents_info(Some(&hex::encode(hash))).await?;
+        Ok(tasks.into_iter().find(|t| t.hash == hash))
     }
 
     async fn pause_task(&self, _hash: [u8; 20]) -> Result<()> {
@@ -230,7 +316,24 @@ impl DownloadProvider for Qbit {
         anyhow::bail!("operation not supported for external downloader")
     }
 
-    async fn delete_task(&self, _hash: [u8; 20]) -> Result<()> {
-        anyhow::bail!("operation not supported for external downloader")
+    async fn delete_task(&self, hash: [u8; 20]) -> Result<()> {
+        self.check_and_login().await?;
+        let rsp = self
+            .client
+            .post(Url::parse(self.config.url.as_str())?.join("api/v2/torrents/delete")?)
+            .form(&[
+                ("hashes", hex::encode(hash).as_str()),
+                ("deleteFiles", "true"),
+            ])
+            .send()
+            .await?;
+        if rsp.status() != StatusCode::OK {
+            return Err(Error::msg(format!(
+                "delete qbit torrent {} failed, http status code is {}",
+                hex::encode(hash),
+                rsp.status()
+            )));
+        }
+        Ok(())
     }
 }
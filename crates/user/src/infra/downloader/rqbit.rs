@@ -10,7 +10,7 @@ use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 
 use crate::entity::cap::DownloadProvider;
-use crate::entity::model::{DefaultDownloaderConfig, DownloadState, DownloadTask};
+use crate::entity::model::{DefaultDownloaderConfig, DownloadSource, DownloadState, DownloadTask};
 
 const DEFAULT_DOWNLOADER_STATE_DIR: &str = "downloader_state";
 
@@ -144,7 +144,12 @@ impl DefaultDownloader {
 
 #[async_trait]
 impl DownloadProvider for DefaultDownloader {
-    async fn download(&self, url: &str, path: &str, hash: [u8; 20]) -> Result<bool> {
+    async fn download(
+        &self,
+        source: DownloadSource<'_>,
+        path: &str,
+        hash: [u8; 20],
+    ) -> Result<bool> {
         let id20 = Id20::new(hash);
 
         if self
@@ -156,13 +161,17 @@ impl DownloadProvider for DefaultDownloader {
             return Ok(true);
         }
 
+        let add_torrent = match source {
+            DownloadSource::Url(url) => AddTorrent::from_url(url),
+            DownloadSource::Torrent(bytes) => AddTorrent::from_bytes(bytes.to_vec()),
+        };
         let add_opts = AddTorrentOptions {
             output_folder: Some(path.into()),
             ..Default::default()
         };
 
         self.session
-            .add_torrent(AddTorrent::from_url(url), Some(add_opts))
+            .add_torrent(add_torrent, Some(add_opts))
             .await
             .context("add task failed")?;
 
@@ -7,6 +7,7 @@ use anime::{
         repository::client::AnimeSqliteClient,
     },
 };
+use common::infra::{http::base_client_builder, torrent_store::FsTorrentStore};
 use dashmap::DashMap;
 use feed::{
     entity::feeds::Feeds,
@@ -16,22 +17,24 @@ use feed::{
     },
 };
 use regex::Regex;
-use reqwest::{
-    Client,
-    header::{HeaderMap, USER_AGENT},
-};
+use reqwest::Client;
 use resource::{entity::resources::Resources, infra::repository::client::ResourceSqliteClient};
 use sqlx::{
     Pool, Sqlite,
     sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
 };
 use subscription::{
-    entity::{rules::Rules, search_mandates::SearchMandates, sub_animes::SubAnimes},
+    entity::{
+        rule_matchers::RuleMatchers, rules::Rules, search_mandates::SearchMandates,
+        sub_animes::SubAnimes,
+    },
     infra::{
         regex::RegexRuleMatcher,
         repository::client::{RuleSqliteClient, SearchMandateSqliteClient, SubAnimeSqliteClient},
+        structured::StructuredRuleMatcher,
     },
 };
+use tokio::sync::Semaphore;
 use user::{
     entity::users::Users,
     infra::{
@@ -47,6 +50,7 @@ pub struct AuthConfig {
     pub token: String,
     pub expire: Duration,
     pub crypto_secret: String,
+    pub push_token: Option<String>,
 }
 
 #[derive(Clone)]
@@ -75,6 +79,7 @@ pub type LogLevelReloader = Arc<dyn Fn(String) -> Result<(), String> + Send + Sy
 #[derive(Clone)]
 pub struct Caps {
     pub matcher: RegexRuleMatcher,
+    pub structured_matcher: StructuredRuleMatcher,
     pub downloader_manager: Arc<DownloaderManager>,
     pub access_policy: BackoffPolicy,
     pub feed_fetcher: HttpFeedFetcher,
@@ -84,6 +89,10 @@ pub struct Caps {
     pub jwt_decoder: Arc<JwtDecoder>,
     pub log_level_reloader: LogLevelReloader,
     pub crypto_provider: Arc<AesCryptoProvider>,
+    pub torrent_store: Arc<FsTorrentStore>,
+    pub push_token: Option<String>,
+    /// 推送条目的后台种子下载同一时间只处理一批，其余批次排队等待
+    pub push_downloads: Arc<Semaphore>,
 }
 
 #[derive(Clone)]
@@ -121,14 +130,6 @@ impl AppContext {
         data_dir: String,
         log_level_reloader: LogLevelReloader,
     ) -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            USER_AGENT,
-            "yanami/1.0"
-                .parse()
-                .expect("build http client default header failed"),
-        );
-
         let base_db_path = Path::new(&data_dir);
         let db_path = base_db_path.join(db_filename);
 
@@ -145,9 +146,7 @@ impl AppContext {
                 .await
                 .expect("connect db failed"),
             regex_cache: Arc::new(DashMap::new()),
-            http_client: Client::builder()
-                .default_headers(headers)
-                .timeout(Duration::from_secs(30))
+            http_client: base_client_builder()
                 .build()
                 .expect("build http client failed"),
             auth_config,
@@ -174,8 +173,8 @@ impl AppContext {
 
     fn init_repo_and_caps(base: &Base, log_level_reloader: LogLevelReloader) -> (Repo, Caps) {
         let matcher = RegexRuleMatcher::new(base.regex_cache.clone());
+        let structured_matcher = StructuredRuleMatcher::new();
         let downloader_manager = Arc::new(DownloaderManager::new(base.data_dir.clone()));
-        let access_policy = BackoffPolicy::new();
 
         let tmdb_client = Arc::new(TmdbClient::new(&base.tmdb_token, base.http_client.clone()));
         let bgm_client = Arc::new(BgmClient::new(
@@ -195,14 +194,24 @@ impl AppContext {
         let crypto_provider = Arc::new(AesCryptoProvider::new(&base.auth_config.crypto_secret));
 
         let anime_repo = Arc::new(AnimeSqliteClient::new(base.pool.clone()));
-        let rule_repo = Arc::new(RuleSqliteClient::new(base.pool.clone(), matcher.clone()));
+        let rule_repo = Arc::new(RuleSqliteClient::new(
+            base.pool.clone(),
+            matcher.clone(),
+            structured_matcher.clone(),
+        ));
         let sub_anime_repo = Arc::new(SubAnimeSqliteClient::new(base.pool.clone()));
         let user_repo = Arc::new(UserSqliteClient::new(base.pool.clone()));
         let feed_repo = Arc::new(FeedSqliteClient::new(base.pool.clone()));
         let res_repo = Arc::new(ResourceSqliteClient::new(base.pool.clone()));
         let mandate_repo = Arc::new(SearchMandateSqliteClient::new(base.pool.clone()));
 
-        let feed_fetcher = HttpFeedFetcher::new(base.http_client.clone(), res_repo.clone());
+        let torrent_store = Arc::new(FsTorrentStore::new(&base.data_dir));
+        let access_policy = BackoffPolicy::new(feed_repo.clone());
+        let feed_fetcher = HttpFeedFetcher::new(
+            base.http_client.clone(),
+            res_repo.clone(),
+            feed_repo.clone(),
+        );
         (
             Repo {
                 anime_repo,
@@ -215,6 +224,7 @@ impl AppContext {
             },
             Caps {
                 matcher,
+                structured_matcher,
                 downloader_manager,
                 access_policy,
                 feed_fetcher,
@@ -224,6 +234,9 @@ impl AppContext {
                 jwt_decoder,
                 log_level_reloader,
                 crypto_provider,
+                torrent_store,
+                push_token: base.auth_config.push_token.clone(),
+                push_downloads: Arc::new(Semaphore::new(1)),
             },
         )
     }
@@ -231,12 +244,16 @@ impl AppContext {
     fn init_roots(repo: &Repo, caps: &Caps) -> Roots {
         let fetcher = Arc::new(caps.feed_fetcher.clone());
         let access_policy = Arc::new(caps.access_policy.clone());
-        let matcher = Arc::new(caps.matcher.clone());
+        let matcher = RuleMatchers::new(
+            Arc::new(caps.matcher.clone()),
+            Arc::new(caps.structured_matcher.clone()),
+        );
         Roots {
             users: Users::new(
                 repo.user_repo.clone(),
                 caps.downloader_manager.clone(),
                 caps.crypto_provider.clone(),
+                caps.torrent_store.clone(),
             ),
             animes: Animes::new(repo.anime_repo.clone()),
             sub_animes: SubAnimes::new(
@@ -248,12 +265,14 @@ impl AppContext {
                 repo.feed_repo.clone(),
                 fetcher.clone(),
                 access_policy.clone(),
+                caps.crypto_provider.clone(),
             ),
-            resources: Resources::new(repo.res_repo.clone()),
+            resources: Resources::new(repo.res_repo.clone(), caps.torrent_store.clone()),
             search_mandates: SearchMandates::new(
                 repo.mandate_repo.clone(),
                 fetcher.clone(),
                 access_policy.clone(),
+                caps.crypto_provider.clone(),
             ),
             anime_source: AnimeSources::new(caps.bgm_client.clone(), vec![caps.bgm_client.clone()]),
             rules: Rules::new(repo.rule_repo.clone(), matcher.clone()),
@@ -306,6 +325,12 @@ impl AppContext {
 
         tx.commit().await.expect("init database commit failed");
 
+        self.caps
+            .access_policy
+            .restore()
+            .await
+            .expect("restore feed backoff state failed");
+
         self.roots
             .users
             .init_admin_user()
@@ -1,14 +1,26 @@
 use std::sync::Arc;
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
 };
 
+use common::shared::error::Error;
+use feed::entity::model::{FeedPreviewEntry, FeedSchedule, PushedEntries, ResourceKind};
+use futures::stream;
+use subscription::entity::model::{RuleQuery, SubAnimeListQuery, SubAnimeStatus};
+use tracing::error;
+
 use crate::{
     app_ctx::AppContext,
     error::ApiError,
-    model::{ApiResponse, FeedItem, FeedItemRequest},
+    model::{
+        AccessTokenClaims, ApiResponse, FeedImportItem, FeedItem, FeedItemRequest, FeedPreviewItem,
+        FeedPreviewRequest, FeedPreviewResponse, FeedPreviewRule, FeedPreviewSubscription,
+        FeedPushRequest, FeedPushResult,
+    },
 };
 
 /// 创建Feed
@@ -18,7 +30,7 @@ use crate::{
     operation_id = "feed_add",
     tag = "Feed",
     summary = "创建 RSS 订阅源",
-    description = "创建一个新的 RSS 订阅源（Feed）。\n\n- `title` 是必填字段，表示订阅源的名称。\n- `site_url` 和 `search_url` 不能同时为空，至少需要提供一个以便进行订阅更新或搜索。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    description = "创建一个新的 RSS 订阅源（Feed）。\n\n- `title` 是必填字段，表示订阅源的名称。\n- `site_url` 和 `search_url` 不能同时为空，至少需要提供一个以便进行订阅更新或搜索。\n- `search_url` 支持命名占位符：`{keyword}` 为按查询参数编码的关键字，`{keyword_raw}`（或 `{}`）为关键字原文，`{season}`、`{episode}`、`{year}` 为季度、下一集集数及开播年份，数字可用 `{episode:02}` 补零；保存时校验占位符，缺少的值替换为空。\n- `parser` 指定解析器（`default`、`mikan`、`nyaa`、`torznab`），为空时根据地址自动识别，适用于镜像或自建实例。\n- 使用 `torznab` 解析器时可接入 Jackett、Prowlarr 等索引器，`search_url` 可只填写携带 `apikey` 的 API 地址，搜索时自动追加 `t=search&q=` 参数；创建时会查询 `t=caps` 确认支持搜索。\n- `poll_interval` 为轮询间隔（秒），为空时使用默认的 300 秒；`min_spacing` 为对该站点两次请求之间的最小间隔（秒），轮询与搜索共同遵守。\n- `request_options` 可设置代理、额外请求头及 Cookie，用于被封锁的站点或需要鉴权的私有站点，加密保存且同时作用于种子下载。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
     request_body = FeedItemRequest,
     responses(
         (status = 200, description = "创建成功。返回数据的 `data` 字段为创建成功的 `FeedItem` 对象。"),
@@ -41,7 +53,17 @@ pub async fn add(
     let entity = ctx
         .roots
         .feeds
-        .create(req.title, req.site_url, req.search_url)
+        .create(
+            req.title,
+            req.site_url,
+            req.search_url,
+            req.parser,
+            FeedSchedule {
+                poll_interval: req.poll_interval,
+                min_spacing: req.min_spacing,
+            },
+            req.request_options.map(Into::into).unwrap_or_default(),
+        )
         .await?;
 
     Ok(Json(ApiResponse::ok(FeedItem::from(entity))))
@@ -104,6 +126,7 @@ pub async fn delete(
         return Err(ApiError::not_found("not found feed"));
     };
     ctx.roots.feeds.delete(&entity).await?;
+    ctx.roots.resources.remove_feed(feed_id).await?;
     Ok(Json(ApiResponse::ok(())))
 }
 
@@ -114,7 +137,7 @@ pub async fn delete(
     operation_id = "feed_edit",
     tag = "Feed",
     summary = "编辑/更新 RSS 订阅源",
-    description = "根据订阅源 ID (`feed_id`) 更新该 RSS 订阅源的信息。\n\n- 可以更新名称、主页 URL 或搜索 URL。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    description = "根据订阅源 ID (`feed_id`) 更新该 RSS 订阅源的信息。\n\n- 可以更新名称、主页 URL、搜索 URL、解析器及请求频率设置。\n- `request_options` 为空时保留原有的代理、请求头及 Cookie 设置，传入空对象则清除。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
     params(
         ("feed_id" = i64, Path, description = "需要更新的订阅源的唯一 ID")
     ),
@@ -139,7 +162,406 @@ pub async fn edit(
     let Some(mut entity) = ctx.roots.feeds.get(feed_id).await? else {
         return Err(ApiError::not_found("not found feed"));
     };
-    entity.set(req.title, req.site_url, req.search_url).await?;
+    entity
+        .set(
+            req.title,
+            req.site_url,
+            req.search_url,
+            req.parser,
+            FeedSchedule {
+                poll_interval: req.poll_interval,
+                min_spacing: req.min_spacing,
+            },
+            req.request_options.map(Into::into),
+        )
+        .await?;
     ctx.roots.feeds.save(&entity).await?;
     Ok(Json(ApiResponse::ok(())))
 }
+
+/// 试运行Feed
+#[utoipa::path(
+    post,
+    path = "/api/v1/feed/preview",
+    operation_id = "feed_preview",
+    tag = "Feed",
+    summary = "试运行 RSS 订阅源",
+    description = "请求订阅源地址或搜索地址并按完整的解析流程处理，返回每个条目的解析结果，不会写入资源库，也不会触发下载。\n\n- 传入 `feed_id` 时使用已保存的订阅源配置，`keyword` 不为空时请求其搜索地址，否则请求主页地址。\n- 未传入 `feed_id` 时需提供 `url`，可配合 `parser` 与 `request_options` 在保存前调试新的订阅源。\n- 每个条目包含解析出的 info hash、发布时间、是否被识别为合集及命中的标题片段；被跳过的条目给出 `skipped_reason`。\n- `matched_rules` 为当前用户空间中命中标题的规则，`matched_subscriptions` 为会匹配到该条目的已启用订阅。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    request_body = FeedPreviewRequest,
+    responses(
+        (status = 200, description = "试运行成功。返回数据的 `data` 字段为 `FeedPreviewResponse` 对象。"),
+        (status = 400, description = "请求参数校验失败：`feed_id` 与 `url` 同时为空，或 JSON 格式错误"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：需要管理员权限"),
+        (status = 404, description = "资源不存在：找不到指定的订阅源"),
+        (status = 502, description = "请求订阅源失败"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn preview(
+    State(ctx): State<Arc<AppContext>>,
+    Extension(user): Extension<AccessTokenClaims>,
+    Json(req): Json<FeedPreviewRequest>,
+) -> Result<Json<ApiResponse<FeedPreviewResponse>>, ApiError> {
+    let Some(user_entity) = ctx.roots.users.get(user.user_id).await? else {
+        return Err(ApiError::forbidden("not found user"));
+    };
+    let keyword = req.keyword.as_deref().filter(|k| !k.trim().is_empty());
+    let preview = match (req.feed_id, req.url.as_deref()) {
+        (Some(feed_id), _) => {
+            let Some(entity) = ctx.roots.feeds.get(feed_id).await? else {
+                return Err(ApiError::not_found("not found feed"));
+            };
+            entity.preview(keyword).await?
+        }
+        (None, Some(url)) => {
+            ctx.roots
+                .feeds
+                .preview(
+                    url,
+                    req.parser.as_deref(),
+                    &req.request_options.map(Into::into).unwrap_or_default(),
+                )
+                .await?
+        }
+        (None, None) => return Err(ApiError::invalid_request()),
+    };
+
+    let parsed = preview
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            FeedPreviewEntry::Parsed { item, .. } => Some(item.clone()),
+            FeedPreviewEntry::Skipped { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    let resources = ctx.roots.resources.preview(parsed);
+
+    let space_id = user_entity.space_id();
+    let rules = ctx
+        .roots
+        .rules
+        .list(&RuleQuery {
+            space_id: Some(space_id),
+            active: Some(true),
+        })
+        .await?;
+    let sub_animes = ctx
+        .roots
+        .sub_animes
+        .list(&SubAnimeListQuery {
+            anime_id: None,
+            space_id: Some(space_id),
+            search_status: None,
+            sub_status: Some(SubAnimeStatus::Enable),
+            limit: None,
+        })
+        .await?;
+    let mut matchers = Vec::with_capacity(sub_animes.len());
+    for sub_anime in &sub_animes {
+        match ctx.roots.sub_animes.as_matcher(sub_anime).await {
+            Ok(matcher) => matchers.push((sub_anime, matcher)),
+            Err(e) => tracing::warn!("sub anime {} as matcher failed: {}", sub_anime.id(), e),
+        }
+    }
+    for resource in &resources {
+        for (sub_anime, matcher) in &mut matchers {
+            if let Err(e) = matcher.match_resource(resource) {
+                tracing::warn!("sub anime {} match resource failed: {}", sub_anime.id(), e);
+            }
+        }
+    }
+
+    let mut resources = resources.into_iter();
+    let items = preview
+        .entries
+        .into_iter()
+        .map(|entry| match entry {
+            FeedPreviewEntry::Parsed {
+                item,
+                collection_marker,
+            } => {
+                let resource = resources.next().expect("preview resource per parsed item");
+                let matched_rules = rules
+                    .iter()
+                    .filter(|r| r.is_match(&resource))
+                    .map(|r| FeedPreviewRule {
+                        id: r.id(),
+                        name: r.name().to_string(),
+                    })
+                    .collect();
+                let matched_subscriptions = matchers
+                    .iter()
+                    .filter(|(_, m)| m.matched_resource_ids().any(|id| id == resource.id()))
+                    .map(|(sub_anime, m)| FeedPreviewSubscription {
+                        sub_anime_id: sub_anime.id(),
+                        anime_id: sub_anime.anime_id(),
+                        rule_id: m.get_rule_id(),
+                    })
+                    .collect();
+                let (ep_start, ep_end) = resource.episode_range().unzip();
+                FeedPreviewItem {
+                    title: Some(item.title),
+                    source_url: Some(item.source_url),
+                    resource_url: Some(item.resource_url),
+                    info_hash: Some(hex::encode(item.info_hash)),
+                    published_at: Some(item.published_at),
+                    is_collection: resource.is_collection(),
+                    collection_marker,
+                    ep_start,
+                    ep_end,
+                    total_size: resource.total_size(),
+                    skipped_reason: None,
+                    matched_rules,
+                    matched_subscriptions,
+                }
+            }
+            FeedPreviewEntry::Skipped {
+                title,
+                source_url,
+                reason,
+            } => FeedPreviewItem {
+                title,
+                source_url,
+                resource_url: None,
+                info_hash: None,
+                published_at: None,
+                is_collection: false,
+                collection_marker: None,
+                ep_start: None,
+                ep_end: None,
+                total_size: None,
+                skipped_reason: Some(reason),
+                matched_rules: vec![],
+                matched_subscriptions: vec![],
+            },
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(FeedPreviewResponse {
+        source_key: preview.source_key,
+        items,
+    })))
+}
+
+/// 导出OPML
+#[utoipa::path(
+    get,
+    path = "/api/v1/feed/opml",
+    operation_id = "feed_export_opml",
+    tag = "Feed",
+    summary = "导出订阅源为 OPML",
+    description = "将所有 RSS 订阅源导出为 OPML 2.0 文档，用于在实例之间迁移订阅源。\n\n- 每个订阅源对应一个 `outline`，`xmlUrl` 为主页 URL，搜索 URL、解析器及请求频率保存在 `searchUrl`、`parser`、`pollInterval`、`minSpacing` 扩展属性中。\n- 代理、请求头及 Cookie 可能包含凭据，不会被导出。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    responses(
+        (status = 200, description = "导出成功。响应体为 OPML 文档。", content_type = "text/x-opml", body = String),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：需要管理员权限"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn export_opml(
+    State(ctx): State<Arc<AppContext>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let content = ctx.roots.feeds.export_opml().await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/x-opml; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"feeds.opml\"",
+            ),
+        ],
+        content,
+    ))
+}
+
+/// 导入OPML
+#[utoipa::path(
+    post,
+    path = "/api/v1/feed/opml",
+    operation_id = "feed_import_opml",
+    tag = "Feed",
+    summary = "从 OPML 导入订阅源",
+    description = "请求体为 OPML 文档，逐条创建其中携带 `xmlUrl` 或 `searchUrl` 的订阅源。\n\n- 每个条目与创建接口一样经过校验（请求地址并确认站点一致），失败的条目不影响其他条目。\n- 主页 URL 或搜索 URL 与已有订阅源相同的条目被跳过并标记为失败。\n- 返回每个条目的导入结果，成功时 `feed_id` 为新建的订阅源 ID，失败时 `error` 为原因。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    request_body(content = String, content_type = "text/x-opml", description = "OPML 文档"),
+    responses(
+        (status = 200, description = "导入完成。返回数据的 `data` 字段为 `[FeedImportItem]` 数组。"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：需要管理员权限"),
+        (status = 409, description = "OPML 文档格式错误"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn import_opml(
+    State(ctx): State<Arc<AppContext>>,
+    body: String,
+) -> Result<Json<ApiResponse<Vec<FeedImportItem>>>, ApiError> {
+    let results = ctx.roots.feeds.import_opml(&body).await?;
+    Ok(Json(ApiResponse::ok(
+        results.into_iter().map(FeedImportItem::from).collect(),
+    )))
+}
+
+/// 单次推送的条目数量上限
+const MAX_PUSH_ITEMS: usize = 100;
+
+/// 推送资源
+#[utoipa::path(
+    post,
+    path = "/api/v1/feed/{feed_id}/push",
+    operation_id = "feed_push",
+    tag = "Feed",
+    summary = "向订阅源推送资源",
+    description = "供外部爬虫或机器人批量推送资源，条目按与轮询订阅源相同的流程解析、入库并立即触发匹配。\n\n- 单次最多推送 100 个条目，超出时返回 400。\n- 每个条目需提供标题及磁力链接或种子地址，可选提供发布时间与 info hash；磁力链接中的哈希与 `info_hash` 合并。\n- 只提供种子地址且未提供哈希的条目需下载种子计算哈希，这些条目在后台异步下载、入库并匹配，结果中标记为 `queued`，其入库及匹配结果不在本次响应中返回；后台同一时间只处理一批推送，其余批次排队等待。\n- 合集按标题识别，只会匹配开启了接受合集的订阅。\n- 资源记录为来自 `feed_id` 对应的订阅源。\n- 新入库的资源与所有已启用且未处于搜索状态的订阅进行匹配，已存在的资源只更新来源记录。\n- 返回每个条目的处理结果，后台处理的条目排在其后，被跳过的条目给出 `skipped_reason` 并排在最后。\n\n调用此接口需要在请求头中携带配置的推送令牌（`auth.push_token`），或管理员的 JWT Token。",
+    params(
+        ("feed_id" = i64, Path, description = "资源所属的订阅源 ID")
+    ),
+    request_body = FeedPushRequest,
+    responses(
+        (status = 200, description = "推送成功。返回数据的 `data` 字段为 `[FeedPushResult]` 数组。"),
+        (status = 400, description = "请求参数校验失败：条目数量超出上限，或 JSON 格式错误"),
+        (status = 401, description = "未授权：未提供令牌，或令牌已过期/无效"),
+        (status = 403, description = "禁止访问：需要推送令牌或管理员权限"),
+        (status = 404, description = "资源不存在：找不到指定的订阅源"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("push_token" = []),
+        ("jwt" = [])
+    )
+)]
+pub async fn push(
+    State(ctx): State<Arc<AppContext>>,
+    Path(feed_id): Path<i64>,
+    Json(req): Json<FeedPushRequest>,
+) -> Result<Json<ApiResponse<Vec<FeedPushResult>>>, ApiError> {
+    if req.items.len() > MAX_PUSH_ITEMS {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            400,
+            format!("at most {} items per push", MAX_PUSH_ITEMS),
+        ));
+    }
+    if ctx.roots.feeds.get(feed_id).await?.is_none() {
+        return Err(ApiError::not_found("not found feed"));
+    }
+    let PushedEntries { entries, pending } = ctx
+        .roots
+        .feeds
+        .resolve_pushed(req.items.into_iter().map(Into::into).collect())
+        .await;
+
+    let mut items = ingest_pushed(&ctx, feed_id, entries).await?;
+    if !pending.is_empty() {
+        let queued = pending.iter().map(|item| FeedPushResult {
+            title: Some(item.title.clone()),
+            source_url: item.source_url.clone(),
+            info_hash: None,
+            is_collection: false,
+            created: false,
+            queued: true,
+            skipped_reason: None,
+            matched_subscriptions: vec![],
+        });
+        let at = items
+            .iter()
+            .position(|i| i.skipped_reason.is_some())
+            .unwrap_or(items.len());
+        items.splice(at..at, queued);
+
+        // 下载种子耗时较长，放到后台处理，结果与轮询入库的资源一样可在订阅中查看
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = ctx.caps.push_downloads.acquire().await else {
+                return;
+            };
+            let entries = ctx.roots.feeds.download_pushed(pending).await;
+            if let Err(e) = ingest_pushed(&ctx, feed_id, entries).await {
+                error!("feed {} ingest pushed torrents failed, {}", feed_id, e);
+            }
+        });
+    }
+    Ok(Json(ApiResponse::ok(items)))
+}
+
+/// 保存推送的条目，并将新入库的资源与订阅匹配
+async fn ingest_pushed(
+    ctx: &AppContext,
+    feed_id: i64,
+    entries: Vec<FeedPreviewEntry>,
+) -> Result<Vec<FeedPushResult>, Error> {
+    let parsed = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            FeedPreviewEntry::Parsed { item, .. } => Some(item.clone()),
+            FeedPreviewEntry::Skipped { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    let created = if parsed.is_empty() {
+        vec![]
+    } else {
+        ctx.roots.resources.save(feed_id, parsed).await?
+    };
+
+    // 与轮询订阅源后的匹配一致，只匹配新入库的资源
+    let matchers = if created.is_empty() {
+        vec![]
+    } else {
+        ctx.roots
+            .sub_animes
+            .match_active(stream::iter(created.iter().cloned().map(Ok)))
+            .await?
+    };
+
+    let items = entries
+        .into_iter()
+        .map(|entry| match entry {
+            FeedPreviewEntry::Parsed { item, .. } => {
+                let resource = created.iter().find(|r| r.id() == &item.info_hash);
+                let matched_subscriptions = matchers
+                    .iter()
+                    .filter(|(_, m)| m.matched_resource_ids().any(|id| id == &item.info_hash))
+                    .map(|(sub_anime, m)| FeedPreviewSubscription {
+                        sub_anime_id: m.sub_anime_id(),
+                        anime_id: sub_anime.anime_id(),
+                        rule_id: m.get_rule_id(),
+                    })
+                    .collect();
+                FeedPushResult {
+                    title: Some(item.title),
+                    source_url: Some(item.source_url),
+                    info_hash: Some(hex::encode(item.info_hash)),
+                    is_collection: item.kind == ResourceKind::Collection,
+                    created: resource.is_some(),
+                    queued: false,
+                    skipped_reason: None,
+                    matched_subscriptions,
+                }
+            }
+            FeedPreviewEntry::Skipped {
+                title,
+                source_url,
+                reason,
+            } => FeedPushResult {
+                title,
+                source_url,
+                info_hash: None,
+                is_collection: false,
+                created: false,
+                queued: false,
+                skipped_reason: Some(reason),
+                matched_subscriptions: vec![],
+            },
+        })
+        .collect();
+    Ok(items)
+}
@@ -8,9 +8,22 @@ use axum::{
 use crate::{
     app_ctx::AppContext,
     error::ApiError,
-    model::{AccessTokenClaims, ApiResponse, RuleCreateRequest, RuleItem, RuleUpdateOrderRequest},
+    model::{
+        AccessTokenClaims, ApiResponse, RuleCreateRequest, RuleItem, RuleKindItem, RuleTestItem,
+        RuleTestRequest, RuleTestResponse, RuleTestSubscription, RuleTestWinner,
+        RuleUpdateOrderRequest, StructuredRuleItem,
+    },
 };
-use subscription::entity::model::RuleQuery;
+use resource::entity::model::ResourceQuery;
+use subscription::entity::{
+    model::{RuleDefinition, RuleQuery, SubAnimeListQuery, SubAnimeStatus},
+    sub_anime_entity::SubAnimeEntityMatcher,
+};
+
+/// 规则试运行默认检索的资源数量
+const RULE_TEST_DEFAULT_LIMIT: i32 = 500;
+/// 规则试运行最多检索的资源数量
+const RULE_TEST_MAX_LIMIT: i32 = 5000;
 
 /// 创建规则
 #[utoipa::path(
@@ -19,7 +32,7 @@ use subscription::entity::model::RuleQuery;
     operation_id = "rule_add",
     tag = "Rule",
     summary = "创建规则",
-    description = "创建一条新的规则。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    description = "创建一条新的规则。\n\n- `kind` 为 `regex`（默认）时使用 `pattern` 正则表达式匹配资源标题。\n- `kind` 为 `structured` 时使用 `conditions` 中的条件：须包含的关键字、须排除的关键字、发布组、分辨率、字幕语言及资源大小范围，所有非空条件同时满足时命中。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
     request_body = RuleCreateRequest,
     responses(
         (status = 200, description = "创建成功。返回创建的 `RuleItem` 对象。"),
@@ -40,14 +53,35 @@ pub async fn add(
         return Err(ApiError::forbidden("not found user"));
     };
 
+    let definition = rule_definition(req.kind, req.pattern, req.conditions)?;
     let entity = ctx
         .roots
         .rules
-        .create(&req.name, user_entity.space_id(), &req.pattern, req.order)
+        .create(&req.name, user_entity.space_id(), definition, req.order)
         .await?;
     Ok(Json(ApiResponse::ok(RuleItem::from(entity))))
 }
 
+fn rule_definition(
+    kind: Option<RuleKindItem>,
+    pattern: Option<String>,
+    conditions: Option<StructuredRuleItem>,
+) -> Result<RuleDefinition, ApiError> {
+    Ok(match kind.unwrap_or_default() {
+        RuleKindItem::Regex => {
+            RuleDefinition::Regex(pattern.ok_or_else(ApiError::invalid_request)?)
+        }
+        RuleKindItem::Structured => {
+            let conditions = conditions.ok_or_else(ApiError::invalid_request)?;
+            RuleDefinition::Structured(
+                conditions
+                    .try_into()
+                    .map_err(|_| ApiError::invalid_request())?,
+            )
+        }
+    })
+}
+
 /// 获取规则列表
 #[utoipa::path(
     get,
@@ -172,3 +206,265 @@ pub async fn delete(
     ctx.roots.rules.delete(&entity).await?;
     Ok(Json(ApiResponse::ok(())))
 }
+
+/// 试运行规则
+#[utoipa::path(
+    post,
+    path = "/api/v1/rule/test",
+    operation_id = "rule_test",
+    tag = "Rule",
+    summary = "试运行规则",
+    description = "使用已保存或未保存的规则匹配资源库中已有的资源，不会写入任何数据。\n\n- 传入 `rule_id` 时测试已保存的规则，否则按 `kind`、`pattern`、`conditions` 构造规则，`order` 为空时视为优先级最低。\n- 传入 `sub_anime_id` 时只检索标题包含该番剧标题、且处于订阅匹配时间范围内的资源，否则按 `start_at`、`end_at` 检索最近的资源，数量由 `limit` 限制。\n- 返回规则命中的资源，`subscriptions` 为标题命中番剧关键字的已启用订阅（传入 `sub_anime_id` 时只包含该订阅），`winning_rule` 为空间内优先级更高且同样命中的规则，未绑定规则的订阅会先选中该规则。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    request_body = RuleTestRequest,
+    responses(
+        (status = 200, description = "试运行成功。返回数据的 `data` 字段为 `RuleTestResponse` 对象。"),
+        (status = 400, description = "请求参数校验失败"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：规则或订阅不属于当前用户空间"),
+        (status = 404, description = "规则或订阅不存在"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn test(
+    State(ctx): State<Arc<AppContext>>,
+    Extension(user): Extension<AccessTokenClaims>,
+    Json(req): Json<RuleTestRequest>,
+) -> Result<Json<ApiResponse<RuleTestResponse>>, ApiError> {
+    let Some(user_entity) = ctx.roots.users.get(user.user_id).await? else {
+        return Err(ApiError::forbidden("not found user"));
+    };
+    let space_id = user_entity.space_id();
+
+    let rule = match req.rule_id {
+        Some(rule_id) => {
+            let Some(entity) = ctx.roots.rules.find(rule_id).await? else {
+                return Err(ApiError::not_found("not found rule"));
+            };
+            if entity.space_id() != space_id {
+                return Err(ApiError::forbidden("not your rule"));
+            }
+            entity
+        }
+        None => ctx.roots.rules.preview(
+            "test",
+            space_id,
+            rule_definition(req.kind, req.pattern, req.conditions)?,
+            req.order.unwrap_or(i64::MAX),
+        )?,
+    };
+
+    let sub_animes = match req.sub_anime_id {
+        Some(id) => {
+            let Some(entity) = ctx.roots.sub_animes.find_by_sub_anime_id(id).await? else {
+                return Err(ApiError::not_found("not found sub anime"));
+            };
+            if entity.space_id() != space_id {
+                return Err(ApiError::forbidden("not your sub anime"));
+            }
+            vec![entity]
+        }
+        None => {
+            ctx.roots
+                .sub_animes
+                .list(&SubAnimeListQuery {
+                    anime_id: None,
+                    space_id: Some(space_id),
+                    search_status: None,
+                    sub_status: Some(SubAnimeStatus::Enable),
+                    limit: None,
+                })
+                .await?
+        }
+    };
+
+    let limit = Some(
+        req.limit
+            .unwrap_or(RULE_TEST_DEFAULT_LIMIT)
+            .clamp(1, RULE_TEST_MAX_LIMIT),
+    );
+    let query =
+        match (req.sub_anime_id, sub_animes.first()) {
+            (Some(_), Some(sub_anime)) => {
+                let time_range = sub_anime.match_time_range();
+                ResourceQuery {
+                    keywords: None,
+                    any_titles: Some(sub_anime.titles().to_vec()),
+                    start_at: req
+                        .start_at
+                        .or(Some(time_range.start.and_utc().timestamp())),
+                    // 与订阅匹配一致，接受合集时不限制结束时间
+                    end_at: req.end_at.or((!sub_anime.accept_collection())
+                        .then(|| time_range.end.and_utc().timestamp())),
+                    limit,
+                    offset: None,
+                }
+            }
+            _ => ResourceQuery {
+                keywords: None,
+                any_titles: None,
+                start_at: req.start_at,
+                end_at: req.end_at,
+                limit,
+                offset: None,
+            },
+        };
+    let resources = ctx.roots.resources.list(&query).await?;
+
+    // 与空间规则一致按优先级及 ID 排列，只保留排在被测规则之前的规则，
+    // 未保存的规则排在同优先级的已保存规则之后
+    let rank = (rule.order(), req.rule_id.unwrap_or(i64::MAX));
+    let mut higher_rules = ctx
+        .roots
+        .rules
+        .list(&RuleQuery {
+            space_id: Some(space_id),
+            active: Some(true),
+        })
+        .await?
+        .into_iter()
+        .filter(|r| (r.order(), r.id()) < rank)
+        .collect::<Vec<_>>();
+    higher_rules.sort_by_key(|r| (r.order(), r.id()));
+
+    let items = resources
+        .iter()
+        .filter(|res| rule.is_match(res))
+        .map(|res| RuleTestItem {
+            title: res.title().to_string(),
+            info_hash: hex::encode(res.id()),
+            published_at: res.published_at(),
+            subscriptions: sub_animes
+                .iter()
+                .filter(|s| s.is_keyword_match(res))
+                .map(|s| RuleTestSubscription {
+                    sub_anime_id: s.id(),
+                    anime_id: s.anime_id(),
+                    rule_id: s.get_rule_id(),
+                })
+                .collect(),
+            winning_rule: higher_rules
+                .iter()
+                .find(|r| r.is_match(res))
+                .map(|r| RuleTestWinner {
+                    id: r.id(),
+                    name: r.name().to_string(),
+                    order: r.order(),
+                }),
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(RuleTestResponse {
+        scanned: resources.len(),
+        items,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use feed::entity::model::{FeedItem, ResourceKind};
+    use user::entity::model::UserRole;
+
+    use super::*;
+    use crate::app_ctx::AuthConfig;
+
+    async fn setup(dir: &tempfile::TempDir) -> (Arc<AppContext>, AccessTokenClaims) {
+        let ctx = AppContext::new(
+            "test.db",
+            AuthConfig {
+                token: "secret".to_string(),
+                expire: Duration::from_secs(3600),
+                crypto_secret: "secret".to_string(),
+                push_token: None,
+            },
+            String::new(),
+            dir.path().to_str().unwrap().to_string(),
+            Arc::new(|_| Ok(())),
+        )
+        .await;
+        ctx.init_database().await;
+        let claims = AccessTokenClaims {
+            user_id: 1,
+            exp: usize::MAX,
+            character: UserRole::Admin,
+        };
+        ctx.roots
+            .resources
+            .save(
+                1,
+                vec![FeedItem {
+                    title: "[Group] Title - 01 [1080p]".to_string(),
+                    source_url: "https://example.com/view/1".to_string(),
+                    resource_url: "https://example.com/1.torrent".to_string(),
+                    published_at: 1,
+                    info_hash: [1; 20],
+                    info_hash_v2: None,
+                    kind: ResourceKind::Episode,
+                    episode_range: None,
+                    files: vec![],
+                    size: None,
+                    torrent: None,
+                }],
+            )
+            .await
+            .unwrap();
+        (Arc::new(ctx), claims)
+    }
+
+    async fn create_rule(ctx: &AppContext, name: &str, order: i64) -> i64 {
+        let space_id = ctx.roots.users.get(1).await.unwrap().unwrap().space_id();
+        ctx.roots
+            .rules
+            .create(
+                name,
+                space_id,
+                RuleDefinition::Regex(".*1080.*".to_string()),
+                order,
+            )
+            .await
+            .unwrap()
+            .id()
+    }
+
+    async fn winning_rule(
+        ctx: &Arc<AppContext>,
+        claims: &AccessTokenClaims,
+        rule_id: Option<i64>,
+    ) -> Option<i64> {
+        let req = RuleTestRequest {
+            rule_id,
+            kind: None,
+            pattern: Some(".*1080.*".to_string()),
+            conditions: None,
+            order: Some(10),
+            sub_anime_id: None,
+            start_at: None,
+            end_at: None,
+            limit: None,
+        };
+        let Json(res) = test(State(ctx.clone()), Extension(claims.clone()), Json(req))
+            .await
+            .unwrap();
+        let data = res.data;
+        assert_eq!(data.items.len(), 1);
+        data.items[0].winning_rule.as_ref().map(|r| r.id)
+    }
+
+    #[tokio::test]
+    async fn winning_rule_follows_space_rule_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ctx, claims) = setup(&dir).await;
+        let first = create_rule(&ctx, "first", 10).await;
+        let second = create_rule(&ctx, "second", 10).await;
+
+        // 同优先级时空间规则选中先保存的规则
+        assert_eq!(winning_rule(&ctx, &claims, Some(first)).await, None);
+        assert_eq!(winning_rule(&ctx, &claims, Some(second)).await, Some(first));
+        // 未保存的规则排在同优先级的已保存规则之后
+        assert_eq!(winning_rule(&ctx, &claims, None).await, Some(first));
+    }
+}
@@ -2,7 +2,8 @@ use crate::{
     app_ctx::AppContext,
     error::ApiError,
     model::{
-        AccessTokenClaims, ApiResponse, BindRuleRequest, CreateSubscriptionRequest, EpisodeItem,
+        AcceptCollectionRequest, AccessTokenClaims, ApiResponse, BindRuleRequest,
+        CreateSubscriptionRequest, EpisodeItem, EpisodeNumberingRequest, MatchExplainResponse,
         RecentEpisodeQuery, RecentEpisodeResponse, SearchStatusRequest,
     },
 };
@@ -10,6 +11,7 @@ use axum::{
     Extension, Json,
     extract::{Path, Query, State},
 };
+use common::shared::info_hash::InfoHash;
 use std::sync::Arc;
 
 /// 创建订阅
@@ -236,6 +238,110 @@ pub async fn set_search_status(
     Ok(Json(ApiResponse::ok(())))
 }
 
+/// 设置是否接受合集资源
+#[utoipa::path(
+    post,
+    path = "/api/v1/subscription/{id}/accept_collection",
+    operation_id = "subscription_set_accept_collection",
+    tag = "Subscription",
+    summary = "设置订阅是否接受合集资源",
+    description = "开启后，匹配时允许使用合集/打包资源（如 `[01-12]`、`Batch`、`合集`）一次性补全缺失剧集，适用于已完结的番剧。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    params(
+        ("id" = i64, Path, description = "订阅记录的唯一 ID")
+    ),
+    request_body = AcceptCollectionRequest,
+    responses(
+        (status = 200, description = "操作成功。返回数据的 `data` 字段为空。"),
+        (status = 400, description = "请求参数校验失败"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：Token 鉴权通过但系统中找不到该对应的用户记录或越权操作"),
+        (status = 404, description = "资源不存在：未找到该订阅记录"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn set_accept_collection(
+    State(ctx): State<Arc<AppContext>>,
+    Extension(user): Extension<AccessTokenClaims>,
+    Path(id): Path<i64>,
+    Json(req): Json<AcceptCollectionRequest>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let Some(user_entity) = ctx.roots.users.get(user.user_id).await? else {
+        return Err(ApiError::forbidden("not found user"));
+    };
+
+    let Some(mut entity) = ctx.roots.sub_animes.find_by_sub_anime_id(id).await? else {
+        return Err(ApiError::not_found("not found subscription"));
+    };
+
+    if entity.space_id() != user_entity.space_id() {
+        return Err(ApiError::forbidden("forbidden"));
+    }
+
+    if entity.set_accept_collection(req.enable) {
+        ctx.roots.sub_animes.save(&entity).await?;
+    }
+
+    Ok(Json(ApiResponse::ok(())))
+}
+
+/// 设置订阅的集数编号方式
+#[utoipa::path(
+    post,
+    path = "/api/v1/subscription/{id}/numbering",
+    operation_id = "subscription_set_numbering",
+    tag = "Subscription",
+    summary = "设置订阅的集数编号方式",
+    description = "设置资源标题中集数的编号方式及偏移量，用于分割放送或连续播出的番剧（如第二季资源标注为第 13 集）。\n\n- `relative`：按本季从 1 开始编号，忽略偏移量。\n- `absolute`：按全系列累计编号，集数大于偏移量时减去偏移量。\n- `auto`：仅在集数超过本季总集数时按累计编号处理。\n\n编号方式只影响集数换算，各编号方式的下载目录均为番剧名下的季度目录（如 `S02`）。\n\n修改后立即按新的编号方式重新计算已有剧集的集数及进度。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    params(
+        ("id" = i64, Path, description = "订阅记录的唯一 ID")
+    ),
+    request_body = EpisodeNumberingRequest,
+    responses(
+        (status = 200, description = "操作成功。返回数据的 `data` 字段为空。"),
+        (status = 400, description = "请求参数校验失败"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：Token 鉴权通过但系统中找不到该对应的用户记录或越权操作"),
+        (status = 404, description = "资源不存在：未找到该订阅记录"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn set_numbering(
+    State(ctx): State<Arc<AppContext>>,
+    Extension(user): Extension<AccessTokenClaims>,
+    Path(id): Path<i64>,
+    Json(req): Json<EpisodeNumberingRequest>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let Some(user_entity) = ctx.roots.users.get(user.user_id).await? else {
+        return Err(ApiError::forbidden("not found user"));
+    };
+
+    let Some(mut entity) = ctx.roots.sub_animes.find_by_sub_anime_id(id).await? else {
+        return Err(ApiError::not_found("not found subscription"));
+    };
+
+    if entity.space_id() != user_entity.space_id() {
+        return Err(ApiError::forbidden("forbidden"));
+    }
+
+    if entity.set_numbering(req.numbering.into(), req.episode_offset) {
+        ctx.roots.sub_animes.save(&entity).await?;
+        ctx.roots
+            .sub_animes
+            .as_eps(&entity)
+            .await
+            .renumber()
+            .await?;
+    }
+
+    Ok(Json(ApiResponse::ok(())))
+}
+
 /// 手动绑定规则
 #[utoipa::path(
     post,
@@ -390,3 +496,70 @@ pub async fn update_ep_status(
 
     Ok(Json(ApiResponse::ok(())))
 }
+
+/// 解释订阅对资源的匹配过程
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscription/{id}/explain/{info_hash}",
+    operation_id = "subscription_explain_match",
+    tag = "Subscription",
+    summary = "解释资源匹配过程",
+    description = "使用与订阅匹配相同的流程检查资源库中的指定资源，返回每一步的检查结果及最终结论，用于排查资源未被订阅选中的原因，不会写入任何数据。\n\n- 检查依次为：合集是否被接受、发布时间是否处于匹配时间窗口、规则是否命中（未绑定规则时使用空间内优先级最高的命中规则）、标题是否命中订阅关键字、与候选规则的比较。\n- 检查单独进行，不包含同一轮匹配中其他资源确定的候选规则。\n- 资源已是该订阅的剧集时返回 `episode_id`。\n\n调用此接口需要在请求头中携带有效的 JWT Token。",
+    params(
+        ("id" = i64, Path, description = "订阅记录的唯一 ID"),
+        ("info_hash" = String, Path, description = "资源的 info hash（40 位 v1 或 64 位 v2 十六进制）")
+    ),
+    responses(
+        (status = 200, description = "获取成功。返回数据的 `data` 字段为 `MatchExplainResponse` 对象。"),
+        (status = 400, description = "请求参数校验失败：info hash 格式错误"),
+        (status = 401, description = "未授权：未提供 Token，或 Token 已过期/无效"),
+        (status = 403, description = "禁止访问：找不到该对应的用户记录或越权操作"),
+        (status = 404, description = "资源不存在：未找到该订阅、资源，或订阅未绑定规则且空间内没有启用的规则"),
+        (status = 500, description = "服务器内部错误"),
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn explain_match(
+    State(ctx): State<Arc<AppContext>>,
+    Extension(user): Extension<AccessTokenClaims>,
+    Path((id, info_hash)): Path<(i64, String)>,
+) -> Result<Json<ApiResponse<MatchExplainResponse>>, ApiError> {
+    let Some(user_entity) = ctx.roots.users.get(user.user_id).await? else {
+        return Err(ApiError::forbidden("not found user"));
+    };
+
+    let Some(sub_anime_entity) = ctx.roots.sub_animes.find_by_sub_anime_id(id).await? else {
+        return Err(ApiError::not_found("not found subscription"));
+    };
+
+    if sub_anime_entity.space_id() != user_entity.space_id() {
+        return Err(ApiError::forbidden("forbidden"));
+    }
+
+    let resource_id = InfoHash::from_hex(&info_hash)
+        .and_then(|h| h.id())
+        .ok_or_else(ApiError::invalid_request)?;
+    let Some(resource) = ctx.roots.resources.find(&resource_id).await? else {
+        return Err(ApiError::not_found("not found resource"));
+    };
+
+    let episode_id = ctx
+        .roots
+        .sub_animes
+        .as_eps(&sub_anime_entity)
+        .await
+        .list()
+        .await?
+        .into_iter()
+        .find(|ep| ep.resource_id() == resource.id())
+        .map(|ep| ep.id());
+
+    let mut matcher = ctx.roots.sub_animes.as_matcher(&sub_anime_entity).await?;
+    let trace = matcher.trace_resource(&resource);
+
+    Ok(Json(ApiResponse::ok(MatchExplainResponse::new(
+        id, &resource, episode_id, trace,
+    ))))
+}
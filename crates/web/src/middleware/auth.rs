@@ -62,6 +62,38 @@ pub async fn require_auth(
     Ok(next.run(request).await)
 }
 
+/// 推送接口认证中间件，接受配置的推送令牌或管理员的 JWT Token。
+pub async fn require_push_auth(
+    State(ctx): State<Arc<AppContext>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let header = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| ApiError::unauthorized("missing authorization header"))?;
+    let token = header
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| ApiError::unauthorized("invalid authorization scheme"))?;
+    if let Some(push_token) = ctx.caps.push_token.as_deref()
+        && !push_token.is_empty()
+        && constant_time_eq(push_token.as_bytes(), token.as_bytes())
+    {
+        return Ok(next.run(request).await);
+    }
+    let user = ctx.caps.jwt_decoder.decode_token(token)?;
+    if user.character != UserRole::Admin {
+        return Err(ApiError::forbidden("require push token or admin privilege"));
+    }
+    Ok(next.run(request).await)
+}
+
+/// 比较令牌时不因首个不同字节提前返回，避免通过响应时间猜测令牌。
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 pub async fn require_admin(
     Extension(user): Extension<AccessTokenClaims>,
     request: Request<axum::body::Body>,
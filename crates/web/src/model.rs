@@ -2,11 +2,20 @@ use anime::entity::model::{
     AnimeAirWeekday, AnimeEpisode, AnimeEx, AnimeIdType, AnimeLangTarget, AnimeMetadata,
     AnimeSearchResult, AnimeSeason, AnimeSourceTarget, AnimeTitle,
 };
+use std::collections::BTreeMap;
+
 use chrono::NaiveDate;
-use feed::entity::feed_entity::FeedEntity;
+use feed::entity::{
+    feed_entity::FeedEntity,
+    model::{FeedImportResult, FeedRequestOptions, PushedItem},
+};
+use resource::entity::{model::SubtitleLang, resource_entity::ResourceEntity};
 use serde::{Deserialize, Serialize};
 use subscription::entity::episode_entity::EpsiodeEntity;
-use subscription::entity::rule_entity::RuleEntity;
+use subscription::entity::{
+    model::{EpisodeNumbering, MatchDecision, MatchStep, MatchTrace, RuleKind, StructuredRule},
+    rule_entity::RuleEntity,
+};
 use user::entity::model::{
     DefaultDownloaderConfig, DownloadConfig, DownloaderConfig, QbitConfig, UserRole,
 };
@@ -109,6 +118,153 @@ pub struct BindRuleRequest {
     pub rule_id: i64,
 }
 
+/// 订阅对单个资源的匹配过程
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MatchExplainResponse {
+    /// 订阅 ID
+    pub sub_anime_id: i64,
+    /// 资源标题
+    #[schema(example = "[Group] Title - 01 [1080p]")]
+    pub title: String,
+    /// info hash（十六进制）
+    pub info_hash: String,
+    /// 发布时间戳（秒）
+    pub published_at: i64,
+    /// 资源已是该订阅的剧集时为剧集 ID
+    pub episode_id: Option<i64>,
+    /// 按检查顺序排列的步骤
+    pub steps: Vec<MatchStepItem>,
+    /// 最终结论
+    pub decision: MatchDecisionItem,
+}
+
+impl MatchExplainResponse {
+    pub fn new(
+        sub_anime_id: i64,
+        resource: &ResourceEntity,
+        episode_id: Option<i64>,
+        trace: MatchTrace,
+    ) -> Self {
+        Self {
+            sub_anime_id,
+            title: resource.title().to_string(),
+            info_hash: hex::encode(resource.id()),
+            published_at: resource.published_at(),
+            episode_id,
+            steps: trace.steps.into_iter().map(MatchStepItem::from).collect(),
+            decision: trace.decision.into(),
+        }
+    }
+}
+
+/// 匹配检查步骤
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum MatchStepItem {
+    /// 合集资源是否被订阅接受
+    Collection { accepted: bool },
+    /// 发布时间是否处于匹配时间窗口，合集不限制结束时间
+    TimeWindow {
+        published_at: i64,
+        start: i64,
+        end: Option<i64>,
+        passed: bool,
+    },
+    /// 规则匹配结果，未绑定规则时为空间内首个命中的规则
+    Rule {
+        matched: bool,
+        rule_id: Option<i64>,
+        rule_order: Option<i64>,
+    },
+    /// 命中的订阅关键字
+    Keyword { matched: Option<String> },
+    /// 与当前候选规则比较，`replaced` 表示新规则优先级更高并替换了候选规则
+    Candidate {
+        rule_id: Option<i64>,
+        rule_order: Option<i64>,
+        replaced: bool,
+    },
+}
+
+impl From<MatchStep> for MatchStepItem {
+    fn from(step: MatchStep) -> Self {
+        match step {
+            MatchStep::Collection { accepted } => Self::Collection { accepted },
+            MatchStep::TimeWindow {
+                published_at,
+                start,
+                end,
+                passed,
+            } => Self::TimeWindow {
+                published_at,
+                start,
+                end,
+                passed,
+            },
+            MatchStep::Rule(result) => Self::Rule {
+                matched: result.matched,
+                rule_id: result.matched.then_some(result.rule_id),
+                rule_order: result.matched.then_some(result.rule_order),
+            },
+            MatchStep::Keyword { matched } => Self::Keyword { matched },
+            MatchStep::Candidate {
+                rule_id,
+                rule_order,
+                replaced,
+            } => Self::Candidate {
+                rule_id,
+                rule_order,
+                replaced,
+            },
+        }
+    }
+}
+
+/// 匹配结论
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum MatchDecisionItem {
+    /// 资源被选中
+    Matched,
+    /// 资源已被选中
+    AlreadyMatched,
+    /// 订阅未开启合集下载
+    CollectionRejected,
+    /// 发布时间不在匹配时间窗口内
+    OutOfTimeRange,
+    /// 没有规则命中
+    RuleNotMatched,
+    /// 标题未命中订阅关键字
+    KeywordNotMatched,
+    /// 命中的规则与订阅绑定的规则不一致
+    BoundRuleConflict { bound_rule_id: i64, rule_id: i64 },
+    /// 已有优先级更高或同级的候选规则，资源被忽略
+    CandidateKept { candidate_rule_id: i64 },
+}
+
+impl From<MatchDecision> for MatchDecisionItem {
+    fn from(decision: MatchDecision) -> Self {
+        match decision {
+            MatchDecision::Matched => Self::Matched,
+            MatchDecision::AlreadyMatched => Self::AlreadyMatched,
+            MatchDecision::CollectionRejected => Self::CollectionRejected,
+            MatchDecision::OutOfTimeRange => Self::OutOfTimeRange,
+            MatchDecision::RuleNotMatched => Self::RuleNotMatched,
+            MatchDecision::KeywordNotMatched => Self::KeywordNotMatched,
+            MatchDecision::BoundRuleConflict {
+                bound_rule_id,
+                rule_id,
+            } => Self::BoundRuleConflict {
+                bound_rule_id,
+                rule_id,
+            },
+            MatchDecision::CandidateKept { candidate_rule_id } => {
+                Self::CandidateKept { candidate_rule_id }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct PageAnimeRequest {
     pub page: Option<usize>,
@@ -170,6 +326,45 @@ pub struct AnimeSubInfo {
     pub progress: u32,
     pub rule_id: Option<i64>,
     pub rule_name: Option<String>,
+    /// 是否接受合集资源补全缺失剧集
+    pub accept_collection: bool,
+    /// 资源标题中集数的编号方式
+    pub numbering: EpisodeNumberingItem,
+    /// 本季之前的累计集数
+    pub episode_offset: u32,
+}
+
+/// 资源标题中集数的编号方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EpisodeNumberingItem {
+    /// 按本季从 1 开始编号
+    #[default]
+    Relative,
+    /// 按全系列累计编号，减去偏移量得到本季集数
+    Absolute,
+    /// 集数超过本季总集数时视为累计编号
+    Auto,
+}
+
+impl From<EpisodeNumbering> for EpisodeNumberingItem {
+    fn from(numbering: EpisodeNumbering) -> Self {
+        match numbering {
+            EpisodeNumbering::Relative => Self::Relative,
+            EpisodeNumbering::Absolute => Self::Absolute,
+            EpisodeNumbering::Auto => Self::Auto,
+        }
+    }
+}
+
+impl From<EpisodeNumberingItem> for EpisodeNumbering {
+    fn from(numbering: EpisodeNumberingItem) -> Self {
+        match numbering {
+            EpisodeNumberingItem::Relative => Self::Relative,
+            EpisodeNumberingItem::Absolute => Self::Absolute,
+            EpisodeNumberingItem::Auto => Self::Auto,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -229,6 +424,22 @@ pub struct SearchStatusRequest {
     pub enable: bool,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcceptCollectionRequest {
+    /// 是否接受合集资源
+    pub enable: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EpisodeNumberingRequest {
+    /// 资源标题中集数的编号方式
+    pub numbering: EpisodeNumberingItem,
+    /// 本季之前的累计集数，如第二季从第 13 集开始时为 12
+    #[serde(default)]
+    #[schema(example = 12)]
+    pub episode_offset: u32,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     /// 用户标识
@@ -286,8 +497,45 @@ pub struct FeedItemRequest {
     #[schema(example = "https://example.com")]
     pub site_url: Option<String>,
     /// RSS搜索地址
-    #[schema(example = "https://example.com?keyword={}")]
+    #[schema(example = "https://example.com?keyword={keyword}")]
     pub search_url: Option<String>,
+    /// 解析器名称，可选 default、mikan、nyaa、torznab，为空时根据地址自动识别
+    #[schema(example = "mikan")]
+    pub parser: Option<String>,
+    /// 轮询间隔（秒），为空时使用默认的 300 秒
+    #[schema(example = 600)]
+    pub poll_interval: Option<i64>,
+    /// 对该站点两次请求之间的最小间隔（秒），轮询与搜索共同遵守
+    #[schema(example = 60)]
+    pub min_spacing: Option<i64>,
+    /// 代理、请求头及 Cookie 设置，编辑时为空表示保持原有设置
+    pub request_options: Option<FeedRequestOptionsItem>,
+}
+
+/// Feed 请求选项，加密保存，同时作用于 RSS 及种子下载
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct FeedRequestOptionsItem {
+    /// 代理地址，支持 http、https、socks5
+    #[schema(example = "socks5://127.0.0.1:1080")]
+    pub proxy: Option<String>,
+    /// 额外请求头
+    #[serde(default)]
+    #[schema(example = json!({"Referer": "https://example.com"}))]
+    pub headers: BTreeMap<String, String>,
+    /// Cookie 请求头内容
+    #[schema(example = "uid=1; pass=abcdef")]
+    pub cookies: Option<String>,
+}
+
+impl From<FeedRequestOptionsItem> for FeedRequestOptions {
+    fn from(value: FeedRequestOptionsItem) -> Self {
+        let non_empty = |v: Option<String>| v.filter(|v| !v.trim().is_empty());
+        Self {
+            proxy: non_empty(value.proxy),
+            headers: value.headers,
+            cookies: non_empty(value.cookies),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -304,6 +552,225 @@ pub struct FeedItem {
     /// RSS搜索地址
     #[schema(example = "https://example.com?keyword={}")]
     pub search_url: Option<String>,
+    /// 解析器名称，为空时根据地址自动识别
+    #[schema(example = "mikan")]
+    pub parser: Option<String>,
+    /// 轮询间隔（秒），为空时使用默认值
+    #[schema(example = 600)]
+    pub poll_interval: Option<i64>,
+    /// 两次请求之间的最小间隔（秒）
+    #[schema(example = 60)]
+    pub min_spacing: Option<i64>,
+    /// 是否设置了代理、请求头或 Cookie，具体内容不对外返回
+    #[schema(example = false)]
+    pub has_request_options: bool,
+}
+
+/// Feed 试运行请求
+///
+/// 传入 `feed_id` 时使用已保存的 feed 配置，否则使用 `url` 及其余字段
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewRequest {
+    /// 已保存的 feed ID
+    #[schema(example = 1)]
+    pub feed_id: Option<i64>,
+    /// 搜索关键字，配合 `feed_id` 使用时请求该 feed 的搜索地址
+    #[schema(example = "败犬女主太多了")]
+    pub keyword: Option<String>,
+    /// 未传入 `feed_id` 时请求的地址
+    #[schema(example = "https://mikanani.me/RSS/Classic")]
+    pub url: Option<String>,
+    /// 解析器名称，为空时根据地址自动识别
+    #[schema(example = "mikan")]
+    pub parser: Option<String>,
+    /// 代理、请求头及 Cookie 设置
+    pub request_options: Option<FeedRequestOptionsItem>,
+}
+
+/// Feed 试运行结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewResponse {
+    /// 订阅源唯一标识
+    #[schema(example = "9f1c2d...")]
+    pub source_key: String,
+    /// 条目列表，包含被跳过的条目
+    pub items: Vec<FeedPreviewItem>,
+}
+
+/// Feed 试运行的单个条目
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewItem {
+    /// 标题
+    #[schema(example = "[Group] Title - 01 [1080p]")]
+    pub title: Option<String>,
+    /// 条目页面地址
+    #[schema(example = "https://example.com/view/1")]
+    pub source_url: Option<String>,
+    /// 种子或磁力地址
+    #[schema(example = "https://example.com/download/1.torrent")]
+    pub resource_url: Option<String>,
+    /// 解析出的 info hash（十六进制）
+    #[schema(example = "0123456789abcdef0123456789abcdef01234567")]
+    pub info_hash: Option<String>,
+    /// 发布时间戳（秒）
+    #[schema(example = 1736308800)]
+    pub published_at: Option<i64>,
+    /// 是否被识别为合集
+    #[schema(example = false)]
+    pub is_collection: bool,
+    /// 被识别为合集时命中的标题片段
+    #[schema(example = "[01-12]")]
+    pub collection_marker: Option<String>,
+    /// 合集起始集数
+    #[schema(example = 1)]
+    pub ep_start: Option<u32>,
+    /// 合集结束集数
+    #[schema(example = 12)]
+    pub ep_end: Option<u32>,
+    /// 种子内文件总大小（字节），磁力链接为空
+    #[schema(example = 1073741824)]
+    pub total_size: Option<u64>,
+    /// 条目被跳过的原因，为空表示解析成功
+    #[schema(example = "missing resource url")]
+    pub skipped_reason: Option<String>,
+    /// 调用者空间内命中标题的规则
+    pub matched_rules: Vec<FeedPreviewRule>,
+    /// 会匹配到该条目的订阅
+    pub matched_subscriptions: Vec<FeedPreviewSubscription>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewRule {
+    /// 规则 ID
+    #[schema(example = 1)]
+    pub id: i64,
+    /// 规则名称
+    #[schema(example = "1080p 简体")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewSubscription {
+    /// 订阅 ID
+    #[schema(example = 1)]
+    pub sub_anime_id: i64,
+    /// 番剧 ID
+    #[schema(example = 1)]
+    pub anime_id: i64,
+    /// 匹配采用的规则 ID
+    #[schema(example = 1)]
+    pub rule_id: Option<i64>,
+}
+
+/// 推送资源请求
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPushRequest {
+    /// 推送的条目列表
+    pub items: Vec<FeedPushItem>,
+}
+
+/// 推送的单个资源条目
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPushItem {
+    /// 标题
+    #[schema(example = "[Group] Title - 01 [1080p]")]
+    pub title: String,
+    /// 磁力链接或种子地址
+    #[schema(example = "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567")]
+    pub resource_url: String,
+    /// 条目页面地址，为空时使用资源地址
+    #[schema(example = "https://example.com/view/1")]
+    pub source_url: Option<String>,
+    /// 发布时间戳（秒），为空时使用当前时间
+    #[schema(example = 1736308800)]
+    pub published_at: Option<i64>,
+    /// info hash（十六进制），v1 为 40 位，v2 为 64 位
+    #[schema(example = "0123456789abcdef0123456789abcdef01234567")]
+    pub info_hash: Option<String>,
+    /// 资源大小（字节）
+    #[schema(example = 1073741824)]
+    pub size: Option<u64>,
+}
+
+impl From<FeedPushItem> for PushedItem {
+    fn from(value: FeedPushItem) -> Self {
+        Self {
+            title: value.title,
+            resource_url: value.resource_url,
+            source_url: value.source_url,
+            published_at: value.published_at,
+            info_hash: value.info_hash,
+            size: value.size,
+        }
+    }
+}
+
+/// 推送的单个条目的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPushResult {
+    /// 标题
+    #[schema(example = "[Group] Title - 01 [1080p]")]
+    pub title: Option<String>,
+    /// 条目页面地址
+    #[schema(example = "https://example.com/view/1")]
+    pub source_url: Option<String>,
+    /// 解析出的 info hash（十六进制）
+    #[schema(example = "0123456789abcdef0123456789abcdef01234567")]
+    pub info_hash: Option<String>,
+    /// 是否被识别为合集
+    #[schema(example = false)]
+    pub is_collection: bool,
+    /// 是否为新入库的资源，已存在的资源不会再次匹配
+    #[schema(example = true)]
+    pub created: bool,
+    /// 是否需下载种子而转入后台处理，处理结果不在本次响应中
+    #[schema(example = false)]
+    pub queued: bool,
+    /// 条目被跳过的原因，为空表示解析成功
+    #[schema(example = "invalid info hash")]
+    pub skipped_reason: Option<String>,
+    /// 匹配到该资源的订阅
+    pub matched_subscriptions: Vec<FeedPreviewSubscription>,
+}
+
+/// OPML 导入的单个条目结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedImportItem {
+    /// 名字
+    #[schema(example = "mikan")]
+    pub title: String,
+    /// RSS地址
+    #[schema(example = "https://mikanani.me/RSS/Classic")]
+    pub site_url: Option<String>,
+    /// RSS搜索地址
+    #[schema(example = "https://mikanani.me/RSS/Search?searchstr={}")]
+    pub search_url: Option<String>,
+    /// 解析器名称
+    #[schema(example = "mikan")]
+    pub parser: Option<String>,
+    /// 导入成功时为新建的订阅源 ID
+    #[schema(example = 1)]
+    pub feed_id: Option<i64>,
+    /// 导入失败的原因，为空表示成功
+    #[schema(example = "feed already exists")]
+    pub error: Option<String>,
+}
+
+impl From<FeedImportResult> for FeedImportItem {
+    fn from(value: FeedImportResult) -> Self {
+        let (feed_id, error) = match value.result {
+            Ok(id) => (Some(id), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            title: value.outline.title,
+            site_url: value.outline.site_url,
+            search_url: value.outline.search_url,
+            parser: value.outline.parser,
+            feed_id,
+            error,
+        }
+    }
 }
 
 impl From<FeedEntity> for FeedItem {
@@ -313,6 +780,10 @@ impl From<FeedEntity> for FeedItem {
             title: value.title().to_string(),
             site_url: value.site_url().map(String::from),
             search_url: value.search_url().map(String::from),
+            parser: value.parser().map(String::from),
+            poll_interval: value.schedule().poll_interval,
+            min_spacing: value.schedule().min_spacing,
+            has_request_options: value.has_request_options(),
         }
     }
 }
@@ -339,6 +810,9 @@ pub struct DownloadSettings<T> {
     pub name: String,
     pub active: bool,
     pub base_path: String,
+    /// 修正版（如 `05v2`）下载完成后删除被替换的旧任务及其文件
+    #[serde(default)]
+    pub remove_superseded: bool,
     pub config: T,
 }
 
@@ -393,6 +867,7 @@ impl From<DownloadConfig<QbitConfig>> for DownloadSettings<QbitSettings> {
             name: config.name,
             active: config.active,
             base_path: config.base_path,
+            remove_superseded: config.remove_superseded,
             config: config.config.into(),
         }
     }
@@ -404,6 +879,7 @@ impl From<DownloadConfig<DefaultDownloaderConfig>> for DownloadSettings<DefaultD
             name: config.name,
             active: config.active,
             base_path: config.base_path,
+            remove_superseded: config.remove_superseded,
             config: config.config.into(),
         }
     }
@@ -415,6 +891,7 @@ impl From<DownloadSettings<QbitSettings>> for DownloadConfig<QbitConfig> {
             name: settings.name,
             active: settings.active,
             base_path: settings.base_path,
+            remove_superseded: settings.remove_superseded,
             config: settings.config.into(),
         }
     }
@@ -426,6 +903,7 @@ impl From<DownloadSettings<DefaultDownloaderSettings>> for DownloadConfig<Defaul
             name: settings.name,
             active: settings.active,
             base_path: settings.base_path,
+            remove_superseded: settings.remove_superseded,
             config: settings.config.into(),
         }
     }
@@ -460,14 +938,193 @@ pub struct RuleCreateRequest {
     /// 规则名称
     #[schema(example = "规则1")]
     pub name: String,
-    /// 匹配正则表达式
+    /// 规则类型，为空时为正则规则
+    pub kind: Option<RuleKindItem>,
+    /// 匹配正则表达式，正则规则必填
     #[schema(example = ".*")]
-    pub pattern: String,
+    pub pattern: Option<String>,
+    /// 结构化规则的条件，结构化规则必填
+    pub conditions: Option<StructuredRuleItem>,
+    /// 优先级序号，值越小优先级越高
+    #[schema(example = 10)]
+    pub order: i64,
+}
+
+/// 规则类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKindItem {
+    /// 正则表达式匹配资源标题
+    #[default]
+    Regex,
+    /// 按关键字、发布组、分辨率、字幕语言及大小等条件匹配
+    Structured,
+}
+
+impl From<RuleKind> for RuleKindItem {
+    fn from(value: RuleKind) -> Self {
+        match value {
+            RuleKind::Regex => Self::Regex,
+            RuleKind::Structured => Self::Structured,
+        }
+    }
+}
+
+/// 结构化规则条件，所有非空条件同时满足时命中
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct StructuredRuleItem {
+    /// 标题须包含全部关键字，不区分大小写及全半角
+    #[schema(example = json!(["败犬女主"]))]
+    pub include: Vec<String>,
+    /// 标题包含任一关键字时不命中
+    #[schema(example = json!(["繁體", "HEVC"]))]
+    pub exclude: Vec<String>,
+    /// 发布组，须为其中之一
+    #[schema(example = json!(["ANi"]))]
+    pub groups: Vec<String>,
+    /// 纵向分辨率，须为其中之一
+    #[schema(example = json!([1080]))]
+    pub resolutions: Vec<u32>,
+    /// 字幕语言，须包含其中之一：chs、cht、jpn、eng
+    #[schema(example = json!(["chs"]))]
+    pub subtitle_langs: Vec<String>,
+    /// 资源大小下限（字节），大小未知的资源不受限制
+    pub min_size: Option<u64>,
+    /// 资源大小上限（字节），大小未知的资源不受限制
+    #[schema(example = 2147483648u64)]
+    pub max_size: Option<u64>,
+}
+
+impl TryFrom<StructuredRuleItem> for StructuredRule {
+    type Error = String;
+
+    fn try_from(value: StructuredRuleItem) -> Result<Self, Self::Error> {
+        let subtitle_langs = value
+            .subtitle_langs
+            .iter()
+            .map(|code| {
+                SubtitleLang::from_code(code)
+                    .ok_or_else(|| format!("unknown subtitle lang {}", code))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            include: value.include,
+            exclude: value.exclude,
+            groups: value.groups,
+            resolutions: value.resolutions,
+            subtitle_langs,
+            min_size: value.min_size,
+            max_size: value.max_size,
+        })
+    }
+}
+
+impl From<StructuredRule> for StructuredRuleItem {
+    fn from(value: StructuredRule) -> Self {
+        Self {
+            include: value.include,
+            exclude: value.exclude,
+            groups: value.groups,
+            resolutions: value.resolutions,
+            subtitle_langs: value
+                .subtitle_langs
+                .iter()
+                .map(|l| l.as_str().to_string())
+                .collect(),
+            min_size: value.min_size,
+            max_size: value.max_size,
+        }
+    }
+}
+
+/// 规则试运行请求
+///
+/// 传入 `rule_id` 时测试已保存的规则，否则使用 `kind`、`pattern`、`conditions` 及 `order`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuleTestRequest {
+    /// 已保存的规则 ID
+    #[schema(example = 1)]
+    pub rule_id: Option<i64>,
+    /// 规则类型，为空时为正则规则
+    pub kind: Option<RuleKindItem>,
+    /// 匹配正则表达式
+    #[schema(example = ".*1080.*")]
+    pub pattern: Option<String>,
+    /// 结构化规则的条件
+    pub conditions: Option<StructuredRuleItem>,
+    /// 未保存规则的优先级序号，为空时排在所有规则之后
+    #[schema(example = 10)]
+    pub order: Option<i64>,
+    /// 订阅 ID，传入时只检索该订阅的番剧标题及匹配时间范围内的资源
+    #[schema(example = 1)]
+    pub sub_anime_id: Option<i64>,
+    /// 开始时间戳（秒）
+    #[schema(example = 1736308800)]
+    pub start_at: Option<i64>,
+    /// 结束时间戳（秒）
+    #[schema(example = 1738987200)]
+    pub end_at: Option<i64>,
+    /// 检索的资源数量上限，默认 500，最大 5000
+    #[schema(example = 500)]
+    pub limit: Option<i32>,
+}
+
+/// 规则试运行结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuleTestResponse {
+    /// 检索的资源数量
+    #[schema(example = 500)]
+    pub scanned: usize,
+    /// 规则命中的资源，按发布时间倒序
+    pub items: Vec<RuleTestItem>,
+}
+
+/// 规则命中的单个资源
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuleTestItem {
+    /// 标题
+    #[schema(example = "[Group] Title - 01 [1080p]")]
+    pub title: String,
+    /// info hash（十六进制）
+    #[schema(example = "0123456789abcdef0123456789abcdef01234567")]
+    pub info_hash: String,
+    /// 发布时间戳（秒）
+    #[schema(example = 1736308800)]
+    pub published_at: i64,
+    /// 标题命中番剧关键字的已启用订阅
+    pub subscriptions: Vec<RuleTestSubscription>,
+    /// 优先级更高且同样命中的规则，按空间规则匹配时将由该规则选中
+    pub winning_rule: Option<RuleTestWinner>,
+}
+
+/// 按空间规则顺序排在被测规则之前且同样命中的规则
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuleTestWinner {
+    /// 规则 ID
+    #[schema(example = 1)]
+    pub id: i64,
+    /// 规则名称
+    #[schema(example = "1080p 简体")]
+    pub name: String,
     /// 优先级序号，值越小优先级越高
     #[schema(example = 10)]
     pub order: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RuleTestSubscription {
+    /// 订阅 ID
+    #[schema(example = 1)]
+    pub sub_anime_id: i64,
+    /// 番剧 ID
+    #[schema(example = 1)]
+    pub anime_id: i64,
+    /// 订阅绑定的规则 ID，为空时按空间规则匹配
+    #[schema(example = 1)]
+    pub rule_id: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RuleUpdateOrderRequest {
     /// 优先级序号，值越小优先级越高
@@ -480,7 +1137,11 @@ pub struct RuleItem {
     pub id: i64,
     pub name: String,
     pub order: i64,
+    pub kind: RuleKindItem,
+    /// 正则表达式，结构化规则为条件的 JSON
     pub pattern: String,
+    /// 结构化规则的条件
+    pub conditions: Option<StructuredRuleItem>,
 }
 
 impl From<RuleEntity> for RuleItem {
@@ -489,7 +1150,9 @@ impl From<RuleEntity> for RuleItem {
             id: value.id(),
             name: value.name().to_string(),
             order: value.order(),
+            kind: value.kind().into(),
             pattern: value.pattern().to_string(),
+            conditions: value.conditions().map(Into::into),
         }
     }
 }
@@ -499,12 +1162,18 @@ pub struct EpisodeItem {
     pub id: i64,
     pub title: String,
     pub url: String,
-    /// 剧集状态: 0=未下载(Pending), 1=已完成(Downloaded)
+    /// 剧集状态: 0=未下载(Pending), 1=已完成(Downloaded), 2=已被修正版替换(Superseded)
     #[schema(example = 0)]
     pub status: i32,
-    /// 剧集集数
+    /// 剧集集数，合集资源为起始集数
     #[schema(example = 1.0)]
     pub ep_num: Option<f64>,
+    /// 合集资源覆盖的最后一集，单集资源为空
+    #[schema(example = 12.0)]
+    pub ep_end: Option<f64>,
+    /// 最早提供该资源的订阅源 ID，来源未记录时为空
+    #[schema(example = 1)]
+    pub feed_id: Option<i64>,
 }
 
 impl From<EpsiodeEntity> for EpisodeItem {
@@ -515,6 +1184,8 @@ impl From<EpsiodeEntity> for EpisodeItem {
             url: value.url().to_string(),
             status: value.status().into(),
             ep_num: value.ep_num(),
+            ep_end: value.ep_end(),
+            feed_id: value.feed_id(),
         }
     }
 }
@@ -935,6 +1606,39 @@ pub struct BackoffFeed {
     pub backoff_until: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedHealth {
+    /// 订阅源ID
+    pub feed_id: i64,
+    /// 订阅源名称
+    pub feed_name: String,
+    /// 最近请求记录中的成功率（0~1），无请求记录时为空
+    #[schema(example = 0.95)]
+    pub success_rate: Option<f64>,
+    /// 最近请求记录的总数
+    pub total_count: i64,
+    /// 当前连续失败次数
+    pub failure_count: i64,
+    /// 最近一次失败的错误信息
+    pub last_error: Option<String>,
+    /// 最近一次失败时间（Unix时间戳，秒级）
+    pub last_error_at: Option<i64>,
+    /// 最近一次成功时间（Unix时间戳，秒级）
+    pub last_success_at: Option<i64>,
+    /// 允许再次请求的时间（Unix时间戳，秒级），0 表示不受限
+    pub next_allowed_at: i64,
+    /// 最近的错误记录，按时间倒序
+    pub recent_errors: Vec<FeedErrorRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedErrorRecord {
+    /// 错误信息
+    pub error: String,
+    /// 发生时间（Unix时间戳，秒级）
+    pub occurred_at: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SystemStatResponse {
     /// 系统已入库的番剧总数
@@ -945,6 +1649,8 @@ pub struct SystemStatResponse {
     pub waiting_mandates_count: i64,
     /// 由于请求失败过多，当前正处于退避等待期的订阅源列表
     pub backoff_feeds: Vec<BackoffFeed>,
+    /// 各订阅源的请求健康状况
+    pub feed_health: Vec<FeedHealth>,
     /// 分季度的番剧统计与订阅进度列表
     pub quarter_stats: Vec<QuarterStat>,
 }
@@ -3,6 +3,7 @@ use sqlx::{Pool, Row, Sqlite};
 
 use crate::model::{AnimeResponse, AnimeSubInfo, Page, PageAnimeRequest, RecentEpisodeResponse};
 use anime::entity::model::AnimeLangTarget;
+use subscription::entity::model::{EpisodeNumbering, EpsiodeStatus};
 
 #[derive(Clone)]
 pub struct AnimeViewQuery {
@@ -144,6 +145,9 @@ impl AnimeViewQuery {
                 sa.search_status,
                 sa.progress,
                 sa.rule_id,
+                sa.accept_collection,
+                sa.numbering,
+                sa.episode_offset,
                 r.name AS rule_name,
                 (SELECT name FROM anime_title t WHERE t.anime_id = p.id AND t.is_origin = 1 LIMIT 1) AS origin_name,
                 ",
@@ -209,6 +213,11 @@ impl AnimeViewQuery {
                 progress: row.get::<i32, _>("progress") as u32,
                 rule_id: row.get::<Option<i64>, _>("rule_id"),
                 rule_name: row.get::<Option<String>, _>("rule_name"),
+                accept_collection: row.get::<bool, _>("accept_collection"),
+                numbering: EpisodeNumbering::try_from(row.get::<i32, _>("numbering"))
+                    .unwrap_or_default()
+                    .into(),
+                episode_offset: row.get::<i64, _>("episode_offset") as u32,
             });
 
             data.push(AnimeResponse {
@@ -259,8 +268,10 @@ impl AnimeViewQuery {
             "FROM sub_anime_episode e
             JOIN sub_anime sa ON sa.id = e.sub_anime_id
             LEFT JOIN rule r ON r.id = sa.rule_id
-            WHERE sa.space_id = ",
+            WHERE e.status != ",
         );
+        qb.push_bind(i32::from(EpsiodeStatus::Superseded));
+        qb.push(" AND sa.space_id = ");
         qb.push_bind(space_id);
         qb.push(" ORDER BY e.updated_at DESC LIMIT 10");
 
@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Row, Sqlite};
+
+/// 每个订阅源返回的最近错误条数
+const RECENT_ERROR_LIMIT: i64 = 5;
 
 #[derive(Clone)]
 pub struct StatQuery {
@@ -74,7 +79,6 @@ impl StatQuery {
         .await?;
 
         let mut quarter_stats = Vec::new();
-        use sqlx::Row;
         for row in quarter_rows {
             quarter_stats.push(crate::model::QuarterStat {
                 quarter: row.get::<i32, _>("air_quarter") as u32,
@@ -99,7 +103,7 @@ impl StatQuery {
             }
             separated.push_unseparated(")");
             let rows = qb.build().fetch_all(&self.pool).await?;
-            let mut name_map = std::collections::HashMap::new();
+            let mut name_map = HashMap::new();
             for row in rows {
                 let id: i64 = row.get("id");
                 let title: String = row.get("title");
@@ -116,12 +120,87 @@ impl StatQuery {
             }
         }
 
+        let feed_health = self.get_feed_health().await?;
+
         Ok(crate::model::SystemStatResponse {
             total_anime_count,
             user_subscribed_count,
             waiting_mandates_count,
             backoff_feeds,
+            feed_health,
             quarter_stats,
         })
     }
+
+    async fn get_feed_health(&self) -> Result<Vec<crate::model::FeedHealth>> {
+        let error_rows = sqlx::query(
+            "
+            SELECT feed_id, error, created_at FROM (
+                SELECT
+                    feed_id,
+                    error,
+                    created_at,
+                    ROW_NUMBER() OVER(PARTITION BY feed_id ORDER BY id DESC) AS rn
+                FROM feed_fetch_log
+                WHERE success = 0
+            )
+            WHERE rn <= ?
+            ORDER BY feed_id, created_at DESC
+            ",
+        )
+        .bind(RECENT_ERROR_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut errors_map: HashMap<i64, Vec<crate::model::FeedErrorRecord>> = HashMap::new();
+        for row in error_rows {
+            errors_map
+                .entry(row.get::<i64, _>("feed_id"))
+                .or_default()
+                .push(crate::model::FeedErrorRecord {
+                    error: row.get::<Option<String>, _>("error").unwrap_or_default(),
+                    occurred_at: row.get::<i64, _>("created_at"),
+                });
+        }
+
+        let rows = sqlx::query(
+            "
+            SELECT
+                f.id,
+                f.title,
+                COALESCE(h.retries, 0) AS retries,
+                COALESCE(h.next_allowed_at, 0) AS next_allowed_at,
+                h.last_error,
+                h.last_error_at,
+                h.last_success_at,
+                (SELECT COUNT(*) FROM feed_fetch_log l WHERE l.feed_id = f.id) AS total_count,
+                (SELECT COUNT(*) FROM feed_fetch_log l WHERE l.feed_id = f.id AND l.success = 1) AS success_count
+            FROM feed f
+            LEFT JOIN feed_health h ON h.feed_id = f.id
+            ORDER BY f.id
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let feed_id: i64 = row.get("id");
+            let total_count: i64 = row.get("total_count");
+            let success_count: i64 = row.get("success_count");
+            result.push(crate::model::FeedHealth {
+                feed_id,
+                feed_name: row.get("title"),
+                success_rate: (total_count > 0).then(|| success_count as f64 / total_count as f64),
+                total_count,
+                failure_count: row.get("retries"),
+                last_error: row.get("last_error"),
+                last_error_at: row.get("last_error_at"),
+                last_success_at: row.get("last_success_at"),
+                next_allowed_at: row.get("next_allowed_at"),
+                recent_errors: errors_map.remove(&feed_id).unwrap_or_default(),
+            });
+        }
+        Ok(result)
+    }
 }
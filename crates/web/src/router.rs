@@ -14,16 +14,26 @@ use utoipa_swagger_ui::SwaggerUi;
 use crate::{
     app_ctx::AppContext,
     handler::{anime, downloader, feed, rule, stat, subscription, user},
-    middleware::auth::{require_admin, require_auth},
+    middleware::auth::{require_admin, require_auth, require_push_auth},
 };
 
 pub fn route(ctx: Arc<AppContext>) -> Router {
     let admin = Router::new()
         .route("/feed", post(feed::add))
+        .route("/feed/preview", post(feed::preview))
+        .route("/feed/opml", get(feed::export_opml).post(feed::import_opml))
         .route("/feed/{feed_id}", delete(feed::delete).put(feed::edit))
         .route("/system/log-level", put(stat::set_log_level))
         .layer(middleware::from_fn(require_admin));
 
+    // 推送接口供外部爬虫调用，接受推送令牌，不要求登录
+    let push = Router::new()
+        .route("/feed/{feed_id}/push", post(feed::push))
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            require_push_auth,
+        ));
+
     let auth = Router::new()
         .route("/anime", post(anime::list))
         .route("/anime/search", get(anime::search))
@@ -32,6 +42,7 @@ pub fn route(ctx: Arc<AppContext>) -> Router {
         .route("/anime/{anime_id}", put(anime::edit))
         .route("/feed", get(feed::list))
         .route("/rule", post(rule::add).get(rule::list))
+        .route("/rule/test", post(rule::test))
         .route("/rule/{rule_id}", put(rule::edit).delete(rule::delete))
         .route("/subscription", post(subscription::add))
         .route("/subscription/recent", get(subscription::recent_episodes))
@@ -41,10 +52,22 @@ pub fn route(ctx: Arc<AppContext>) -> Router {
             "/subscription/{id}/search_status",
             post(subscription::set_search_status),
         )
+        .route(
+            "/subscription/{id}/accept_collection",
+            post(subscription::set_accept_collection),
+        )
+        .route(
+            "/subscription/{id}/numbering",
+            post(subscription::set_numbering),
+        )
         .route(
             "/subscription/{id}/bind_rule",
             post(subscription::bind_rule),
         )
+        .route(
+            "/subscription/{id}/explain/{info_hash}",
+            get(subscription::explain_match),
+        )
         .route("/subscription/{id}/eps", put(subscription::reset_all_eps))
         .route(
             "/subscription/{id}/eps/{ep_id}",
@@ -79,6 +102,7 @@ pub fn route(ctx: Arc<AppContext>) -> Router {
     let api = Router::new()
         .route("/user/login", post(user::login))
         .merge(auth)
+        .merge(push)
         .fallback(api_not_found);
 
     Router::new()
@@ -105,16 +129,24 @@ async fn api_not_found() -> impl IntoResponse {
         feed::list,
         feed::delete,
         feed::edit,
+        feed::preview,
+        feed::export_opml,
+        feed::import_opml,
+        feed::push,
         rule::add,
         rule::list,
         rule::edit,
         rule::delete,
+        rule::test,
         subscription::add,
         subscription::delete,
         subscription::list_eps,
         subscription::recent_episodes,
         subscription::set_search_status,
+        subscription::set_accept_collection,
+        subscription::set_numbering,
         subscription::bind_rule,
+        subscription::explain_match,
         subscription::reset_all_eps,
         subscription::update_ep_status,
         user::list_download_config,
@@ -143,7 +175,13 @@ async fn api_not_found() -> impl IntoResponse {
             crate::model::RecentEpisodeResponse,
             crate::model::RecentEpisodeQuery,
             crate::model::SearchStatusRequest,
+            crate::model::AcceptCollectionRequest,
+            crate::model::EpisodeNumberingRequest,
+            crate::model::EpisodeNumberingItem,
             crate::model::BindRuleRequest,
+            crate::model::MatchExplainResponse,
+            crate::model::MatchStepItem,
+            crate::model::MatchDecisionItem,
             crate::model::EditAnimeRequest,
             crate::model::PageAnimeRequest,
             crate::error::ErrorResponse,
@@ -153,8 +191,20 @@ async fn api_not_found() -> impl IntoResponse {
             crate::model::SystemStatResponse,
             crate::model::QuarterStat,
             crate::model::BackoffFeed,
+            crate::model::FeedHealth,
+            crate::model::FeedErrorRecord,
             crate::model::FeedItemRequest,
+            crate::model::FeedRequestOptionsItem,
+            crate::model::FeedPreviewRequest,
+            crate::model::FeedPreviewResponse,
+            crate::model::FeedPreviewItem,
+            crate::model::FeedPreviewRule,
+            crate::model::FeedPreviewSubscription,
             crate::model::FeedItem,
+            crate::model::FeedImportItem,
+            crate::model::FeedPushRequest,
+            crate::model::FeedPushItem,
+            crate::model::FeedPushResult,
             crate::model::QbitSettings,
             crate::model::DefaultDownloaderSettings,
             crate::model::DownloaderSettings,
@@ -162,6 +212,13 @@ async fn api_not_found() -> impl IntoResponse {
             crate::model::DownloadTaskActionRequest,
 
             crate::model::RuleCreateRequest,
+            crate::model::RuleKindItem,
+            crate::model::RuleTestRequest,
+            crate::model::RuleTestResponse,
+            crate::model::RuleTestItem,
+            crate::model::RuleTestSubscription,
+            crate::model::RuleTestWinner,
+            crate::model::StructuredRuleItem,
             crate::model::RuleUpdateOrderRequest,
             crate::model::RuleItem,
             crate::model::EpisodeItem,
@@ -196,6 +253,14 @@ impl utoipa::Modify for SecurityAddon {
                     ),
                 ),
             );
+            components.add_security_scheme(
+                "push_token",
+                utoipa::openapi::security::SecurityScheme::Http(
+                    utoipa::openapi::security::Http::new(
+                        utoipa::openapi::security::HttpAuthScheme::Bearer,
+                    ),
+                ),
+            );
         }
     }
 }